@@ -0,0 +1,487 @@
+//! Command line tool to poke a KMBox Net.
+//!
+//! The connection is read from `--config`, then the `KMBOX_IP`, `KMBOX_PORT` and `KMBOX_UUID`
+//! environment variables and last the `--ip`, `--port` and `--uuid` flags.
+
+use std::{
+    error::Error,
+    net::Ipv4Addr,
+    path::PathBuf,
+    process::ExitCode,
+    thread,
+    time::{Duration, Instant},
+};
+
+#[cfg(feature = "tui")]
+mod tui;
+
+use clap::{Parser, Subcommand, ValueEnum};
+use input_middleware::{
+    button_state::{ButtonState, MwheelState},
+    chord::Chord,
+    devices::kmbox_net::{CaptureReader, KMBoxNet, KMBoxNetConfig},
+    input_macro::{Macro, MacroEvent, MacroPlayer, MacroRecorder},
+    keyboard_layout::{DeLayout, FrLayout, UkLayout, UsLayout},
+    typing::TypingOptions,
+    InputDevice, InputMiddlewareDeviceAction,
+};
+
+#[derive(Debug, Parser)]
+#[command(
+    name = "kmbox",
+    version,
+    about = "Control a KMBox Net from the command line"
+)]
+struct Cli {
+    /// TOML or JSON config file of the device
+    #[arg(short, long, global = true)]
+    config: Option<PathBuf>,
+    /// ip address or hostname of the kmbox
+    #[arg(long, global = true)]
+    ip: Option<String>,
+    #[arg(long, global = true)]
+    port: Option<u16>,
+    /// 8 hex characters shown on the kmbox display
+    #[arg(long, global = true)]
+    uuid: Option<String>,
+    /// timeout of every request, e.g. `500ms`
+    #[arg(long, global = true, value_parser = parse_duration)]
+    timeout: Option<Duration>,
+    /// send the mouse and keyboard commands over the encrypted channel
+    #[arg(long, global = true)]
+    encrypted: bool,
+    /// write the packets exchanged with the kmbox to a pcap-ng file
+    #[arg(long, global = true)]
+    capture: Option<PathBuf>,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Connect once and report the time it took
+    Connect,
+    /// Connect repeatedly and report the round trip times
+    Ping {
+        #[arg(short = 'n', long, default_value_t = 4)]
+        count: u32,
+        #[arg(long, default_value = "1s", value_parser = parse_duration)]
+        interval: Duration,
+    },
+    /// Move the mouse relative to its position
+    #[command(allow_negative_numbers = true)]
+    Move { x: i32, y: i32 },
+    /// Click a mouse button
+    Click {
+        #[arg(value_enum)]
+        button: Button,
+        #[arg(value_enum, default_value_t = Action::Tap)]
+        action: Action,
+        /// how long a tap holds the button
+        #[arg(long, default_value = "50ms", value_parser = parse_duration)]
+        hold: Duration,
+    },
+    /// Press a key or chord like `ctrl+shift+esc`
+    Key {
+        chord: Chord,
+        #[arg(value_enum, default_value_t = Action::Tap)]
+        action: Action,
+        /// how long a tap holds the keys
+        #[arg(long, default_value = "50ms", value_parser = parse_duration)]
+        hold: Duration,
+    },
+    /// Type text on the layout of the target PC
+    Type {
+        text: String,
+        #[arg(long, value_enum, default_value_t = Layout::Us)]
+        layout: Layout,
+        /// pause between two keys
+        #[arg(long, default_value = "20ms", value_parser = parse_duration)]
+        delay: Duration,
+    },
+    /// Scroll, positive is up
+    #[command(allow_negative_numbers = true)]
+    Wheel { amount: i32 },
+    /// Reboot the kmbox
+    Reboot {
+        /// wait until the kmbox answers again
+        #[arg(long)]
+        wait: bool,
+    },
+    /// Print the input of the devices attached to the kmbox until interrupted
+    Monitor,
+    /// Enable the firmware debug output and print it until interrupted
+    DebugLog,
+    /// Show the live input of the attached devices, mask inputs and inject clicks
+    #[cfg(feature = "tui")]
+    Tui,
+    /// Change the address of the kmbox, it is used after a reboot
+    SetIp {
+        ip: Ipv4Addr,
+        /// new port, the current one is kept if not set
+        #[arg(long)]
+        new_port: Option<u16>,
+        /// reboot right away so the new address is used
+        #[arg(long)]
+        reboot: bool,
+        /// after the reboot wait until the kmbox answers on the new address
+        #[arg(long, requires = "reboot")]
+        wait: bool,
+    },
+    /// Play a macro file (binary or .json)
+    Replay {
+        file: PathBuf,
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+        /// how often the macro is played, 0 repeats until interrupted
+        #[arg(long, default_value_t = 1)]
+        loops: u32,
+    },
+    /// Read or resend a packet capture made with `--capture`
+    #[command(subcommand)]
+    Capture(CaptureCommand),
+}
+
+#[derive(Debug, Subcommand)]
+enum CaptureCommand {
+    /// Print the packets of a capture
+    Decode { file: PathBuf },
+    /// Resend the packets of a capture with their original timing
+    Replay {
+        file: PathBuf,
+        #[arg(long, default_value_t = 1.0)]
+        speed: f64,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Button {
+    Left,
+    Right,
+    Middle,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Action {
+    Tap,
+    Down,
+    Up,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Layout {
+    Us,
+    Uk,
+    De,
+    Fr,
+}
+
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let (number, unit) = value
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .map(|i| value.split_at(i))
+        .unwrap_or((value, "ms"));
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration {value:?}"))?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "s" => number,
+        _ => return Err(format!("unknown unit {unit:?} in {value:?}, use ms or s")),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())
+}
+
+impl Cli {
+    fn config(&self) -> Result<KMBoxNetConfig, Box<dyn Error>> {
+        // the file, then the environment, then the flags, validated once everything is set
+        let mut config = match &self.config {
+            Some(path) => match InputDevice::parse_config_file(path)? {
+                InputDevice::KMBoxNet(config) => config,
+            },
+            None => KMBoxNetConfig::default(),
+        }
+        .with_env_overrides()?;
+        if let Some(ip) = &self.ip {
+            config.ip = ip.clone();
+        }
+        if let Some(port) = self.port {
+            config.port = port;
+        }
+        if let Some(uuid) = &self.uuid {
+            config.uuid = uuid.clone();
+        }
+        if let Some(timeout) = self.timeout {
+            config.timeout = timeout;
+        }
+        if self.encrypted {
+            config.encrypted = true;
+        }
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn connect(&self) -> Result<KMBoxNet, Box<dyn Error>> {
+        let config = self.config()?;
+        let address = format!("{}:{}", config.ip, config.port);
+        let mut km =
+            KMBoxNet::new(config).map_err(|e| format!("failed to connect to {address}: {e}"))?;
+        if let Some(path) = &self.capture {
+            km.start_capture(path)
+                .map_err(|e| format!("failed to create {}: {e}", path.display()))?;
+        }
+        Ok(km)
+    }
+}
+
+fn press(
+    action: Action,
+    hold: Duration,
+    mut send: impl FnMut(ButtonState) -> Result<(), Box<dyn Error>>,
+) -> Result<(), Box<dyn Error>> {
+    match action {
+        Action::Down => send(ButtonState::Pressed),
+        Action::Up => send(ButtonState::Released),
+        Action::Tap => {
+            send(ButtonState::Pressed)?;
+            thread::sleep(hold);
+            send(ButtonState::Released)
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn run(cli: Cli) -> Result<(), Box<dyn Error>> {
+    match &cli.command {
+        Command::Connect => {
+            let begin = Instant::now();
+            let mut km = cli.connect()?;
+            println!("connected to {} in {:?}", km.socket_addr(), begin.elapsed());
+            println!("{}", km.device_info()?);
+        }
+        Command::Ping { count, interval } => {
+            let config = cli.config()?;
+            let mut times = Vec::new();
+            for seq in 0..*count {
+                if seq > 0 {
+                    thread::sleep(*interval);
+                }
+                let begin = Instant::now();
+                match KMBoxNet::new(config.clone()) {
+                    Ok(_) => {
+                        let time = begin.elapsed();
+                        println!("reply from {}: seq={seq} time={time:?}", config.ip);
+                        times.push(time);
+                    }
+                    Err(e) => println!("no reply from {}: seq={seq} {e}", config.ip),
+                }
+            }
+            let lost = *count as usize - times.len();
+            print!("{count} sent, {} received, {lost} lost", times.len());
+            match (times.iter().min(), times.iter().max()) {
+                (Some(min), Some(max)) => {
+                    let avg = times.iter().sum::<Duration>() / times.len() as u32;
+                    println!(", min/avg/max = {min:?}/{avg:?}/{max:?}");
+                }
+                _ => println!(),
+            }
+            if lost > 0 {
+                return Err("not every ping was answered".into());
+            }
+        }
+        Command::Move { x, y } => cli.connect()?.mouse_move([*x, *y])?,
+        Command::Click {
+            button,
+            action,
+            hold,
+        } => {
+            let mut km = cli.connect()?;
+            press(*action, *hold, |state| {
+                match button {
+                    Button::Left => km.mouse_left_click(state),
+                    Button::Right => km.mouse_right_click(state),
+                    Button::Middle => km.mouse_middle_click(state),
+                }?;
+                Ok(())
+            })?;
+            if matches!(action, Action::Down) {
+                km.stop_capture()?;
+                // keep the button held after the tool exits
+                std::mem::forget(km);
+            }
+        }
+        Command::Key {
+            chord,
+            action,
+            hold,
+        } => {
+            let mut km = cli.connect()?;
+            press(*action, *hold, |state| {
+                match state {
+                    ButtonState::Pressed => km.press_chord(chord),
+                    ButtonState::Released => km.release_chord(chord),
+                }?;
+                Ok(())
+            })?;
+            if matches!(action, Action::Down) {
+                km.stop_capture()?;
+                // keep the keys held after the tool exits
+                std::mem::forget(km);
+            }
+        }
+        Command::Type {
+            text,
+            layout,
+            delay,
+        } => {
+            let options = TypingOptions::default().set_key_delay(*delay);
+            let options = match layout {
+                Layout::Us => options.set_layout(UsLayout),
+                Layout::Uk => options.set_layout(UkLayout),
+                Layout::De => options.set_layout(DeLayout),
+                Layout::Fr => options.set_layout(FrLayout),
+            };
+            let report = cli.connect()?.type_text(text, &options)?;
+            if !report.unmappable.is_empty() {
+                eprintln!("skipped characters: {:?}", report.unmappable);
+            }
+        }
+        Command::Wheel { amount } => {
+            let state = match *amount < 0 {
+                true => MwheelState::Down(
+                    amount
+                        .checked_neg()
+                        .ok_or(format!("wheel amount {amount} is out of range"))?,
+                ),
+                false => MwheelState::Up(*amount),
+            };
+            cli.connect()?.mouse_wheel(state)?
+        }
+        Command::Reboot { wait } => {
+            let reboot = cli.connect()?.reboot()?;
+            println!("rebooting");
+            if *wait {
+                reboot.wait()?;
+                println!("kmbox is back");
+            }
+        }
+        Command::Monitor => {
+            let mut monitor = cli.connect()?.into_monitor()?;
+            monitor.bind()?;
+            eprintln!("monitoring, press ctrl+c to stop");
+            let mut recorder = MacroRecorder::new();
+            loop {
+                let data = match monitor.recv_monitor_data() {
+                    Ok(data) => data,
+                    Err(e) if is_timeout(&e.0) => continue,
+                    Err(e) => return Err(e.into()),
+                };
+                let seen = recorder.events().len();
+                recorder.record(&data);
+                for timed in &recorder.events()[seen..] {
+                    println!(
+                        "{:>10.3}s {}",
+                        timed.at.as_secs_f64(),
+                        describe(&timed.event)
+                    );
+                }
+            }
+        }
+        Command::DebugLog => {
+            let mut km = cli.connect()?;
+            let log = km.debug_log()?;
+            eprintln!(
+                "receiving the debug log on {}, press ctrl+c to stop",
+                log.local_addr()
+            );
+            for line in log {
+                println!("{}", line?);
+            }
+        }
+        #[cfg(feature = "tui")]
+        Command::Tui => tui::run(cli.connect()?)?,
+        Command::SetIp {
+            ip,
+            new_port,
+            reboot,
+            wait,
+        } => {
+            let mut km = cli.connect()?;
+            let port = new_port.unwrap_or(km.socket_addr().port());
+            km.set_config(*ip, port)?;
+            println!("address set to {ip}:{port}");
+            if *reboot {
+                let reboot = km.reboot()?;
+                println!("rebooting");
+                if *wait {
+                    reboot.wait()?;
+                    println!("kmbox is back on {ip}:{port}");
+                }
+            } else {
+                println!("reboot the kmbox to use it");
+            }
+        }
+        Command::Replay { file, speed, loops } => {
+            let input_macro = Macro::load(file)?;
+            let mut km = cli.connect()?;
+            println!(
+                "playing {} events over {:?}",
+                input_macro.events.len(),
+                input_macro.duration()
+            );
+            MacroPlayer::default()
+                .set_speed(*speed)
+                .set_loops(*loops)
+                .play(&mut km, &input_macro)?;
+        }
+        Command::Capture(CaptureCommand::Decode { file }) => {
+            let mut first = None;
+            for packet in CaptureReader::open(file)? {
+                let packet = packet?;
+                let offset = packet
+                    .time
+                    .duration_since(*first.get_or_insert(packet.time))
+                    .unwrap_or_default();
+                println!("{:>10.6}s {packet}", offset.as_secs_f64());
+            }
+        }
+        Command::Capture(CaptureCommand::Replay { file, speed }) => {
+            let packets = CaptureReader::open(file)?.collect::<Result<Vec<_>, _>>()?;
+            let sent = cli.connect()?.replay_capture(packets, *speed)?;
+            println!("resent {sent} packets");
+        }
+    }
+    Ok(())
+}
+
+fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
+
+fn describe(event: &MacroEvent) -> String {
+    let state = |pressed: bool| if pressed { "down" } else { "up" };
+    match event {
+        MacroEvent::Move { x, y } => format!("move {x} {y}"),
+        MacroEvent::Button { button, pressed } => {
+            format!("click {button:?} {}", state(*pressed)).to_lowercase()
+        }
+        MacroEvent::Wheel { delta } => format!("wheel {delta}"),
+        MacroEvent::Key { key, pressed } => format!(
+            "key {} {}",
+            key.short_name().unwrap_or(key.name()),
+            state(*pressed)
+        ),
+    }
+}
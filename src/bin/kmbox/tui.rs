@@ -0,0 +1,294 @@
+//! Live view of what the devices attached to the kmbox send.
+
+use std::{
+    collections::VecDeque,
+    error::Error,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::{Duration, Instant},
+};
+
+use input_middleware::{
+    button_state::ButtonState,
+    devices::kmbox_net::{KMBoxNet, KMBoxNetMask, MonitorData},
+    keyboardkeys::KeyboardKey,
+};
+use ratatui::{
+    crossterm::event::{self, Event, KeyCode, KeyEventKind},
+    layout::{Constraint, Layout},
+    style::{Color, Modifier, Style, Stylize},
+    text::{Line, Span},
+    widgets::{Block, Paragraph},
+    DefaultTerminal, Frame,
+};
+
+/// Window the rates are averaged over
+const WINDOW: Duration = Duration::from_secs(1);
+const PING_INTERVAL: Duration = Duration::from_secs(1);
+const TICK: Duration = Duration::from_millis(50);
+
+const BUTTONS: [(&str, u8); 5] = [
+    ("left", 1 << 0),
+    ("right", 1 << 1),
+    ("middle", 1 << 2),
+    ("side1", 1 << 3),
+    ("side2", 1 << 4),
+];
+
+const MASK_KEYS: [(char, &str, KMBoxNetMask); 8] = [
+    ('1', "left", KMBoxNetMask::LEFT),
+    ('2', "right", KMBoxNetMask::RIGHT),
+    ('3', "middle", KMBoxNetMask::MIDDLE),
+    ('4', "side1", KMBoxNetMask::SIDE1),
+    ('5', "side2", KMBoxNetMask::SIDE2),
+    ('x', "x", KMBoxNetMask::X),
+    ('y', "y", KMBoxNetMask::Y),
+    ('w', "wheel", KMBoxNetMask::WHEEL),
+];
+
+#[derive(Default)]
+struct State {
+    last: MonitorData,
+    /// time, movement and wheel of the reports in the last window
+    reports: VecDeque<(Instant, [i32; 2], i32)>,
+    wheel_total: i64,
+    latency: Option<Duration>,
+    latency_sum: Duration,
+    pings: u32,
+    last_ping: Option<Instant>,
+    status: String,
+}
+
+impl State {
+    fn record(&mut self, at: Instant, data: MonitorData) {
+        let mouse = data.mouse;
+        self.reports
+            .push_back((at, [mouse.x.into(), mouse.y.into()], mouse.wheel.into()));
+        self.wheel_total += i64::from(mouse.wheel);
+        self.last = data;
+    }
+
+    fn expire(&mut self, now: Instant) {
+        while let Some((at, ..)) = self.reports.front() {
+            if now.duration_since(*at) <= WINDOW {
+                break;
+            }
+            self.reports.pop_front();
+        }
+    }
+
+    fn held_keys(&self) -> Vec<String> {
+        let keyboard = self.last.keyboard;
+        let modifier = KeyboardKey::KEY_LEFTCONTROL.hid_usage();
+        (0..8)
+            .filter(|bit| keyboard.buttons & (1 << bit) != 0)
+            .map(|bit| modifier + bit)
+            .chain(keyboard.data.into_iter().filter(|code| *code != 0))
+            .map(|code| match KeyboardKey::try_from(code) {
+                Ok(key) => key.short_name().unwrap_or(key.name()).to_string(),
+                Err(_) => format!("{code:#04x}"),
+            })
+            .collect()
+    }
+}
+
+/// Receive the monitor reports on a thread so the screen stays responsive
+fn spawn_monitor(km: &mut KMBoxNet) -> Result<Receiver<(Instant, MonitorData)>, Box<dyn Error>> {
+    let mut monitor = km.monitor()?;
+    monitor.bind()?;
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || loop {
+        match monitor.recv_monitor_data() {
+            Ok(data) => {
+                if sender.send((Instant::now(), data)).is_err() {
+                    return;
+                }
+            }
+            Err(e) if is_timeout(&e.0) => continue,
+            Err(_) => return,
+        }
+    });
+    Ok(receiver)
+}
+
+fn is_timeout(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+    )
+}
+
+pub fn run(mut km: KMBoxNet) -> Result<(), Box<dyn Error>> {
+    let reports = spawn_monitor(&mut km)?;
+    let mut terminal = ratatui::init();
+    let result = event_loop(&mut terminal, &mut km, &reports);
+    ratatui::restore();
+    result
+}
+
+fn event_loop(
+    terminal: &mut DefaultTerminal,
+    km: &mut KMBoxNet,
+    reports: &Receiver<(Instant, MonitorData)>,
+) -> Result<(), Box<dyn Error>> {
+    let mut state = State::default();
+    loop {
+        let now = Instant::now();
+        for (at, data) in reports.try_iter() {
+            state.record(at, data);
+        }
+        state.expire(now);
+        if state
+            .last_ping
+            .is_none_or(|at| now.duration_since(at) >= PING_INTERVAL)
+        {
+            state.last_ping = Some(now);
+            match km.ping() {
+                Ok(rtt) => {
+                    state.latency = Some(rtt);
+                    state.latency_sum += rtt;
+                    state.pings += 1;
+                }
+                Err(e) => state.status = format!("ping failed: {e}"),
+            }
+        }
+        terminal.draw(|frame| draw(frame, km, &state))?;
+
+        if !event::poll(TICK)? {
+            continue;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        let result = match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(()),
+            KeyCode::Char('u') => km.unmask_all(),
+            KeyCode::Char(c @ ('l' | 'r' | 'm')) => {
+                let mut click = |state| match c {
+                    'l' => km.mouse_left_click(state),
+                    'r' => km.mouse_right_click(state),
+                    _ => km.mouse_middle_click(state),
+                };
+                click(ButtonState::Pressed).and_then(|_| click(ButtonState::Released))
+            }
+            KeyCode::Char(c) => match MASK_KEYS.iter().find(|(key, ..)| *key == c) {
+                Some((_, _, mask)) => km.mask_mouse(km.mouse_mask().toggle(*mask)),
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        };
+        state.status = match result {
+            Ok(()) => String::new(),
+            Err(e) => format!("command failed: {e}"),
+        };
+    }
+}
+
+fn draw(frame: &mut Frame, km: &KMBoxNet, state: &State) {
+    let [top, middle, bottom, help] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(6),
+        Constraint::Min(3),
+        Constraint::Length(2),
+    ])
+    .areas(frame.area());
+    let [buttons, mask] =
+        Layout::horizontal([Constraint::Percentage(50), Constraint::Percentage(50)]).areas(top);
+    let [movement, wheel, link] = Layout::horizontal([Constraint::Ratio(1, 3); 3]).areas(middle);
+
+    let held = Style::default().fg(Color::Black).bg(Color::Green);
+    let button_spans: Vec<Span> = BUTTONS
+        .iter()
+        .flat_map(|(name, bit)| {
+            let style = match state.last.mouse.buttons & bit != 0 {
+                true => held,
+                false => Style::default().dim(),
+            };
+            [Span::styled(format!(" {name} "), style), Span::raw(" ")]
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(Line::from(button_spans)).block(Block::bordered().title("Buttons")),
+        buttons,
+    );
+
+    let masked = km.mouse_mask();
+    let mask_spans: Vec<Span> = MASK_KEYS
+        .iter()
+        .flat_map(|(_, name, bit)| {
+            let style = match masked.contains(*bit) {
+                true => Style::default().fg(Color::Black).bg(Color::Red),
+                false => Style::default().dim(),
+            };
+            [Span::styled(format!(" {name} "), style), Span::raw(" ")]
+        })
+        .collect();
+    frame.render_widget(
+        Paragraph::new(Line::from(mask_spans)).block(Block::bordered().title("Masked")),
+        mask,
+    );
+
+    let window = WINDOW.as_secs_f64();
+    let travel = state
+        .reports
+        .iter()
+        .map(|(_, [x, y], _)| f64::from(*x).hypot(f64::from(*y)))
+        .sum::<f64>();
+    let mouse = state.last.mouse;
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(format!("rate  {:>8.0} counts/s", travel / window)),
+            Line::from(format!("last  {:>5} {:>5}", mouse.x, mouse.y)),
+        ])
+        .block(Block::bordered().title("Movement")),
+        movement,
+    );
+
+    let wheel_window: i32 = state.reports.iter().map(|(.., wheel)| wheel).sum();
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(format!("last   {:>6}", mouse.wheel)),
+            Line::from(format!("1s     {:>6}", wheel_window)),
+            Line::from(format!("total  {:>6}", state.wheel_total)),
+        ])
+        .block(Block::bordered().title("Wheel")),
+        wheel,
+    );
+
+    let average = match state.pings {
+        0 => None,
+        pings => Some(state.latency_sum / pings),
+    };
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(format!(
+                "packets  {:>6.0}/s",
+                state.reports.len() as f64 / window
+            )),
+            Line::from(format!(
+                "latency  {:>10?}",
+                state.latency.unwrap_or_default()
+            )),
+            Line::from(format!("average  {:>10?}", average.unwrap_or_default())),
+        ])
+        .block(Block::bordered().title(format!("Link {}", km.socket_addr()))),
+        link,
+    );
+
+    frame.render_widget(
+        Paragraph::new(state.held_keys().join(" ").add_modifier(Modifier::BOLD))
+            .block(Block::bordered().title("Keys")),
+        bottom,
+    );
+
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from("q quit  l/r/m click  1-5 x y w toggle mask  u unmask all"),
+            Line::from(state.status.clone().red()),
+        ]),
+        help,
+    );
+}
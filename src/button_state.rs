@@ -40,3 +40,38 @@ impl From<MwheelState> for i32 {
         }
     }
 }
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum MouseButton {
+    Left,
+    Right,
+    Middle,
+    Side1,
+    Side2,
+}
+
+impl MouseButton {
+    pub fn all() -> impl Iterator<Item = MouseButton> {
+        [
+            MouseButton::Left,
+            MouseButton::Right,
+            MouseButton::Middle,
+            MouseButton::Side1,
+            MouseButton::Side2,
+        ]
+        .into_iter()
+    }
+
+    /// The bit of the button in a HID mouse report
+    pub fn bit(&self) -> i32 {
+        match self {
+            MouseButton::Left => 0x01,
+            MouseButton::Right => 0x02,
+            MouseButton::Middle => 0x04,
+            MouseButton::Side1 => 0x08,
+            MouseButton::Side2 => 0x10,
+        }
+    }
+}
@@ -0,0 +1,47 @@
+//! What a backend can send, see [`InputMiddlewareDeviceAction::capabilities`].
+//!
+//! [`InputMiddlewareDeviceAction::capabilities`]: crate::InputMiddlewareDeviceAction::capabilities
+
+/// Largest relative move of a single report on the KMBox protocol
+pub const MAX_MOUSE_MOVE: i32 = 32767;
+
+/// Limits and features of a backend
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeviceCapabilities {
+    /// largest delta per axis sent in one report, larger moves are split into several reports
+    pub max_mouse_move: i32,
+    /// the side buttons can be clicked
+    pub side_buttons: bool,
+    /// media keys can be sent with `consumer_key`
+    pub consumer_keys: bool,
+}
+
+impl Default for DeviceCapabilities {
+    fn default() -> Self {
+        Self {
+            max_mouse_move: MAX_MOUSE_MOVE,
+            side_buttons: false,
+            consumer_keys: false,
+        }
+    }
+}
+
+/// Split a relative move into steps that stay within `limit` on both axes.
+/// The steps are spread evenly along the line and add up to exactly `pos`,
+/// a zero move is a single zero step.
+pub fn split_mouse_move(pos: [i32; 2], limit: i32) -> impl Iterator<Item = [i32; 2]> {
+    let limit = i64::from(limit.max(1));
+    let [x, y] = pos.map(i64::from);
+    let steps = x
+        .abs()
+        .max(y.abs())
+        .unsigned_abs()
+        .div_ceil(limit.unsigned_abs());
+    let steps = (steps as i64).max(1);
+    // position after `step` steps, the difference of two of them is at most `limit`
+    let at = move |step: i64| [x * step / steps, y * step / steps];
+    (0..steps).map(move |step| {
+        let (from, to) = (at(step), at(step + 1));
+        [(to[0] - from[0]) as i32, (to[1] - from[1]) as i32]
+    })
+}
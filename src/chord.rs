@@ -0,0 +1,64 @@
+//! Key chords like `ctrl+shift+esc` that are pressed together.
+
+use std::{fmt, str::FromStr};
+
+use crate::{errors::InputMiddlewareParseError, keyboardkeys::KeyboardKey};
+
+/// Keys pressed together, modifiers are always pressed first and released last
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Chord {
+    keys: Vec<KeyboardKey>,
+}
+
+impl Chord {
+    /// Create a chord, modifiers are moved to the front keeping the given order otherwise
+    pub fn new(
+        keys: impl IntoIterator<Item = KeyboardKey>,
+    ) -> Result<Self, InputMiddlewareParseError> {
+        let mut chord: Vec<KeyboardKey> = Vec::new();
+        for key in keys {
+            if chord.contains(&key) {
+                return Err(InputMiddlewareParseError::DuplicateKey(key.to_string()));
+            }
+            chord.push(key);
+        }
+        if chord.is_empty() {
+            return Err(InputMiddlewareParseError::EmptyChord);
+        }
+        chord.sort_by_key(|key| !key.is_modifier());
+        Ok(Self { keys: chord })
+    }
+
+    /// The keys in press order
+    pub fn keys(&self) -> &[KeyboardKey] {
+        &self.keys
+    }
+}
+
+impl FromStr for Chord {
+    type Err = InputMiddlewareParseError;
+
+    /// Parse keys separated by `+`, e.g. `ctrl+shift+esc` or `alt+f4`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.trim().is_empty() {
+            return Err(InputMiddlewareParseError::EmptyChord);
+        }
+        let keys = s
+            .split('+')
+            .map(KeyboardKey::from_str)
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(keys)
+    }
+}
+
+impl fmt::Display for Chord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, key) in self.keys.iter().enumerate() {
+            if i > 0 {
+                f.write_str("+")?;
+            }
+            f.write_str(key.short_name().unwrap_or(key.name()))?;
+        }
+        Ok(())
+    }
+}
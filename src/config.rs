@@ -0,0 +1,73 @@
+//! Load an [`InputDevice`] from a TOML or JSON config file.
+//!
+//! The file describes a single device, selected by the `device` key:
+//!
+//! ```toml
+//! device = "kmbox_net"
+//! ip = "192.168.2.188"
+//! port = 16824
+//! uuid = "XXXXXXXX"
+//! timeout = "3s"
+//! ```
+//!
+//! Missing fields fall back to the device defaults and the `KMBOX_IP`, `KMBOX_PORT` and
+//! `KMBOX_UUID` environment variables override whatever is in the file. The result is
+//! validated before it is returned.
+
+use std::path::Path;
+
+use crate::{
+    errors::InputMiddlewareConfigError, InputDevice, InputMiddleware, InputMiddlewareDeviceAction,
+};
+
+impl InputDevice {
+    /// Read a device config from a `.toml` or `.json` file and apply the environment overrides
+    pub fn from_config_file(path: impl AsRef<Path>) -> Result<Self, InputMiddlewareConfigError> {
+        let device = Self::parse_config_file(path)?.with_env_overrides()?;
+        device.validate()?;
+        Ok(device)
+    }
+
+    /// Read a device config from a `.toml` or `.json` file as it is, without the environment
+    /// overrides and without validating it, e.g. to fill in the missing fields first
+    pub fn parse_config_file(path: impl AsRef<Path>) -> Result<Self, InputMiddlewareConfigError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Self::from_toml_str(&content),
+            Some("json") => Self::from_json_str(&content),
+            _ => Err(InputMiddlewareConfigError::UnsupportedFormat(
+                path.to_path_buf(),
+            )),
+        }
+    }
+
+    /// Parse a device config from a TOML string
+    pub fn from_toml_str(content: &str) -> Result<Self, InputMiddlewareConfigError> {
+        Ok(toml::from_str(content)?)
+    }
+
+    /// Parse a device config from a JSON string
+    pub fn from_json_str(content: &str) -> Result<Self, InputMiddlewareConfigError> {
+        Ok(serde_json::from_str(content)?)
+    }
+
+    /// Apply the device specific environment variable overrides
+    pub fn with_env_overrides(self) -> Result<Self, InputMiddlewareConfigError> {
+        match self {
+            InputDevice::KMBoxNet(config) => {
+                Ok(InputDevice::KMBoxNet(config.with_env_overrides()?))
+            }
+        }
+    }
+}
+
+impl InputMiddleware {
+    /// Load the device from a config file and connect to it
+    pub fn from_config_file(
+        path: impl AsRef<Path>,
+    ) -> Result<Box<dyn InputMiddlewareDeviceAction>, InputMiddlewareConfigError> {
+        let device = InputDevice::from_config_file(path)?;
+        Ok(InputMiddleware::new(device)?)
+    }
+}
@@ -0,0 +1,189 @@
+//! Absolute cursor positioning on top of relative moves.
+//!
+//! The devices only send relative counts, the host turns them into pixels with its pointer
+//! speed and acceleration. A [`CursorModel`] estimates that mapping to track the cursor and to
+//! compute the counts for [`CursorModel::move_to`].
+
+use crate::logging::debug;
+
+use crate::{errors::InputMiddlewareSendError, InputMiddlewareDeviceAction};
+
+/// Pointer acceleration of the host, the gain multiplies the pointer speed
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum Acceleration {
+    /// every count moves the same distance
+    #[default]
+    None,
+    /// gain by counts per report as `[counts, gain]` points sorted by counts,
+    /// interpolated linearly in between and flat outside
+    Curve(Vec<[f64; 2]>),
+}
+
+impl Acceleration {
+    /// The gain for a report with `counts` of movement
+    pub fn gain(&self, counts: f64) -> f64 {
+        let points = match self {
+            Acceleration::Curve(points) if !points.is_empty() => points,
+            _ => return 1.0,
+        };
+        let first = points[0];
+        let last = points[points.len() - 1];
+        if counts <= first[0] {
+            return first[1];
+        }
+        if counts >= last[0] {
+            return last[1];
+        }
+        points
+            .windows(2)
+            .find(|w| counts <= w[1][0])
+            .map(|w| {
+                let t = (counts - w[0][0]) / (w[1][0] - w[0][0]);
+                w[0][1] + (w[1][1] - w[0][1]) * t
+            })
+            .unwrap_or(last[1])
+    }
+}
+
+/// Estimated absolute cursor position on a screen
+#[derive(Debug, Clone, PartialEq)]
+pub struct CursorModel {
+    /// screen size in pixels
+    pub screen: [u32; 2],
+    /// pixels per count without acceleration, default is 1.0
+    pub speed: f64,
+    pub acceleration: Acceleration,
+    /// `move_to` stops once the estimate is this many pixels away from the target, default 0.5
+    pub tolerance: f64,
+    /// `move_to` sends at most this many moves, default is 8
+    pub max_iterations: usize,
+    position: [f64; 2],
+}
+
+impl CursorModel {
+    /// Model for a screen of `width` x `height`, the cursor is assumed to be in the center
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            screen: [width, height],
+            speed: 1.0,
+            acceleration: Acceleration::None,
+            tolerance: 0.5,
+            max_iterations: 8,
+            position: [f64::from(width) / 2.0, f64::from(height) / 2.0],
+        }
+    }
+
+    pub fn set_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn set_acceleration(mut self, acceleration: Acceleration) -> Self {
+        self.acceleration = acceleration;
+        self
+    }
+
+    pub fn set_tolerance(mut self, tolerance: f64) -> Self {
+        self.tolerance = tolerance;
+        self
+    }
+
+    pub fn set_max_iterations(mut self, max_iterations: usize) -> Self {
+        self.max_iterations = max_iterations;
+        self
+    }
+
+    /// The estimated position
+    pub fn position(&self) -> [f64; 2] {
+        self.position
+    }
+
+    /// Replace the estimate with the real position, e.g. read from the host
+    pub fn resync(&mut self, x: f64, y: f64) {
+        self.position = self.clamp([x, y]);
+    }
+
+    fn clamp(&self, position: [f64; 2]) -> [f64; 2] {
+        [0, 1].map(|axis| {
+            let max = f64::from(self.screen[axis].saturating_sub(1));
+            position[axis].clamp(0.0, max)
+        })
+    }
+
+    /// Pixels the host moves the cursor for a report of `counts`
+    pub fn counts_to_pixels(&self, counts: [i32; 2]) -> [f64; 2] {
+        let counts = counts.map(f64::from);
+        let gain = self.speed * self.acceleration.gain(counts[0].hypot(counts[1]));
+        counts.map(|c| c * gain)
+    }
+
+    /// Counts for a report that moves the cursor by about `pixels`
+    pub fn pixels_to_counts(&self, pixels: [f64; 2]) -> [i32; 2] {
+        let distance = pixels[0].hypot(pixels[1]);
+        if distance == 0.0 || self.speed <= 0.0 {
+            return [0, 0];
+        }
+        let travel = |counts: f64| counts * self.speed * self.acceleration.gain(counts);
+        // the travel grows with the counts, search the counts for the distance
+        let mut high = 1.0;
+        while travel(high) < distance && high < f64::from(i32::MAX) {
+            high *= 2.0;
+        }
+        let mut low = 0.0;
+        for _ in 0..64 {
+            let mid = (low + high) / 2.0;
+            match travel(mid) < distance {
+                true => low = mid,
+                false => high = mid,
+            }
+        }
+        let counts = (low + high) / 2.0;
+        pixels.map(|p| (p / distance * counts).round() as i32)
+    }
+
+    /// Update the estimate with a move that was sent, the cursor stops at the screen edges
+    pub fn track(&mut self, counts: [i32; 2]) {
+        let pixels = self.counts_to_pixels(counts);
+        self.position = self.clamp([self.position[0] + pixels[0], self.position[1] + pixels[1]]);
+    }
+
+    /// Move the cursor to `x`, `y` on the screen, see [`move_to_with`](Self::move_to_with)
+    pub fn move_to<D: InputMiddlewareDeviceAction + ?Sized>(
+        &mut self,
+        device: &mut D,
+        x: f64,
+        y: f64,
+    ) -> Result<[f64; 2], InputMiddlewareSendError> {
+        self.move_to_with(device, x, y, || None)
+    }
+
+    /// Move the cursor to `x`, `y` on the screen and return the estimated position.
+    /// After every move `resync` can return the real position to correct the estimate,
+    /// moves are sent until the estimate is within the tolerance or `max_iterations` is reached.
+    pub fn move_to_with<D: InputMiddlewareDeviceAction + ?Sized>(
+        &mut self,
+        device: &mut D,
+        x: f64,
+        y: f64,
+        mut resync: impl FnMut() -> Option<[f64; 2]>,
+    ) -> Result<[f64; 2], InputMiddlewareSendError> {
+        let target = self.clamp([x, y]);
+        for _ in 0..self.max_iterations {
+            let remaining = [target[0] - self.position[0], target[1] - self.position[1]];
+            if remaining[0].hypot(remaining[1]) <= self.tolerance {
+                break;
+            }
+            let counts = self.pixels_to_counts(remaining);
+            if counts == [0, 0] {
+                break;
+            }
+            device.mouse_move(counts)?;
+            self.track(counts);
+            if let Some([x, y]) = resync() {
+                self.resync(x, y);
+            }
+            debug!("Cursor moved by {:?} to {:?}", counts, self.position);
+        }
+        Ok(self.position)
+    }
+}
@@ -0,0 +1,411 @@
+//! Capture of the packets exchanged with a kmbox as pcap-ng file.
+//!
+//! Every packet is wrapped in a synthetic Ethernet, IPv4 and UDP frame so the file opens in
+//! Wireshark, the direction is stored in the `epb_flags` option.
+
+use std::{
+    fmt,
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    net::SocketAddrV4,
+    path::Path,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{cmd::CMD, structs::SoftKeyboard, structs::SoftMouse};
+
+const BLOCK_SECTION_HEADER: u32 = 0x0A0D_0D0A;
+const BLOCK_INTERFACE: u32 = 0x0000_0001;
+const BLOCK_ENHANCED_PACKET: u32 = 0x0000_0006;
+const BYTE_ORDER_MAGIC: u32 = 0x1A2B_3C4D;
+const LINKTYPE_ETHERNET: u16 = 1;
+const OPTION_END: u16 = 0;
+const OPTION_EPB_FLAGS: u16 = 2;
+
+const ETHERNET_HEADER: usize = 14;
+const IPV4_HEADER: usize = 20;
+const UDP_HEADER: usize = 8;
+/// locally administered addresses of the synthetic frames
+const LOCAL_MAC: [u8; 6] = [0x02, 0, 0, 0, 0, 0x01];
+const KMBOX_MAC: [u8; 6] = [0x02, 0, 0, 0, 0, 0x02];
+
+const HEAD_SIZE: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CaptureDirection {
+    /// from this host to the kmbox
+    Sent,
+    /// the answer of the kmbox
+    Received,
+}
+
+/// A packet read back from a capture, `data` is the UDP payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CapturedPacket {
+    pub time: SystemTime,
+    pub direction: CaptureDirection,
+    pub data: Vec<u8>,
+}
+
+impl CapturedPacket {
+    fn u32_at(&self, offset: usize) -> Option<u32> {
+        let bytes = self.data.get(offset..offset + 4)?;
+        Some(u32::from_le_bytes(bytes.try_into().ok()?))
+    }
+
+    pub fn mac(&self) -> Option<u32> {
+        self.u32_at(0)
+    }
+
+    pub fn rand(&self) -> Option<u32> {
+        self.u32_at(4)
+    }
+
+    pub fn indexpts(&self) -> Option<u32> {
+        self.u32_at(8)
+    }
+
+    /// The raw command of the header
+    pub fn cmd_raw(&self) -> Option<u32> {
+        self.u32_at(12)
+    }
+
+    /// The command of the header, `None` for short packets
+    pub fn cmd(&self) -> Option<CMD> {
+        self.cmd_raw().map(CMD::from_code)
+    }
+
+    /// Everything after the 16 byte header
+    pub fn payload(&self) -> &[u8] {
+        self.data.get(HEAD_SIZE..).unwrap_or_default()
+    }
+
+    fn mouse(&self) -> Option<SoftMouse> {
+        let word = |i: usize| self.u32_at(HEAD_SIZE + i * 4).map(|v| v as i32);
+        Some(SoftMouse {
+            button: word(0)?,
+            x: word(1)?,
+            y: word(2)?,
+            wheel: word(3)?,
+            point: [0; 10],
+        })
+    }
+
+    fn keyboard(&self) -> Option<SoftKeyboard> {
+        let payload = self.payload();
+        Some(SoftKeyboard {
+            ctrl: *payload.first()?,
+            resvel: *payload.get(1)?,
+            button: payload.get(2..12)?.try_into().ok()?,
+        })
+    }
+}
+
+impl fmt::Display for CapturedPacket {
+    /// One line like `> MOUSE_MOVE #12 rand=0x1a2b3c4d buttons=0x01 x=10 y=-5 wheel=0`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let arrow = match self.direction {
+            CaptureDirection::Sent => '>',
+            CaptureDirection::Received => '<',
+        };
+        let (Some(cmd), Some(indexpts), Some(rand)) = (self.cmd(), self.indexpts(), self.rand())
+        else {
+            return write!(f, "{arrow} short packet of {} bytes", self.data.len());
+        };
+        write!(f, "{arrow} {cmd} #{indexpts} rand={rand:#010x}")?;
+        match cmd {
+            CMD::MOUSE_MOVE
+            | CMD::MOUSE_LEFT
+            | CMD::MOUSE_MIDDLE
+            | CMD::MOUSE_RIGHT
+            | CMD::MOUSE_WHEEL
+            | CMD::MOUSE_AUTOMOVE => {
+                if let Some(mouse) = self.mouse() {
+                    write!(
+                        f,
+                        " buttons={:#04x} x={} y={} wheel={}",
+                        mouse.button, mouse.x, mouse.y, mouse.wheel
+                    )?;
+                }
+            }
+            CMD::KEYBOARD_ALL => {
+                if let Some(keyboard) = self.keyboard() {
+                    let keys: Vec<String> = keyboard
+                        .button
+                        .iter()
+                        .filter(|code| **code != 0)
+                        .map(|code| format!("{code:02x}"))
+                        .collect();
+                    write!(f, " ctrl={:#04x} keys=[{}]", keyboard.ctrl, keys.join(" "))?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+/// Writes packets as pcap-ng
+pub struct CaptureWriter<W: Write> {
+    writer: W,
+    local: SocketAddrV4,
+    remote: SocketAddrV4,
+}
+
+impl<W: Write> fmt::Debug for CaptureWriter<W> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CaptureWriter")
+            .field("local", &self.local)
+            .field("remote", &self.remote)
+            .finish_non_exhaustive()
+    }
+}
+
+impl CaptureWriter<BufWriter<File>> {
+    pub fn create(
+        path: impl AsRef<Path>,
+        local: SocketAddrV4,
+        remote: SocketAddrV4,
+    ) -> io::Result<Self> {
+        Self::new(BufWriter::new(File::create(path)?), local, remote)
+    }
+}
+
+impl<W: Write> CaptureWriter<W> {
+    /// Write the section and interface header, `local` and `remote` are used in the frames
+    pub fn new(mut writer: W, local: SocketAddrV4, remote: SocketAddrV4) -> io::Result<Self> {
+        let mut section = Vec::new();
+        section.extend(BYTE_ORDER_MAGIC.to_le_bytes());
+        section.extend(1u16.to_le_bytes());
+        section.extend(0u16.to_le_bytes());
+        // section length is not known
+        section.extend((-1i64).to_le_bytes());
+        write_block(&mut writer, BLOCK_SECTION_HEADER, &section)?;
+
+        let mut interface = Vec::new();
+        interface.extend(LINKTYPE_ETHERNET.to_le_bytes());
+        interface.extend(0u16.to_le_bytes());
+        // no snap length limit
+        interface.extend(0u32.to_le_bytes());
+        write_block(&mut writer, BLOCK_INTERFACE, &interface)?;
+        Ok(Self {
+            writer,
+            local,
+            remote,
+        })
+    }
+
+    pub fn write_packet(
+        &mut self,
+        direction: CaptureDirection,
+        time: SystemTime,
+        data: &[u8],
+    ) -> io::Result<()> {
+        let (from, to, from_mac, to_mac) = match direction {
+            CaptureDirection::Sent => (self.local, self.remote, LOCAL_MAC, KMBOX_MAC),
+            CaptureDirection::Received => (self.remote, self.local, KMBOX_MAC, LOCAL_MAC),
+        };
+        let frame = udp_frame(from, to, from_mac, to_mac, data);
+        let micros = time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_micros() as u64;
+
+        let mut block = Vec::with_capacity(frame.len() + 32);
+        block.extend(0u32.to_le_bytes());
+        block.extend(((micros >> 32) as u32).to_le_bytes());
+        block.extend((micros as u32).to_le_bytes());
+        block.extend((frame.len() as u32).to_le_bytes());
+        block.extend((frame.len() as u32).to_le_bytes());
+        block.extend(&frame);
+        pad(&mut block);
+        let flags: u32 = match direction {
+            CaptureDirection::Received => 0b01,
+            CaptureDirection::Sent => 0b10,
+        };
+        block.extend(OPTION_EPB_FLAGS.to_le_bytes());
+        block.extend(4u16.to_le_bytes());
+        block.extend(flags.to_le_bytes());
+        block.extend(OPTION_END.to_le_bytes());
+        block.extend(0u16.to_le_bytes());
+        write_block(&mut self.writer, BLOCK_ENHANCED_PACKET, &block)
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+}
+
+fn pad(buffer: &mut Vec<u8>) {
+    buffer.resize(buffer.len().next_multiple_of(4), 0);
+}
+
+fn write_block(writer: &mut impl Write, kind: u32, body: &[u8]) -> io::Result<()> {
+    let length = (body.len() + 12) as u32;
+    writer.write_all(&kind.to_le_bytes())?;
+    writer.write_all(&length.to_le_bytes())?;
+    writer.write_all(body)?;
+    writer.write_all(&length.to_le_bytes())
+}
+
+fn udp_frame(
+    from: SocketAddrV4,
+    to: SocketAddrV4,
+    from_mac: [u8; 6],
+    to_mac: [u8; 6],
+    data: &[u8],
+) -> Vec<u8> {
+    let udp_length = (UDP_HEADER + data.len()) as u16;
+    let ip_length = IPV4_HEADER as u16 + udp_length;
+    let mut frame = Vec::with_capacity(ETHERNET_HEADER + ip_length as usize);
+    frame.extend(to_mac);
+    frame.extend(from_mac);
+    frame.extend(0x0800u16.to_be_bytes());
+
+    let mut ip = [0u8; IPV4_HEADER];
+    ip[0] = 0x45;
+    ip[2..4].copy_from_slice(&ip_length.to_be_bytes());
+    // don't fragment
+    ip[6] = 0x40;
+    ip[8] = 64;
+    ip[9] = 17;
+    ip[12..16].copy_from_slice(&from.ip().octets());
+    ip[16..20].copy_from_slice(&to.ip().octets());
+    let checksum = ipv4_checksum(&ip);
+    ip[10..12].copy_from_slice(&checksum.to_be_bytes());
+    frame.extend(ip);
+
+    frame.extend(from.port().to_be_bytes());
+    frame.extend(to.port().to_be_bytes());
+    frame.extend(udp_length.to_be_bytes());
+    // the udp checksum is optional over ipv4
+    frame.extend(0u16.to_be_bytes());
+    frame.extend(data);
+    frame
+}
+
+fn ipv4_checksum(header: &[u8]) -> u16 {
+    let mut sum: u32 = header
+        .chunks(2)
+        .map(|word| u32::from(u16::from_be_bytes([word[0], word[1]])))
+        .sum();
+    while sum > 0xffff {
+        sum = (sum & 0xffff) + (sum >> 16);
+    }
+    !(sum as u16)
+}
+
+/// Reads back the packets of a capture written by [`CaptureWriter`]
+pub struct CaptureReader<R: Read> {
+    reader: R,
+}
+
+impl CaptureReader<BufReader<File>> {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::new(BufReader::new(File::open(path)?))
+    }
+}
+
+impl<R: Read> CaptureReader<R> {
+    /// Check the section header, only little endian captures are read
+    pub fn new(mut reader: R) -> io::Result<Self> {
+        let (kind, body) = read_block(&mut reader)?.ok_or_else(|| invalid("empty capture"))?;
+        if kind != BLOCK_SECTION_HEADER {
+            return Err(invalid("not a pcap-ng file"));
+        }
+        if body.get(..4) != Some(&BYTE_ORDER_MAGIC.to_le_bytes()[..]) {
+            return Err(invalid("only little endian captures are supported"));
+        }
+        Ok(Self { reader })
+    }
+
+    fn next_packet(&mut self) -> io::Result<Option<CapturedPacket>> {
+        loop {
+            let Some((kind, body)) = read_block(&mut self.reader)? else {
+                return Ok(None);
+            };
+            if kind == BLOCK_ENHANCED_PACKET {
+                return parse_packet(&body).map(Some);
+            }
+        }
+    }
+}
+
+impl<R: Read> Iterator for CaptureReader<R> {
+    type Item = io::Result<CapturedPacket>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_packet().transpose()
+    }
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Type and body of the next block, `None` at the end of the file
+fn read_block(reader: &mut impl Read) -> io::Result<Option<(u32, Vec<u8>)>> {
+    let mut header = [0u8; 8];
+    match reader.read_exact(&mut header) {
+        Ok(()) => {}
+        Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+        Err(e) => return Err(e),
+    }
+    let kind = u32::from_le_bytes(header[..4].try_into().unwrap());
+    let length = u32::from_le_bytes(header[4..].try_into().unwrap()) as usize;
+    if length < 12 || !length.is_multiple_of(4) {
+        return Err(invalid("invalid block length"));
+    }
+    let mut body = vec![0u8; length - 8];
+    reader.read_exact(&mut body)?;
+    // the trailing copy of the length
+    body.truncate(length - 12);
+    Ok(Some((kind, body)))
+}
+
+fn parse_packet(body: &[u8]) -> io::Result<CapturedPacket> {
+    let word = |offset: usize| {
+        body.get(offset..offset + 4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .ok_or_else(|| invalid("truncated packet block"))
+    };
+    let micros = (u64::from(word(4)?) << 32) | u64::from(word(8)?);
+    let captured = word(12)? as usize;
+    let frame = body
+        .get(20..20 + captured)
+        .ok_or_else(|| invalid("truncated packet data"))?;
+
+    let mut direction = CaptureDirection::Sent;
+    let mut options = &body[(20 + captured).next_multiple_of(4).min(body.len())..];
+    while options.len() >= 4 {
+        let code = u16::from_le_bytes([options[0], options[1]]);
+        let length = u16::from_le_bytes([options[2], options[3]]) as usize;
+        if code == OPTION_END {
+            break;
+        }
+        let value = options.get(4..4 + length).unwrap_or_default();
+        if code == OPTION_EPB_FLAGS && value.first().map(|flags| flags & 0b11) == Some(0b01) {
+            direction = CaptureDirection::Received;
+        }
+        options = options
+            .get((4 + length).next_multiple_of(4)..)
+            .unwrap_or_default();
+    }
+
+    let ip_header = frame
+        .get(ETHERNET_HEADER)
+        .map(|version| usize::from(version & 0x0f) * 4)
+        .ok_or_else(|| invalid("frame too short"))?;
+    let data = frame
+        .get(ETHERNET_HEADER + ip_header + UDP_HEADER..)
+        .ok_or_else(|| invalid("frame too short"))?;
+    Ok(CapturedPacket {
+        time: UNIX_EPOCH + Duration::from_micros(micros),
+        direction,
+        data: data.to_vec(),
+    })
+}
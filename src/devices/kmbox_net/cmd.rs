@@ -1,11 +1,14 @@
+use std::{fmt, str::FromStr};
+
 use super::cmd_instruction::{
     CMD_BAZER_MOVE, CMD_CONNECT, CMD_DEBUG, CMD_KEYBOARD_ALL, CMD_MASK_MOUSE, CMD_MONITOR,
     CMD_MOUSE_AUTOMOVE, CMD_MOUSE_LEFT, CMD_MOUSE_MIDDLE, CMD_MOUSE_MOVE, CMD_MOUSE_RIGHT,
     CMD_MOUSE_WHEEL, CMD_REBOOT, CMD_SETCONFIG, CMD_SHOWPIC, CMD_UNMASK_ALL,
 };
+use super::errors::KMBoxNetProtocolError;
 
 #[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum CMD {
     CONNECT,
     MOUSE_MOVE,
@@ -23,6 +26,58 @@ pub enum CMD {
     UNMASK_ALL,
     SETCONFIG,
     SHOWPIC,
+    /// A code this crate does not know, kept so it can be shown
+    Unknown(u32),
+}
+
+impl CMD {
+    /// Every known command
+    pub const ALL: [CMD; 16] = [
+        CMD::CONNECT,
+        CMD::MOUSE_MOVE,
+        CMD::MOUSE_LEFT,
+        CMD::MOUSE_MIDDLE,
+        CMD::MOUSE_RIGHT,
+        CMD::MOUSE_WHEEL,
+        CMD::MOUSE_AUTOMOVE,
+        CMD::KEYBOARD_ALL,
+        CMD::REBOOT,
+        CMD::BAZER_MOVE,
+        CMD::MONITOR,
+        CMD::DEBUG,
+        CMD::MASK_MOUSE,
+        CMD::UNMASK_ALL,
+        CMD::SETCONFIG,
+        CMD::SHOWPIC,
+    ];
+
+    /// The command of a code read from the network, unknown codes become [`CMD::Unknown`]
+    pub fn from_code(code: u32) -> Self {
+        CMD::try_from(code).unwrap_or(CMD::Unknown(code))
+    }
+
+    /// The name of a known command, `None` for [`CMD::Unknown`]
+    pub fn name(&self) -> Option<&'static str> {
+        Some(match self {
+            CMD::CONNECT => "CONNECT",
+            CMD::MOUSE_MOVE => "MOUSE_MOVE",
+            CMD::MOUSE_LEFT => "MOUSE_LEFT",
+            CMD::MOUSE_MIDDLE => "MOUSE_MIDDLE",
+            CMD::MOUSE_RIGHT => "MOUSE_RIGHT",
+            CMD::MOUSE_WHEEL => "MOUSE_WHEEL",
+            CMD::MOUSE_AUTOMOVE => "MOUSE_AUTOMOVE",
+            CMD::KEYBOARD_ALL => "KEYBOARD_ALL",
+            CMD::REBOOT => "REBOOT",
+            CMD::BAZER_MOVE => "BAZER_MOVE",
+            CMD::MONITOR => "MONITOR",
+            CMD::DEBUG => "DEBUG",
+            CMD::MASK_MOUSE => "MASK_MOUSE",
+            CMD::UNMASK_ALL => "UNMASK_ALL",
+            CMD::SETCONFIG => "SETCONFIG",
+            CMD::SHOWPIC => "SHOWPIC",
+            CMD::Unknown(_) => return None,
+        })
+    }
 }
 
 impl From<CMD> for u32 {
@@ -44,53 +99,69 @@ impl From<CMD> for u32 {
             CMD::UNMASK_ALL => CMD_UNMASK_ALL,
             CMD::SETCONFIG => CMD_SETCONFIG,
             CMD::SHOWPIC => CMD_SHOWPIC,
+            CMD::Unknown(code) => code,
         }
     }
 }
 
-impl From<CMD> for String {
-    fn from(value: CMD) -> Self {
-        match value {
-            CMD::CONNECT => "CONNECT".to_string(),
-            CMD::MOUSE_MOVE => "MOUSE_MOVE".to_string(),
-            CMD::MOUSE_LEFT => "MOUSE_LEFT".to_string(),
-            CMD::MOUSE_MIDDLE => "MOUSE_MIDDLE".to_string(),
-            CMD::MOUSE_RIGHT => "MOUSE_RIGHT".to_string(),
-            CMD::MOUSE_WHEEL => "MOUSE_WHEEL".to_string(),
-            CMD::MOUSE_AUTOMOVE => "MOUSE_AUTOMOVE".to_string(),
-            CMD::KEYBOARD_ALL => "KEYBOARD_ALL".to_string(),
-            CMD::REBOOT => "REBOOT".to_string(),
-            CMD::BAZER_MOVE => "BAZER_MOVE".to_string(),
-            CMD::MONITOR => "MONITOR".to_string(),
-            CMD::DEBUG => "DEBUG".to_string(),
-            CMD::MASK_MOUSE => "MASK_MOUSE".to_string(),
-            CMD::UNMASK_ALL => "UNMASK_ALL".to_string(),
-            CMD::SETCONFIG => "SETCONFIG".to_string(),
-            CMD::SHOWPIC => "SHOWPIC".to_string(),
+impl TryFrom<u32> for CMD {
+    type Error = KMBoxNetProtocolError;
+
+    /// Only known codes are accepted, see [`CMD::from_code`] to keep unknown ones
+    fn try_from(cmd: u32) -> Result<Self, Self::Error> {
+        match cmd {
+            CMD_CONNECT => Ok(CMD::CONNECT),
+            CMD_MOUSE_MOVE => Ok(CMD::MOUSE_MOVE),
+            CMD_MOUSE_LEFT => Ok(CMD::MOUSE_LEFT),
+            CMD_MOUSE_MIDDLE => Ok(CMD::MOUSE_MIDDLE),
+            CMD_MOUSE_RIGHT => Ok(CMD::MOUSE_RIGHT),
+            CMD_MOUSE_WHEEL => Ok(CMD::MOUSE_WHEEL),
+            CMD_MOUSE_AUTOMOVE => Ok(CMD::MOUSE_AUTOMOVE),
+            CMD_KEYBOARD_ALL => Ok(CMD::KEYBOARD_ALL),
+            CMD_REBOOT => Ok(CMD::REBOOT),
+            CMD_BAZER_MOVE => Ok(CMD::BAZER_MOVE),
+            CMD_MONITOR => Ok(CMD::MONITOR),
+            CMD_DEBUG => Ok(CMD::DEBUG),
+            CMD_MASK_MOUSE => Ok(CMD::MASK_MOUSE),
+            CMD_UNMASK_ALL => Ok(CMD::UNMASK_ALL),
+            CMD_SETCONFIG => Ok(CMD::SETCONFIG),
+            CMD_SHOWPIC => Ok(CMD::SHOWPIC),
+            _ => Err(KMBoxNetProtocolError::UnknownCommand(cmd)),
         }
     }
 }
 
-impl From<u32> for CMD {
-    fn from(cmd: u32) -> Self {
-        match cmd {
-            CMD_CONNECT => CMD::CONNECT,
-            CMD_MOUSE_MOVE => CMD::MOUSE_MOVE,
-            CMD_MOUSE_LEFT => CMD::MOUSE_LEFT,
-            CMD_MOUSE_MIDDLE => CMD::MOUSE_MIDDLE,
-            CMD_MOUSE_RIGHT => CMD::MOUSE_RIGHT,
-            CMD_MOUSE_WHEEL => CMD::MOUSE_WHEEL,
-            CMD_MOUSE_AUTOMOVE => CMD::MOUSE_AUTOMOVE,
-            CMD_KEYBOARD_ALL => CMD::KEYBOARD_ALL,
-            CMD_REBOOT => CMD::REBOOT,
-            CMD_BAZER_MOVE => CMD::BAZER_MOVE,
-            CMD_MONITOR => CMD::MONITOR,
-            CMD_DEBUG => CMD::DEBUG,
-            CMD_MASK_MOUSE => CMD::MASK_MOUSE,
-            CMD_UNMASK_ALL => CMD::UNMASK_ALL,
-            CMD_SETCONFIG => CMD::SETCONFIG,
-            CMD_SHOWPIC => CMD::SHOWPIC,
-            _ => panic!("Unknown command"),
+impl fmt::Display for CMD {
+    /// The name of the command or `UNKNOWN(0x12345678)`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name() {
+            Some(name) => f.write_str(name),
+            None => write!(f, "UNKNOWN({:#010x})", u32::from(*self)),
+        }
+    }
+}
+
+impl FromStr for CMD {
+    type Err = KMBoxNetProtocolError;
+
+    /// Accepts the names in any case, `UNKNOWN(0x..)` and raw hex codes like `0xaf3c2828`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(cmd) = CMD::ALL
+            .into_iter()
+            .find(|cmd| cmd.name().is_some_and(|name| name.eq_ignore_ascii_case(s)))
+        {
+            return Ok(cmd);
         }
+        let code = s
+            .strip_prefix("UNKNOWN(")
+            .or_else(|| s.strip_prefix("unknown("))
+            .and_then(|s| s.strip_suffix(')'))
+            .unwrap_or(s);
+        code.strip_prefix("0x")
+            .or_else(|| code.strip_prefix("0X"))
+            .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+            .map(CMD::from_code)
+            .ok_or_else(|| KMBoxNetProtocolError::UnknownCommandName(s.to_string()))
     }
 }
@@ -0,0 +1,449 @@
+use std::{
+    net::{IpAddr, Ipv4Addr, SocketAddr, ToSocketAddrs},
+    time::Duration,
+};
+
+use socket2::Socket;
+
+use crate::{
+    capabilities::MAX_MOUSE_MOVE, devices::kmbox_net::errors::InvalidConfig,
+    errors::InputMiddlewareConfigError,
+};
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct KMBoxNetConfig {
+    /// ipv4 address or hostname of the kmbox
+    pub ip: String,
+    pub port: u16,
+    /// 8 hex characters shown on the kmbox display
+    pub uuid: String,
+    /// default timeout is 3 seconds
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub timeout: Duration,
+    /// local address the socket is bound to, the OS picks one when not set
+    pub local_addr: Option<SocketAddr>,
+    /// local address the monitor listens on, defaults to `0.0.0.0` and the kmbox port + 1
+    pub monitor_addr: Option<SocketAddr>,
+    /// local address the firmware debug log is received on, defaults to `0.0.0.0` and the
+    /// kmbox port + 2, port 0 lets the OS pick one
+    pub debug_addr: Option<SocketAddr>,
+    /// largest delta per axis in one mouse report, larger moves are split into several reports.
+    /// Use 127 if the host clamps the deltas to i8
+    pub max_mouse_move: i32,
+    /// pause between the reports of a split mouse move, default is none
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub move_pacing: Duration,
+    /// send the mouse and keyboard commands over the encrypted channel of newer firmware
+    pub encrypted: bool,
+    /// how long to wait for the kmbox to answer again after a reboot, default is 30 seconds
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub reboot_timeout: Duration,
+    /// options applied to the command and monitor sockets
+    pub socket: KMBoxNetSocketOptions,
+}
+
+/// Low level socket options, everything left as `None` keeps the OS default
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct KMBoxNetSocketOptions {
+    /// network interface the sockets are bound to (`SO_BINDTODEVICE`), linux only
+    pub interface: Option<String>,
+    /// type of service byte of outgoing packets, the DSCP value is the upper 6 bits
+    pub tos: Option<u8>,
+    pub send_buffer_size: Option<usize>,
+    pub recv_buffer_size: Option<usize>,
+}
+
+impl KMBoxNetSocketOptions {
+    fn validate(&self) -> Result<(), InvalidConfig> {
+        if let Some(interface) = &self.interface {
+            if interface.is_empty() {
+                return Err(InvalidConfig::new("socket.interface", "must not be empty"));
+            }
+            if !cfg!(any(target_os = "android", target_os = "linux")) {
+                return Err(InvalidConfig::new(
+                    "socket.interface",
+                    "binding to an interface is only supported on linux",
+                ));
+            }
+        }
+        if self.send_buffer_size == Some(0) {
+            return Err(InvalidConfig::new(
+                "socket.send_buffer_size",
+                "must be greater than zero",
+            ));
+        }
+        if self.recv_buffer_size == Some(0) {
+            return Err(InvalidConfig::new(
+                "socket.recv_buffer_size",
+                "must be greater than zero",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Apply the options to a freshly created socket
+    pub(crate) fn apply(&self, socket: &Socket) -> std::io::Result<()> {
+        if let Some(interface) = &self.interface {
+            #[cfg(any(target_os = "android", target_os = "linux"))]
+            socket.bind_device(Some(interface.as_bytes()))?;
+            #[cfg(not(any(target_os = "android", target_os = "linux")))]
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Unsupported,
+                format!("can not bind to interface {interface:?} on this platform"),
+            ));
+        }
+        if let Some(tos) = self.tos {
+            socket.set_tos(tos.into())?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            socket.set_send_buffer_size(size)?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            socket.set_recv_buffer_size(size)?;
+        }
+        Ok(())
+    }
+}
+
+impl Default for KMBoxNetConfig {
+    fn default() -> Self {
+        Self {
+            ip: "192.168.2.188".into(),
+            port: 16824,
+            uuid: "XXXXXXXX".into(),
+            timeout: Duration::from_secs(3),
+            local_addr: None,
+            monitor_addr: None,
+            debug_addr: None,
+            max_mouse_move: MAX_MOUSE_MOVE,
+            move_pacing: Duration::ZERO,
+            encrypted: false,
+            reboot_timeout: Duration::from_secs(30),
+            socket: KMBoxNetSocketOptions::default(),
+        }
+    }
+}
+
+impl KMBoxNetConfig {
+    pub fn default_with_uuid(uuid: &str) -> Self {
+        Self::default().set_uuid(uuid.into())
+    }
+
+    pub fn new(ip: &str, port: u16, uuid: &str) -> Self {
+        Self {
+            ip: ip.into(),
+            port,
+            uuid: uuid.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Start building a config that is validated on [`KMBoxNetConfigBuilder::build`]
+    pub fn builder() -> KMBoxNetConfigBuilder {
+        KMBoxNetConfigBuilder::default()
+    }
+
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn set_uuid(mut self, uuid: String) -> Self {
+        self.uuid = uuid;
+        self
+    }
+
+    pub fn set_local_addr(mut self, local_addr: SocketAddr) -> Self {
+        self.local_addr = Some(local_addr);
+        self
+    }
+
+    pub fn set_monitor_addr(mut self, monitor_addr: SocketAddr) -> Self {
+        self.monitor_addr = Some(monitor_addr);
+        self
+    }
+
+    pub fn set_debug_addr(mut self, debug_addr: SocketAddr) -> Self {
+        self.debug_addr = Some(debug_addr);
+        self
+    }
+
+    pub fn set_socket_options(mut self, socket: KMBoxNetSocketOptions) -> Self {
+        self.socket = socket;
+        self
+    }
+
+    pub fn set_max_mouse_move(mut self, max_mouse_move: i32) -> Self {
+        self.max_mouse_move = max_mouse_move;
+        self
+    }
+
+    pub fn set_move_pacing(mut self, move_pacing: Duration) -> Self {
+        self.move_pacing = move_pacing;
+        self
+    }
+
+    pub fn set_encrypted(mut self, encrypted: bool) -> Self {
+        self.encrypted = encrypted;
+        self
+    }
+
+    pub fn set_reboot_timeout(mut self, reboot_timeout: Duration) -> Self {
+        self.reboot_timeout = reboot_timeout;
+        self
+    }
+
+    /// The local address the monitor listens on
+    pub fn monitor_addr(&self, socket_addr: SocketAddr) -> Result<SocketAddr, InvalidConfig> {
+        match self.monitor_addr {
+            Some(monitor_addr) => Ok(monitor_addr),
+            None => next_port(socket_addr, 1, "monitor_addr"),
+        }
+    }
+
+    /// The local address the firmware debug log is received on
+    pub fn debug_addr(&self, socket_addr: SocketAddr) -> Result<SocketAddr, InvalidConfig> {
+        match self.debug_addr {
+            Some(debug_addr) => Ok(debug_addr),
+            None => next_port(socket_addr, 2, "debug_addr"),
+        }
+    }
+
+    /// Override the ip, port and uuid with the `KMBOX_IP`, `KMBOX_PORT` and `KMBOX_UUID`
+    /// environment variables when they are set
+    pub fn with_env_overrides(mut self) -> Result<Self, InputMiddlewareConfigError> {
+        if let Ok(ip) = std::env::var("KMBOX_IP") {
+            self.ip = ip;
+        }
+        if let Ok(port) = std::env::var("KMBOX_PORT") {
+            self.port = port
+                .parse()
+                .map_err(|_| InputMiddlewareConfigError::InvalidEnv {
+                    name: "KMBOX_PORT",
+                    value: port,
+                })?;
+        }
+        if let Ok(uuid) = std::env::var("KMBOX_UUID") {
+            self.uuid = uuid;
+        }
+        Ok(self)
+    }
+
+    /// Check every field, resolving the hostname if `ip` is not an ipv4 address
+    pub fn validate(&self) -> Result<(), InvalidConfig> {
+        self.resolve().map(|_| ())
+    }
+
+    /// Validate the config and return the kmbox address and the parsed uuid
+    pub(crate) fn resolve(&self) -> Result<(SocketAddr, u32), InvalidConfig> {
+        let mac = self.mac()?;
+        let socket_addr = self.socket_addr()?;
+        if self.timeout.is_zero() {
+            return Err(InvalidConfig::new("timeout", "must be greater than zero"));
+        }
+        if let Some(local_addr) = self.local_addr {
+            if !local_addr.is_ipv4() {
+                return Err(InvalidConfig::new(
+                    "local_addr",
+                    format!("{local_addr} is not an ipv4 address"),
+                ));
+            }
+        }
+        if let Some(monitor_addr) = self.monitor_addr {
+            if !monitor_addr.is_ipv4() || monitor_addr.port() == 0 {
+                return Err(InvalidConfig::new(
+                    "monitor_addr",
+                    format!("{monitor_addr} is not an ipv4 address with a fixed port"),
+                ));
+            }
+        }
+        if let Some(debug_addr) = self.debug_addr {
+            if !debug_addr.is_ipv4() {
+                return Err(InvalidConfig::new(
+                    "debug_addr",
+                    format!("{debug_addr} is not an ipv4 address"),
+                ));
+            }
+        }
+        if !(1..=MAX_MOUSE_MOVE).contains(&self.max_mouse_move) {
+            return Err(InvalidConfig::new(
+                "max_mouse_move",
+                format!(
+                    "must be in 1..={MAX_MOUSE_MOVE}, got {}",
+                    self.max_mouse_move
+                ),
+            ));
+        }
+        self.monitor_addr(socket_addr)?;
+        self.debug_addr(socket_addr)?;
+        self.socket.validate()?;
+        Ok((socket_addr, mac))
+    }
+
+    /// The uuid parsed into the mac used in every packet header
+    pub fn mac(&self) -> Result<u32, InvalidConfig> {
+        if self.uuid.len() != 8 || !self.uuid.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(InvalidConfig::new(
+                "uuid",
+                format!("expected exactly 8 hex characters, got {:?}", self.uuid),
+            ));
+        }
+        u32::from_str_radix(&self.uuid, 16)
+            .map_err(|e| InvalidConfig::new("uuid", format!("{:?}: {e}", self.uuid)))
+    }
+
+    /// The address of the kmbox, hostnames are resolved to their first ipv4 address
+    pub fn socket_addr(&self) -> Result<SocketAddr, InvalidConfig> {
+        if self.port == 0 {
+            return Err(InvalidConfig::new("port", "must not be 0"));
+        }
+        if let Ok(ip) = self.ip.parse::<IpAddr>() {
+            return match ip {
+                IpAddr::V4(_) => Ok(SocketAddr::new(ip, self.port)),
+                IpAddr::V6(_) => Err(InvalidConfig::new(
+                    "ip",
+                    format!("{ip} is not an ipv4 address"),
+                )),
+            };
+        }
+        (self.ip.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(|e| InvalidConfig::new("ip", format!("failed to resolve {:?}: {e}", self.ip)))?
+            .find(SocketAddr::is_ipv4)
+            .ok_or_else(|| InvalidConfig::new("ip", format!("{:?} has no ipv4 address", self.ip)))
+    }
+}
+
+/// `0.0.0.0` and the port `offset` above the kmbox port, the default of a local address
+fn next_port(
+    socket_addr: SocketAddr,
+    offset: u16,
+    field: &'static str,
+) -> Result<SocketAddr, InvalidConfig> {
+    let port = socket_addr.port().checked_add(offset).ok_or_else(|| {
+        InvalidConfig::new(
+            field,
+            format!(
+                "kmbox port {} + {offset} is out of range, set it explicitly",
+                socket_addr.port()
+            ),
+        )
+    })?;
+    Ok(SocketAddr::new(IpAddr::V4(Ipv4Addr::UNSPECIFIED), port))
+}
+
+/// Builder for a [`KMBoxNetConfig`] that checks every field before handing it out
+#[derive(Debug, Clone, Default)]
+pub struct KMBoxNetConfigBuilder {
+    config: KMBoxNetConfig,
+    dscp: Option<u8>,
+}
+
+impl KMBoxNetConfigBuilder {
+    pub fn ip(mut self, ip: impl Into<String>) -> Self {
+        self.config.ip = ip.into();
+        self
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.config.port = port;
+        self
+    }
+
+    pub fn uuid(mut self, uuid: impl Into<String>) -> Self {
+        self.config.uuid = uuid.into();
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.config.timeout = timeout;
+        self
+    }
+
+    pub fn local_addr(mut self, local_addr: SocketAddr) -> Self {
+        self.config.local_addr = Some(local_addr);
+        self
+    }
+
+    pub fn monitor_addr(mut self, monitor_addr: SocketAddr) -> Self {
+        self.config.monitor_addr = Some(monitor_addr);
+        self
+    }
+
+    pub fn debug_addr(mut self, debug_addr: SocketAddr) -> Self {
+        self.config.debug_addr = Some(debug_addr);
+        self
+    }
+
+    pub fn max_mouse_move(mut self, max_mouse_move: i32) -> Self {
+        self.config.max_mouse_move = max_mouse_move;
+        self
+    }
+
+    pub fn move_pacing(mut self, move_pacing: Duration) -> Self {
+        self.config.move_pacing = move_pacing;
+        self
+    }
+
+    pub fn encrypted(mut self, encrypted: bool) -> Self {
+        self.config.encrypted = encrypted;
+        self
+    }
+
+    pub fn reboot_timeout(mut self, reboot_timeout: Duration) -> Self {
+        self.config.reboot_timeout = reboot_timeout;
+        self
+    }
+
+    /// Bind the sockets to a network interface, linux only
+    pub fn interface(mut self, interface: impl Into<String>) -> Self {
+        self.config.socket.interface = Some(interface.into());
+        self
+    }
+
+    pub fn tos(mut self, tos: u8) -> Self {
+        self.config.socket.tos = Some(tos);
+        self
+    }
+
+    /// Differentiated services code point (0..=63), stored as the upper 6 bits of the tos
+    pub fn dscp(mut self, dscp: u8) -> Self {
+        self.dscp = Some(dscp);
+        self
+    }
+
+    pub fn send_buffer_size(mut self, size: usize) -> Self {
+        self.config.socket.send_buffer_size = Some(size);
+        self
+    }
+
+    pub fn recv_buffer_size(mut self, size: usize) -> Self {
+        self.config.socket.recv_buffer_size = Some(size);
+        self
+    }
+
+    /// Validate the config, see [`KMBoxNetConfig::validate`]
+    pub fn build(mut self) -> Result<KMBoxNetConfig, InvalidConfig> {
+        if let Some(dscp) = self.dscp {
+            if self.config.socket.tos.is_some() {
+                return Err(InvalidConfig::new(
+                    "socket.tos",
+                    "set either tos or dscp, not both",
+                ));
+            }
+            if dscp > 63 {
+                return Err(InvalidConfig::new(
+                    "socket.tos",
+                    format!("dscp must be in 0..=63, got {dscp}"),
+                ));
+            }
+            self.config.socket.tos = Some(dscp << 2);
+        }
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
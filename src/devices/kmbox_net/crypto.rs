@@ -0,0 +1,84 @@
+//! The encrypted command channel of newer kmbox net firmware.
+//!
+//! Only the first 128 bytes of the packet are sent, encrypted with TEA in 8 byte blocks. The
+//! key is the uuid as 4 big endian bytes followed by zeros. The scheme is modelled on the
+//! `kmNet_enc_*` functions of the vendor SDK but not checked against the packets of a real
+//! kmbox yet, so answers are accepted both in plain text and encrypted.
+
+use super::cmd::CMD;
+
+/// Bytes of a packet on the encrypted channel, the header and the start of the payload
+pub const ENCRYPTED_PACKET_SIZE: usize = 128;
+
+const DELTA: u32 = 0x9e37_79b9;
+const ROUNDS: u32 = 32;
+
+/// The TEA key of a kmbox, `mac` is the parsed uuid
+pub fn key_from_mac(mac: u32) -> [u32; 4] {
+    let mut key = [0u8; 16];
+    key[..4].copy_from_slice(&mac.to_be_bytes());
+    [0, 4, 8, 12].map(|i| u32::from_le_bytes(key[i..i + 4].try_into().unwrap()))
+}
+
+/// Commands that go over the encrypted channel, connect and the control commands stay plain
+pub fn is_encrypted(cmd: CMD) -> bool {
+    matches!(
+        cmd,
+        CMD::MOUSE_MOVE
+            | CMD::MOUSE_LEFT
+            | CMD::MOUSE_MIDDLE
+            | CMD::MOUSE_RIGHT
+            | CMD::MOUSE_WHEEL
+            | CMD::MOUSE_AUTOMOVE
+            | CMD::KEYBOARD_ALL
+    )
+}
+
+pub fn encrypt_block([mut v0, mut v1]: [u32; 2], key: &[u32; 4]) -> [u32; 2] {
+    let mut sum = 0u32;
+    for _ in 0..ROUNDS {
+        sum = sum.wrapping_add(DELTA);
+        v0 = v0.wrapping_add(
+            (v1 << 4).wrapping_add(key[0]) ^ v1.wrapping_add(sum) ^ (v1 >> 5).wrapping_add(key[1]),
+        );
+        v1 = v1.wrapping_add(
+            (v0 << 4).wrapping_add(key[2]) ^ v0.wrapping_add(sum) ^ (v0 >> 5).wrapping_add(key[3]),
+        );
+    }
+    [v0, v1]
+}
+
+pub fn decrypt_block([mut v0, mut v1]: [u32; 2], key: &[u32; 4]) -> [u32; 2] {
+    let mut sum = DELTA.wrapping_mul(ROUNDS);
+    for _ in 0..ROUNDS {
+        v1 = v1.wrapping_sub(
+            (v0 << 4).wrapping_add(key[2]) ^ v0.wrapping_add(sum) ^ (v0 >> 5).wrapping_add(key[3]),
+        );
+        v0 = v0.wrapping_sub(
+            (v1 << 4).wrapping_add(key[0]) ^ v1.wrapping_add(sum) ^ (v1 >> 5).wrapping_add(key[1]),
+        );
+        sum = sum.wrapping_sub(DELTA);
+    }
+    [v0, v1]
+}
+
+fn map_blocks(
+    packet: &mut [u8; ENCRYPTED_PACKET_SIZE],
+    key: &[u32; 4],
+    cipher: fn([u32; 2], &[u32; 4]) -> [u32; 2],
+) {
+    for block in packet.chunks_exact_mut(8) {
+        let word = |i: usize| u32::from_le_bytes(block[i..i + 4].try_into().unwrap());
+        let [v0, v1] = cipher([word(0), word(4)], key);
+        block[..4].copy_from_slice(&v0.to_le_bytes());
+        block[4..].copy_from_slice(&v1.to_le_bytes());
+    }
+}
+
+pub fn encrypt_packet(packet: &mut [u8; ENCRYPTED_PACKET_SIZE], key: &[u32; 4]) {
+    map_blocks(packet, key, encrypt_block);
+}
+
+pub fn decrypt_packet(packet: &mut [u8; ENCRYPTED_PACKET_SIZE], key: &[u32; 4]) {
+    map_blocks(packet, key, decrypt_block);
+}
@@ -0,0 +1,111 @@
+//! The text log the firmware sends once debugging is enabled with [`CMD::DEBUG`](super::cmd::CMD)
+
+use std::{
+    collections::VecDeque,
+    net::{SocketAddr, UdpSocket},
+    time::Duration,
+};
+
+use socket2::{Domain, Protocol, Socket, Type};
+
+use crate::logging::info;
+
+use super::{errors::KMBoxNetConnectionError, KMBoxNetSocketOptions};
+
+/// Log records of the firmware are sent with this target
+pub const FIRMWARE_LOG_TARGET: &str = "kmbox_net::firmware";
+
+/// Receives the debug log of a kmbox, see [`KMBoxNet::debug_log`](super::KMBoxNet::debug_log).
+/// A datagram can hold several lines, they are handed out one by one.
+#[derive(Debug)]
+pub struct KMBoxNetDebugLog {
+    socket: UdpSocket,
+    local_addr: SocketAddr,
+    pending: VecDeque<String>,
+}
+
+impl KMBoxNetDebugLog {
+    /// Bind a socket on `local_addr` with the socket options applied
+    pub fn bind(
+        local_addr: SocketAddr,
+        options: &KMBoxNetSocketOptions,
+    ) -> Result<Self, KMBoxNetConnectionError> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))
+            .map_err(KMBoxNetConnectionError)?;
+        options.apply(&socket).map_err(KMBoxNetConnectionError)?;
+        socket
+            .bind(&local_addr.into())
+            .map_err(KMBoxNetConnectionError)?;
+        let socket = UdpSocket::from(socket);
+        let local_addr = socket.local_addr().map_err(KMBoxNetConnectionError)?;
+        socket
+            .set_read_timeout(Some(Duration::from_secs(3)))
+            .map_err(KMBoxNetConnectionError)?;
+        Ok(Self {
+            socket,
+            local_addr,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// The address the log is received on, with the port the OS picked if it was 0
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// How long [`recv_line`](Self::recv_line) waits, `None` waits forever
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) -> Result<(), std::io::Error> {
+        self.socket.set_read_timeout(timeout)
+    }
+
+    /// The next line of the log without the line break, a timeout is returned as error
+    pub fn recv_line(&mut self) -> Result<String, KMBoxNetConnectionError> {
+        loop {
+            if let Some(line) = self.pending.pop_front() {
+                return Ok(line);
+            }
+            let mut buffer = [0u8; 2048];
+            let length = self
+                .socket
+                .recv(&mut buffer)
+                .map_err(KMBoxNetConnectionError)?;
+            let text = String::from_utf8_lossy(&buffer[..length]);
+            self.pending.extend(
+                text.split('\n')
+                    .map(|line| line.trim_end_matches(['\r', '\0']))
+                    .filter(|line| !line.is_empty())
+                    .map(str::to_string),
+            );
+        }
+    }
+
+    /// Log every line as info record with the [`FIRMWARE_LOG_TARGET`] until the socket fails,
+    /// timeouts are skipped
+    pub fn forward_to_log(&mut self) -> Result<(), KMBoxNetConnectionError> {
+        for line in self {
+            info!(target: FIRMWARE_LOG_TARGET, "{}", line?);
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for KMBoxNetDebugLog {
+    type Item = Result<String, KMBoxNetConnectionError>;
+
+    /// Blocks until the next line, timeouts are skipped
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.recv_line() {
+                Err(e)
+                    if matches!(
+                        e.0.kind(),
+                        std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                    ) =>
+                {
+                    continue
+                }
+                result => return Some(result),
+            }
+        }
+    }
+}
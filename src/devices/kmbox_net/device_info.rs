@@ -0,0 +1,35 @@
+use std::{fmt, net::SocketAddr, time::Duration};
+
+/// What is known about a connected kmbox, see [`KMBoxNet::device_info`](super::KMBoxNet::device_info).
+/// The kmbox only echoes the connect request, so the uuid is all it reports about itself.
+/// The firmware version and the VID and PID of the attached devices are not available.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeviceInfo {
+    /// the uuid the kmbox answered with, as shown on its display
+    pub uuid: String,
+    pub mac: u32,
+    /// address of the kmbox
+    pub addr: SocketAddr,
+    /// local address of the command socket
+    pub local_addr: Option<SocketAddr>,
+    /// where the monitor and debug log are received
+    pub monitor_addr: SocketAddr,
+    pub debug_addr: SocketAddr,
+    pub encrypted: bool,
+    /// round trip time of the connect request
+    pub latency: Duration,
+}
+
+impl fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "uuid       {}", self.uuid)?;
+        writeln!(f, "address    {}", self.addr)?;
+        if let Some(local_addr) = self.local_addr {
+            writeln!(f, "local      {local_addr}")?;
+        }
+        writeln!(f, "monitor    {}", self.monitor_addr)?;
+        writeln!(f, "debug log  {}", self.debug_addr)?;
+        writeln!(f, "encrypted  {}", self.encrypted)?;
+        write!(f, "latency    {:?}", self.latency)
+    }
+}
@@ -0,0 +1,273 @@
+//! A kmbox net on localhost to try things and run tests without the hardware.
+//!
+//! The emulator answers like the kmbox, plain packets are echoed and packets of the
+//! encrypted channel are decrypted first. The state it ends up in can be inspected.
+//! Slow answers and encrypted answers can be switched on while it runs.
+//! A reboot is not answered and nothing is received for [`EMULATOR_REBOOT_TIME`].
+
+use std::{
+    io,
+    net::{SocketAddr, UdpSocket},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, MutexGuard,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use super::{
+    cmd::CMD,
+    crypto::{self, ENCRYPTED_PACKET_SIZE},
+    KMBoxNetConfig,
+};
+
+const HEAD_SIZE: usize = 16;
+/// how long the emulator ignores every packet after a reboot
+pub const EMULATOR_REBOOT_TIME: Duration = Duration::from_millis(700);
+/// how often the emulator thread checks if it should stop
+const POLL: Duration = Duration::from_millis(20);
+
+/// A command the emulator accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EmulatedCommand {
+    pub cmd: CMD,
+    pub rand: u32,
+    /// it came over the encrypted channel
+    pub encrypted: bool,
+}
+
+/// What the emulated kmbox received so far
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmulatorState {
+    pub commands: Vec<EmulatedCommand>,
+    /// held mouse buttons, bit 0 is left
+    pub buttons: u8,
+    /// sum of all relative moves
+    pub position: [i64; 2],
+    pub wheel: i64,
+    /// modifier bits of the keyboard report
+    pub modifiers: u8,
+    pub keys: [u8; 10],
+    /// the argument of the last mask command, 0 when unmasked
+    pub mask: u32,
+    /// port the debug log is sent to while it is enabled
+    pub debug_port: Option<u16>,
+    /// port the monitor data would be sent to
+    pub monitor_port: Option<u16>,
+    /// reboot commands received, a reboot clears the held input, mask, monitor and debug log
+    pub reboots: u32,
+    /// packets that are too short or carry the wrong uuid, they are not answered
+    pub rejected: u64,
+}
+
+/// How the emulator answers, changed while it runs
+#[derive(Debug, Clone, Copy, Default)]
+struct EmulatorOptions {
+    latency: Duration,
+    encrypted_replies: bool,
+}
+
+#[derive(Debug)]
+pub struct KMBoxNetEmulator {
+    addr: SocketAddr,
+    uuid: String,
+    state: Arc<Mutex<EmulatorState>>,
+    options: Arc<Mutex<EmulatorOptions>>,
+    running: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl KMBoxNetEmulator {
+    /// Start an emulator with the uuid on a free port of localhost
+    pub fn start(uuid: &str) -> io::Result<Self> {
+        Self::start_on("127.0.0.1:0".parse().unwrap(), uuid)
+    }
+
+    pub fn start_on(addr: SocketAddr, uuid: &str) -> io::Result<Self> {
+        let mac = KMBoxNetConfig::default_with_uuid(uuid)
+            .mac()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let socket = UdpSocket::bind(addr)?;
+        socket.set_read_timeout(Some(POLL))?;
+        let addr = socket.local_addr()?;
+        let state = Arc::new(Mutex::new(EmulatorState::default()));
+        let options = Arc::new(Mutex::new(EmulatorOptions::default()));
+        let running = Arc::new(AtomicBool::new(true));
+        let thread = {
+            let state = state.clone();
+            let options = options.clone();
+            let running = running.clone();
+            thread::spawn(move || serve(socket, mac, &state, &options, &running))
+        };
+        Ok(Self {
+            addr,
+            uuid: uuid.to_string(),
+            state,
+            options,
+            running,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// A config that connects to this emulator
+    pub fn config(&self) -> KMBoxNetConfig {
+        KMBoxNetConfig::new(&self.addr.ip().to_string(), self.addr.port(), &self.uuid)
+    }
+
+    /// Snapshot of what was received so far
+    pub fn state(&self) -> EmulatorState {
+        self.state.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Answer the packets received from now on this late, default is right away
+    pub fn set_latency(&self, latency: Duration) {
+        self.options().latency = latency;
+    }
+
+    /// Answer the packets of the encrypted channel encrypted instead of plain, default is plain
+    pub fn set_encrypted_replies(&self, encrypted_replies: bool) {
+        self.options().encrypted_replies = encrypted_replies;
+    }
+
+    fn options(&self) -> MutexGuard<'_, EmulatorOptions> {
+        self.options.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for KMBoxNetEmulator {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+fn serve(
+    socket: UdpSocket,
+    mac: u32,
+    state: &Mutex<EmulatorState>,
+    options: &Mutex<EmulatorOptions>,
+    running: &AtomicBool,
+) {
+    let key = crypto::key_from_mac(mac);
+    let mut buffer = [0u8; 2048];
+    let mut debug_to = None;
+    let mut down_until = None;
+    while running.load(Ordering::SeqCst) {
+        let (length, from) = match socket.recv_from(&mut buffer) {
+            Ok(received) => received,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+                ) =>
+            {
+                continue;
+            }
+            Err(_) => return,
+        };
+        if down_until.is_some_and(|until| Instant::now() < until) {
+            continue;
+        }
+        let packet = &mut buffer[..length];
+        // only the encrypted channel sends exactly 128 bytes
+        let encrypted = length == ENCRYPTED_PACKET_SIZE;
+        if encrypted {
+            crypto::decrypt_packet(packet.try_into().unwrap(), &key);
+        }
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        if length < HEAD_SIZE || word(packet, 0) != mac {
+            state.rejected += 1;
+            continue;
+        }
+        let cmd = CMD::from_code(word(packet, 12));
+        let rand = word(packet, 4);
+        let indexpts = word(packet, 8);
+        apply(&mut state, cmd, rand, &packet[HEAD_SIZE..]);
+        if cmd == CMD::DEBUG {
+            debug_to = state
+                .debug_port
+                .map(|port| SocketAddr::new(from.ip(), port));
+        }
+        state.commands.push(EmulatedCommand {
+            cmd,
+            rand,
+            encrypted,
+        });
+        if cmd == CMD::REBOOT {
+            debug_to = None;
+            down_until = Some(Instant::now() + EMULATOR_REBOOT_TIME);
+            continue;
+        }
+        drop(state);
+        let options = *options.lock().unwrap_or_else(|e| e.into_inner());
+        if encrypted && options.encrypted_replies {
+            crypto::encrypt_packet(packet.try_into().unwrap(), &key);
+        }
+        if options.latency.is_zero() {
+            let _ = socket.send_to(packet, from);
+        } else if let Ok(socket) = socket.try_clone() {
+            let (packet, latency) = (packet.to_vec(), options.latency);
+            thread::spawn(move || {
+                thread::sleep(latency);
+                let _ = socket.send_to(&packet, from);
+            });
+        }
+        // the log line of every command the emulator handled
+        if let Some(debug_to) = debug_to {
+            let line = format!("{cmd} #{indexpts} rand={rand:#010x}\r\n");
+            let _ = socket.send_to(line.as_bytes(), debug_to);
+        }
+    }
+}
+
+fn word(packet: &[u8], offset: usize) -> u32 {
+    packet
+        .get(offset..offset + 4)
+        .map_or(0, |bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn apply(state: &mut EmulatorState, cmd: CMD, rand: u32, payload: &[u8]) {
+    match cmd {
+        CMD::MOUSE_MOVE
+        | CMD::MOUSE_LEFT
+        | CMD::MOUSE_MIDDLE
+        | CMD::MOUSE_RIGHT
+        | CMD::MOUSE_WHEEL
+        | CMD::MOUSE_AUTOMOVE => {
+            let signed = |i: usize| i64::from(word(payload, i * 4) as i32);
+            state.buttons = word(payload, 0) as u8;
+            state.position[0] += signed(1);
+            state.position[1] += signed(2);
+            state.wheel += signed(3);
+        }
+        CMD::KEYBOARD_ALL => {
+            state.modifiers = payload.first().copied().unwrap_or_default();
+            if let Some(keys) = payload.get(2..12) {
+                state.keys.copy_from_slice(keys);
+            }
+        }
+        CMD::MASK_MOUSE => state.mask = rand,
+        CMD::UNMASK_ALL => state.mask = 0,
+        // the port in the low half and the enable flag in the high half
+        CMD::DEBUG => state.debug_port = (rand >> 16 != 0).then_some(rand as u16),
+        CMD::MONITOR => state.monitor_port = (rand >> 16 == 0xaa55).then_some(rand as u16),
+        CMD::REBOOT => {
+            *state = EmulatorState {
+                commands: std::mem::take(&mut state.commands),
+                position: state.position,
+                wheel: state.wheel,
+                reboots: state.reboots + 1,
+                rejected: state.rejected,
+                ..EmulatorState::default()
+            }
+        }
+        _ => {}
+    }
+}
@@ -15,3 +15,40 @@ impl From<KMBoxNetSendError> for InputMiddlewareSendError {
         InputMiddlewareSendError(e.0)
     }
 }
+
+/// The kmbox sent or was asked to handle something this crate does not understand
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum KMBoxNetProtocolError {
+    #[error("unknown kmbox command {0:#010x}")]
+    UnknownCommand(u32),
+    #[error("unknown kmbox command `{0}`")]
+    UnknownCommandName(String),
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("invalid kmbox net config `{field}`: {reason}")]
+pub struct InvalidConfig {
+    pub field: &'static str,
+    pub reason: String,
+}
+
+impl InvalidConfig {
+    pub(crate) fn new(field: &'static str, reason: impl Into<String>) -> Self {
+        Self {
+            field,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl From<InvalidConfig> for KMBoxNetConnectionError {
+    fn from(e: InvalidConfig) -> Self {
+        KMBoxNetConnectionError(std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+    }
+}
+
+impl From<InvalidConfig> for KMBoxNetSendError {
+    fn from(e: InvalidConfig) -> Self {
+        KMBoxNetSendError(std::io::Error::new(std::io::ErrorKind::InvalidInput, e))
+    }
+}
@@ -1,228 +1,8 @@
 use crate::keyboardkeys::KeyboardKey;
 
 impl KeyboardKey {
+    /// The kmbox net sends the plain HID usage in its keyboard report
     pub fn as_kmbox_net_u8(&self) -> u8 {
-        match self {
-            Self::KEY_NONE => 0x00,
-            Self::KEY_ERRORROLLOVER => 0x01,
-            Self::KEY_POSTFAIL => 0x02,
-            Self::KEY_ERRORUNDEFINED => 0x03,
-            Self::KEY_A => 0x04,
-            Self::KEY_B => 0x05,
-            Self::KEY_C => 0x06,
-            Self::KEY_D => 0x07,
-            Self::KEY_E => 0x08,
-            Self::KEY_F => 0x09,
-            Self::KEY_G => 0x0A,
-            Self::KEY_H => 0x0B,
-            Self::KEY_I => 0x0C,
-            Self::KEY_J => 0x0D,
-            Self::KEY_K => 0x0E,
-            Self::KEY_L => 0x0F,
-            Self::KEY_M => 0x10,
-            Self::KEY_N => 0x11,
-            Self::KEY_O => 0x12,
-            Self::KEY_P => 0x13,
-            Self::KEY_Q => 0x14,
-            Self::KEY_R => 0x15,
-            Self::KEY_S => 0x16,
-            Self::KEY_T => 0x17,
-            Self::KEY_U => 0x18,
-            Self::KEY_V => 0x19,
-            Self::KEY_W => 0x1A,
-            Self::KEY_X => 0x1B,
-            Self::KEY_Y => 0x1C,
-            Self::KEY_Z => 0x1D,
-            Self::KEY_1_EXCLAMATION_MARK => 0x1E,
-            Self::KEY_2_AT => 0x1F,
-            Self::KEY_3_NUMBER_SIGN => 0x20,
-            Self::KEY_4_DOLLAR => 0x21,
-            Self::KEY_5_PERCENT => 0x22,
-            Self::KEY_6_CARET => 0x23,
-            Self::KEY_7_AMPERSAND => 0x24,
-            Self::KEY_8_ASTERISK => 0x25,
-            Self::KEY_9_OPARENTHESIS => 0x26,
-            Self::KEY_0_CPARENTHESIS => 0x27,
-            Self::KEY_ENTER => 0x28,
-            Self::KEY_ESCAPE => 0x29,
-            Self::KEY_BACKSPACE => 0x2A,
-            Self::KEY_TAB => 0x2B,
-            Self::KEY_SPACEBAR => 0x2C,
-            Self::KEY_MINUS_UNDERSCORE => 0x2D,
-            Self::KEY_EQUAL_PLUS => 0x2E,
-            Self::KEY_OBRACKET_AND_OBRACE => 0x2F,
-            Self::KEY_CBRACKET_AND_CBRACE => 0x30,
-            Self::KEY_BACKSLASH_VERTICAL_BAR => 0x31,
-            Self::KEY_NONUS_NUMBER_SIGN_TILDE => 0x32,
-            Self::KEY_SEMICOLON_COLON => 0x33,
-            Self::KEY_SINGLE_AND_DOUBLE_QUOTE => 0x34,
-            Self::KEY_GRAVE_ACCENT_AND_TILDE => 0x35,
-            Self::KEY_COMMA_AND_LESS => 0x36,
-            Self::KEY_DOT_GREATER => 0x37,
-            Self::KEY_SLASH_QUESTION => 0x38,
-            Self::KEY_CAPS_LOCK => 0x39,
-            Self::KEY_F1 => 0x3A,
-            Self::KEY_F2 => 0x3B,
-            Self::KEY_F3 => 0x3C,
-            Self::KEY_F4 => 0x3D,
-            Self::KEY_F5 => 0x3E,
-            Self::KEY_F6 => 0x3F,
-            Self::KEY_F7 => 0x40,
-            Self::KEY_F8 => 0x41,
-            Self::KEY_F9 => 0x42,
-            Self::KEY_F10 => 0x43,
-            Self::KEY_F11 => 0x44,
-            Self::KEY_F12 => 0x45,
-            Self::KEY_PRINTSCREEN => 0x46,
-            Self::KEY_SCROLL_LOCK => 0x47,
-            Self::KEY_PAUSE => 0x48,
-            Self::KEY_INSERT => 0x49,
-            Self::KEY_HOME => 0x4A,
-            Self::KEY_PAGEUP => 0x4B,
-            Self::KEY_DELETE => 0x4C,
-            Self::KEY_END1 => 0x4D,
-            Self::KEY_PAGEDOWN => 0x4E,
-            Self::KEY_RIGHTARROW => 0x4F,
-            Self::KEY_LEFTARROW => 0x50,
-            Self::KEY_DOWNARROW => 0x51,
-            Self::KEY_UPARROW => 0x52,
-            Self::KEY_KEYPAD_NUM_LOCK_AND_CLEAR => 0x53,
-            Self::KEY_KEYPAD_SLASH => 0x54,
-            Self::KEY_KEYPAD_ASTERIKS => 0x55,
-            Self::KEY_KEYPAD_MINUS => 0x56,
-            Self::KEY_KEYPAD_PLUS => 0x57,
-            Self::KEY_KEYPAD_ENTER => 0x58,
-            Self::KEY_KEYPAD_1_END => 0x59,
-            Self::KEY_KEYPAD_2_DOWN_ARROW => 0x5A,
-            Self::KEY_KEYPAD_3_PAGEDN => 0x5B,
-            Self::KEY_KEYPAD_4_LEFT_ARROW => 0x5C,
-            Self::KEY_KEYPAD_5 => 0x5D,
-            Self::KEY_KEYPAD_6_RIGHT_ARROW => 0x5E,
-            Self::KEY_KEYPAD_7_HOME => 0x5F,
-            Self::KEY_KEYPAD_8_UP_ARROW => 0x60,
-            Self::KEY_KEYPAD_9_PAGEUP => 0x61,
-            Self::KEY_KEYPAD_0_INSERT => 0x62,
-            Self::KEY_KEYPAD_DECIMAL_SEPARATOR_DELETE => 0x63,
-            Self::KEY_NONUS_BACK_SLASH_VERTICAL_BAR => 0x64,
-            Self::KEY_APPLICATION => 0x65,
-            Self::KEY_POWER => 0x66,
-            Self::KEY_KEYPAD_EQUAL => 0x67,
-            Self::KEY_F13 => 0x68,
-            Self::KEY_F14 => 0x69,
-            Self::KEY_F15 => 0x6A,
-            Self::KEY_F16 => 0x6B,
-            Self::KEY_F17 => 0x6C,
-            Self::KEY_F18 => 0x6D,
-            Self::KEY_F19 => 0x6E,
-            Self::KEY_F20 => 0x6F,
-            Self::KEY_F21 => 0x70,
-            Self::KEY_F22 => 0x71,
-            Self::KEY_F23 => 0x72,
-            Self::KEY_F24 => 0x73,
-            Self::KEY_EXECUTE => 0x74,
-            Self::KEY_HELP => 0x75,
-            Self::KEY_MENU => 0x76,
-            Self::KEY_SELECT => 0x77,
-            Self::KEY_STOP => 0x78,
-            Self::KEY_AGAIN => 0x79,
-            Self::KEY_UNDO => 0x7A,
-            Self::KEY_CUT => 0x7B,
-            Self::KEY_COPY => 0x7C,
-            Self::KEY_PASTE => 0x7D,
-            Self::KEY_FIND => 0x7E,
-            Self::KEY_MUTE => 0x7F,
-            Self::KEY_VOLUME_UP => 0x80,
-            Self::KEY_VOLUME_DOWN => 0x81,
-            Self::KEY_LOCKING_CAPS_LOCK => 0x82,
-            Self::KEY_LOCKING_NUM_LOCK => 0x83,
-            Self::KEY_LOCKING_SCROLL_LOCK => 0x84,
-            Self::KEY_KEYPAD_COMMA => 0x85,
-            Self::KEY_KEYPAD_EQUAL_SIGN => 0x86,
-            Self::KEY_INTERNATIONAL1 => 0x87,
-            Self::KEY_INTERNATIONAL2 => 0x88,
-            Self::KEY_INTERNATIONAL3 => 0x89,
-            Self::KEY_INTERNATIONAL4 => 0x8A,
-            Self::KEY_INTERNATIONAL5 => 0x8B,
-            Self::KEY_INTERNATIONAL6 => 0x8C,
-            Self::KEY_INTERNATIONAL7 => 0x8D,
-            Self::KEY_INTERNATIONAL8 => 0x8E,
-            Self::KEY_INTERNATIONAL9 => 0x8F,
-            Self::KEY_LANG1 => 0x90,
-            Self::KEY_LANG2 => 0x91,
-            Self::KEY_LANG3 => 0x92,
-            Self::KEY_LANG4 => 0x93,
-            Self::KEY_LANG5 => 0x94,
-            Self::KEY_LANG6 => 0x95,
-            Self::KEY_LANG7 => 0x96,
-            Self::KEY_LANG8 => 0x97,
-            Self::KEY_LANG9 => 0x98,
-            Self::KEY_ALTERNATE_ERASE => 0x99,
-            Self::KEY_SYSREQ => 0x9A,
-            Self::KEY_CANCEL => 0x9B,
-            Self::KEY_CLEAR => 0x9C,
-            Self::KEY_PRIOR => 0x9D,
-            Self::KEY_RETURN => 0x9E,
-            Self::KEY_SEPARATOR => 0x9F,
-            Self::KEY_OUT => 0xA0,
-            Self::KEY_OPER => 0xA1,
-            Self::KEY_CLEAR_AGAIN => 0xA2,
-            Self::KEY_CRSEL => 0xA3,
-            Self::KEY_EXSEL => 0xA4,
-            Self::KEY_KEYPAD_00 => 0xB0,
-            Self::KEY_KEYPAD_000 => 0xB1,
-            Self::KEY_THOUSANDS_SEPARATOR => 0xB2,
-            Self::KEY_DECIMAL_SEPARATOR => 0xB3,
-            Self::KEY_CURRENCY_UNIT => 0xB4,
-            Self::KEY_CURRENCY_SUB_UNIT => 0xB5,
-            Self::KEY_KEYPAD_OPARENTHESIS => 0xB6,
-            Self::KEY_KEYPAD_CPARENTHESIS => 0xB7,
-            Self::KEY_KEYPAD_OBRACE => 0xB8,
-            Self::KEY_KEYPAD_CBRACE => 0xB9,
-            Self::KEY_KEYPAD_TAB => 0xBA,
-            Self::KEY_KEYPAD_BACKSPACE => 0xBB,
-            Self::KEY_KEYPAD_A => 0xBC,
-            Self::KEY_KEYPAD_B => 0xBD,
-            Self::KEY_KEYPAD_C => 0xBE,
-            Self::KEY_KEYPAD_D => 0xBF,
-            Self::KEY_KEYPAD_E => 0xC0,
-            Self::KEY_KEYPAD_F => 0xC1,
-            Self::KEY_KEYPAD_XOR => 0xC2,
-            Self::KEY_KEYPAD_CARET => 0xC3,
-            Self::KEY_KEYPAD_PERCENT => 0xC4,
-            Self::KEY_KEYPAD_LESS => 0xC5,
-            Self::KEY_KEYPAD_GREATER => 0xC6,
-            Self::KEY_KEYPAD_AMPERSAND => 0xC7,
-            Self::KEY_KEYPAD_LOGICAL_AND => 0xC8,
-            Self::KEY_KEYPAD_VERTICAL_BAR => 0xC9,
-            Self::KEY_KEYPAD_LOGICAL_OR => 0xCA,
-            Self::KEY_KEYPAD_COLON => 0xCB,
-            Self::KEY_KEYPAD_NUMBER_SIGN => 0xCC,
-            Self::KEY_KEYPAD_SPACE => 0xCD,
-            Self::KEY_KEYPAD_AT => 0xCE,
-            Self::KEY_KEYPAD_EXCLAMATION_MARK => 0xCF,
-            Self::KEY_KEYPAD_MEMORY_STORE => 0xD0,
-            Self::KEY_KEYPAD_MEMORY_RECALL => 0xD1,
-            Self::KEY_KEYPAD_MEMORY_CLEAR => 0xD2,
-            Self::KEY_KEYPAD_MEMORY_ADD => 0xD3,
-            Self::KEY_KEYPAD_MEMORY_SUBTRACT => 0xD4,
-            Self::KEY_KEYPAD_MEMORY_MULTIPLY => 0xD5,
-            Self::KEY_KEYPAD_MEMORY_DIVIDE => 0xD6,
-            Self::KEY_KEYPAD_PLUSMINUS => 0xD7,
-            Self::KEY_KEYPAD_CLEAR => 0xD8,
-            Self::KEY_KEYPAD_CLEAR_ENTRY => 0xD9,
-            Self::KEY_KEYPAD_BINARY => 0xDA,
-            Self::KEY_KEYPAD_OCTAL => 0xDB,
-            Self::KEY_KEYPAD_DECIMAL => 0xDC,
-            Self::KEY_KEYPAD_HEXADECIMAL => 0xDD,
-            Self::KEY_LEFTCONTROL => 0xE0,
-            Self::KEY_LEFTSHIFT => 0xE1,
-            Self::KEY_LEFTALT => 0xE2,
-            Self::KEY_LEFT_GUI => 0xE3,
-            Self::KEY_RIGHTCONTROL => 0xE4,
-            Self::KEY_RIGHTSHIFT => 0xE5,
-            Self::KEY_RIGHTALT => 0xE6,
-            Self::KEY_RIGHT_GUI => 0xE7,
-            // _ => panic!("Invalid KeyboardKey"),
-        }
+        self.hid_usage()
     }
 }
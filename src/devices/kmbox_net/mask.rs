@@ -0,0 +1,54 @@
+use std::ops::{BitOr, BitOrAssign};
+
+/// Inputs of the physical mouse that the kmbox stops forwarding to the PC.
+/// Combine them with `|`, e.g. `KMBoxNetMask::LEFT | KMBoxNetMask::WHEEL`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct KMBoxNetMask(u8);
+
+impl KMBoxNetMask {
+    pub const NONE: Self = Self(0);
+    pub const LEFT: Self = Self(1 << 0);
+    pub const RIGHT: Self = Self(1 << 1);
+    pub const MIDDLE: Self = Self(1 << 2);
+    pub const SIDE1: Self = Self(1 << 3);
+    pub const SIDE2: Self = Self(1 << 4);
+    pub const X: Self = Self(1 << 5);
+    pub const Y: Self = Self(1 << 6);
+    pub const WHEEL: Self = Self(1 << 7);
+
+    /// The mask as sent to the kmbox
+    pub const fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub const fn from_bits(bits: u8) -> Self {
+        Self(bits)
+    }
+
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Add `other` if it is not set, otherwise remove it
+    pub const fn toggle(self, other: Self) -> Self {
+        Self(self.0 ^ other.0)
+    }
+}
+
+impl BitOr for KMBoxNetMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for KMBoxNetMask {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
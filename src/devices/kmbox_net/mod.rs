@@ -1,14 +1,17 @@
 use std::{
+    io::Write,
     mem::MaybeUninit,
-    net::{IpAddr, Ipv4Addr, SocketAddr},
-    time::Duration,
+    net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4},
+    path::Path,
+    time::{Duration, Instant, SystemTime},
 };
 
-use log::{debug, error, info};
+use crate::logging::{debug, error, info, trace, warn};
 use socket2::{Domain, Protocol, Socket, Type};
 
 use crate::{
-    button_state::{ButtonState, MwheelState},
+    button_state::{ButtonState, MouseButton, MwheelState},
+    capabilities::{split_mouse_move, DeviceCapabilities},
     devices::kmbox_net::{
         cmd::CMD,
         errors::{KMBoxNetConnectionError, KMBoxNetSendError},
@@ -18,40 +21,67 @@ use crate::{
     InputMiddlewareDeviceAction,
 };
 
-use self::structs::{ClientTx, MonitorData};
+use self::structs::{ClientTx, SoftKeyboard, SoftMouse};
 
+pub use self::capture::{CaptureDirection, CaptureReader, CaptureWriter, CapturedPacket};
+pub use self::config::{KMBoxNetConfig, KMBoxNetConfigBuilder, KMBoxNetSocketOptions};
+pub use self::debug_log::{KMBoxNetDebugLog, FIRMWARE_LOG_TARGET};
+pub use self::device_info::DeviceInfo;
+pub use self::emulator::{EmulatedCommand, EmulatorState, KMBoxNetEmulator, EMULATOR_REBOOT_TIME};
+pub use self::mask::KMBoxNetMask;
+pub use self::reboot::KMBoxNetReboot;
+pub use self::stats::{CommandStats, KMBoxNetStats, RttHistogram, SendOutcome, RTT_BUCKETS};
+pub use self::structs::{MonitorData, MonitorKeyboardData, MonitorMouseData};
+
+mod capture;
 pub mod cmd;
 mod cmd_instruction;
+mod config;
+pub mod crypto;
+mod debug_log;
+mod device_info;
+mod emulator;
 pub mod errors;
-mod key_instructions;
 mod keyboard;
+mod mask;
+mod reboot;
+mod stats;
 pub(crate) mod structs;
 
-/// Convert a hex string to a u32
-fn to_hex(src: &str, len: usize) -> u32 {
-    let mut dest: [u32; 16] = [0; 16];
-    for i in 0..len {
-        let bytes = src.as_bytes();
-        let h1 = bytes[2 * i] as char;
-        let h2 = bytes[2 * i + 1] as char;
-        let mut s1 = h1.to_ascii_uppercase() as u32 - 0x30;
-        if s1 > 9 {
-            s1 -= 7;
-        }
-        let mut s2 = h2.to_ascii_uppercase() as u32 - 0x30;
-        if s2 > 9 {
-            s2 -= 7;
-        }
-        dest[i] = s1 * 16 + s2;
-    }
-    dest[0] << 24 | dest[1] << 16 | dest[2] << 8 | dest[3]
-}
+/// HID usage of the left control key, the modifiers up to right gui are sent as bits in `ctrl`
+const MODIFIER_FIRST: u8 = 0xE0;
+const MODIFIER_LAST: u8 = 0xE7;
 
 /// T can be ClientTx or MonitorData
 #[derive(Debug)]
 pub struct KMBoxNet {
     socket: Socket,
     socket_addr: SocketAddr,
+    /// the uuid parsed once on connect
+    mac: u32,
+    config: KMBoxNetConfig,
+    /// the mouse report, buttons stay set until they are released
+    mouse: SoftMouse,
+    /// the keyboard report with the held keys
+    keyboard: SoftKeyboard,
+    /// masked mouse inputs in the low byte and the masked key in the second byte
+    mask: u32,
+    /// round trip times and results of the sent commands
+    stats: KMBoxNetStats,
+    /// every sent and received packet is written here while capturing
+    capture: Option<CaptureWriter<Box<dyn Write + Send>>>,
+    /// key of the encrypted channel when the config enables it
+    key: Option<[u32; 4]>,
+    /// port the firmware debug log is sent to while it is enabled
+    debug_port: Option<u16>,
+    /// port the monitor data is sent to once the monitor was enabled
+    monitor_port: Option<u16>,
+    /// address set with `set_config`, the kmbox uses it after the next reboot
+    next_addr: Option<SocketAddrV4>,
+    /// the kmbox was told to reboot, there is nothing to release or unmask on drop
+    rebooted: bool,
+    /// bytes of the last answer, the rest of rx is left over from earlier answers
+    rx_length: usize,
     /// rx is the response from the kmbox
     rx: MaybeUninit<ClientTx>,
     /// tx is the request to the kmbox
@@ -65,51 +95,6 @@ pub struct KMBoxNetMonitor {
     monitor: MaybeUninit<MonitorData>,
 }
 
-#[derive(Debug, Clone)]
-pub struct KMBoxNetConfig {
-    pub ip: String,
-    pub port: u16,
-    pub uuid: String,
-    /// default timeout is 3 seconds
-    pub timeout: Duration,
-}
-
-impl Default for KMBoxNetConfig {
-    fn default() -> Self {
-        Self {
-            ip: "192.168.2.188".into(),
-            port: 16824,
-            uuid: "XXXXXXXX".into(),
-            timeout: Duration::from_secs(3),
-        }
-    }
-}
-
-impl KMBoxNetConfig {
-    pub fn default_with_uuid(uuid: &str) -> Self {
-        Self::default().set_uuid(uuid.into())
-    }
-
-    pub fn new(ip: &str, port: u16, uuid: &str) -> Self {
-        Self {
-            ip: ip.into(),
-            port,
-            uuid: uuid.into(),
-            timeout: Duration::from_secs(3),
-        }
-    }
-
-    pub fn set_timeout(mut self, timeout: Duration) -> Self {
-        self.timeout = timeout;
-        self
-    }
-
-    pub fn set_uuid(mut self, uuid: String) -> Self {
-        self.uuid = uuid;
-        self
-    }
-}
-
 impl KMBoxNetMonitor {
     pub fn new(socket_addr: SocketAddr) -> Self {
         let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP)).unwrap();
@@ -123,6 +108,21 @@ impl KMBoxNetMonitor {
         }
     }
 
+    /// Create a monitor listening on `local_addr` with the socket options applied
+    pub fn with_options(
+        local_addr: SocketAddr,
+        options: &KMBoxNetSocketOptions,
+    ) -> Result<Self, KMBoxNetConnectionError> {
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))
+            .map_err(KMBoxNetConnectionError)?;
+        options.apply(&socket).map_err(KMBoxNetConnectionError)?;
+        Ok(Self {
+            socket,
+            socket_addr: local_addr,
+            monitor: MaybeUninit::new(MonitorData::default()),
+        })
+    }
+
     /// binds a socket to localhost so the kmbox can connect to that socket and send the data
     pub fn bind(&mut self) -> Result<(), KMBoxNetConnectionError> {
         debug!("Bind local Monitor for KMBoxNet at {:?}", self.socket_addr);
@@ -154,12 +154,22 @@ impl KMBoxNetMonitor {
 
 impl KMBoxNet {
     pub fn new(config: KMBoxNetConfig) -> Result<Self, KMBoxNetConnectionError> {
-        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP)).unwrap();
-        let socket_addr = SocketAddr::new(config.ip.parse().unwrap(), config.port);
+        let (socket_addr, mac) = config.resolve()?;
+        let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))
+            .map_err(KMBoxNetConnectionError)?;
+        config
+            .socket
+            .apply(&socket)
+            .map_err(KMBoxNetConnectionError)?;
+        if let Some(local_addr) = config.local_addr {
+            socket
+                .bind(&local_addr.into())
+                .map_err(KMBoxNetConnectionError)?;
+        }
         let rand = rand::random::<u32>();
         let tx = ClientTx {
             head: structs::CmdHead {
-                mac: to_hex(&config.uuid, 4),
+                mac,
                 rand,
                 indexpts: 0,
                 cmd: CMD::CONNECT.into(),
@@ -167,8 +177,12 @@ impl KMBoxNet {
             data: structs::CmdData { u8buff: [0; 1024] },
         };
         debug!("Connecting to KMBox Net\n{:#?}", tx.head);
-        socket.set_read_timeout(Some(config.timeout)).unwrap();
-        socket.set_write_timeout(Some(config.timeout)).unwrap();
+        socket
+            .set_read_timeout(Some(config.timeout))
+            .map_err(KMBoxNetConnectionError)?;
+        socket
+            .set_write_timeout(Some(config.timeout))
+            .map_err(KMBoxNetConnectionError)?;
         socket
             .send_to(
                 unsafe {
@@ -200,14 +214,126 @@ impl KMBoxNet {
         }
         info!("KMBox Net connected");
 
+        let key = config.encrypted.then(|| crypto::key_from_mac(mac));
         Ok(KMBoxNet {
             socket,
             socket_addr,
+            mac,
+            config,
+            mouse: SoftMouse::default(),
+            keyboard: SoftKeyboard::default(),
+            mask: 0,
+            stats: KMBoxNetStats::default(),
+            capture: None,
+            key,
+            debug_port: None,
+            monitor_port: None,
+            next_addr: None,
+            rebooted: false,
+            rx_length: 0,
             tx: MaybeUninit::new(tx),
             rx: MaybeUninit::new(rx),
         })
     }
 
+    /// The mac parsed from the uuid the kmbox was connected with
+    pub fn mac(&self) -> u32 {
+        self.mac
+    }
+
+    /// The address of the kmbox
+    pub fn socket_addr(&self) -> SocketAddr {
+        self.socket_addr
+    }
+
+    /// Snapshot of the round trip times and results of the commands sent since connecting
+    /// or the last [`reset_stats`](Self::reset_stats)
+    pub fn stats(&self) -> KMBoxNetStats {
+        let mut stats = self.stats.clone();
+        stats.elapsed = stats.since.elapsed();
+        stats
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.stats = KMBoxNetStats::default();
+    }
+
+    /// Write every packet sent to and received from the kmbox to a pcap-ng file
+    pub fn start_capture(&mut self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        let file = std::io::BufWriter::new(std::fs::File::create(path)?);
+        self.start_capture_to(file)
+    }
+
+    /// Like [`start_capture`](Self::start_capture) but to any writer, a running capture is
+    /// flushed and replaced
+    pub fn start_capture_to(&mut self, writer: impl Write + Send + 'static) -> std::io::Result<()> {
+        self.stop_capture()?;
+        let unspecified = SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, 0);
+        let local = self
+            .socket
+            .local_addr()
+            .ok()
+            .and_then(|addr| addr.as_socket_ipv4())
+            .unwrap_or(unspecified);
+        let remote = match self.socket_addr {
+            SocketAddr::V4(addr) => addr,
+            SocketAddr::V6(_) => unspecified,
+        };
+        let writer: Box<dyn Write + Send> = Box::new(writer);
+        self.capture = Some(CaptureWriter::new(writer, local, remote)?);
+        debug!("Capturing packets of {}", self.socket_addr);
+        Ok(())
+    }
+
+    /// Flush and close the capture
+    pub fn stop_capture(&mut self) -> std::io::Result<()> {
+        match self.capture.take() {
+            Some(mut capture) => capture.flush(),
+            None => Ok(()),
+        }
+    }
+
+    /// Resend the sent packets of a capture with their original spacing divided by `speed`.
+    /// The header gets the mac and packet index of this connection, reboot and set config
+    /// are skipped so the connection survives. Returns the number of packets sent.
+    ///
+    /// The replayed input is not tracked, only what was pressed through this connection is
+    /// released on drop.
+    pub fn replay_capture(
+        &mut self,
+        packets: impl IntoIterator<Item = CapturedPacket>,
+        speed: f64,
+    ) -> Result<usize, KMBoxNetSendError> {
+        let speed = if speed > 0.0 { speed } else { 1.0 };
+        let begin = Instant::now();
+        let mut first = None;
+        let mut sent = 0;
+        for packet in packets {
+            if packet.direction != CaptureDirection::Sent {
+                continue;
+            }
+            let (Some(cmd), Some(rand)) = (packet.cmd(), packet.rand()) else {
+                warn!("Skipping short packet {packet}");
+                continue;
+            };
+            if matches!(cmd, CMD::REBOOT | CMD::SETCONFIG | CMD::Unknown(_)) {
+                warn!("Skipping {packet}");
+                continue;
+            }
+            let offset = packet
+                .time
+                .duration_since(*first.get_or_insert(packet.time))
+                .unwrap_or_default()
+                .div_f64(speed);
+            if let Some(wait) = offset.checked_sub(begin.elapsed()) {
+                std::thread::sleep(wait);
+            }
+            self.send_raw(cmd, rand, packet.payload())?;
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
     /// Set the timeout for the socket
     pub fn set_timeout(&mut self, timeout: std::time::Duration) -> Result<(), std::io::Error> {
         self.socket.set_read_timeout(Some(timeout))?;
@@ -216,50 +342,88 @@ impl KMBoxNet {
         Ok(())
     }
 
+    /// The keys that are currently held down
+    pub fn pressed_keys(&self) -> Vec<KeyboardKey> {
+        let modifiers = (MODIFIER_FIRST..=MODIFIER_LAST)
+            .filter(|code| self.keyboard.ctrl & (1 << (code - MODIFIER_FIRST)) != 0);
+        let keys = self
+            .keyboard
+            .button
+            .iter()
+            .copied()
+            .filter(|code| *code != 0);
+        modifiers
+            .chain(keys)
+            .filter_map(|code| KeyboardKey::try_from(code).ok())
+            .collect()
+    }
+
+    /// The mouse buttons that are currently held down
+    pub fn pressed_buttons(&self) -> Vec<MouseButton> {
+        MouseButton::all()
+            .filter(|button| self.mouse.button & button.bit() != 0)
+            .collect()
+    }
+
     /// Send a keyboard keydown event
     pub fn keyboard_keydown(&mut self, key: KeyboardKey) -> Result<(), KMBoxNetSendError> {
-        let tx = unsafe { self.tx.assume_init_mut() };
-        let key = key as u8;
-        for i in 0..10 {
-            unsafe {
-                if tx.data.cmd_keyboard.button[i] == 0 as char {
-                    tx.data.cmd_keyboard.button[i] = key as char;
+        let keyboard = &mut self.keyboard;
+        match key.as_kmbox_net_u8() {
+            code @ MODIFIER_FIRST..=MODIFIER_LAST => keyboard.ctrl |= 1 << (code - MODIFIER_FIRST),
+            code => {
+                if !keyboard.button.contains(&code) {
+                    match keyboard.button.iter_mut().find(|button| **button == 0) {
+                        Some(button) => *button = code,
+                        None => warn!("Keyboard rollover, {:?} is not pressed", key),
+                    }
                 }
             }
         }
-        debug!("Keyboard key set tx\n{:?}", unsafe { tx.data.cmd_keyboard });
+        debug!("Keyboard key set\n{:?}", self.keyboard);
         self.send(CMD::KEYBOARD_ALL)?;
         Ok(())
     }
 
     /// keybord keyup
     pub fn keyboard_keyup(&mut self, key: KeyboardKey) -> Result<(), KMBoxNetSendError> {
-        let tx = unsafe { self.tx.assume_init_mut() };
-        let key = key as u8;
-        for i in 0..10 {
-            unsafe {
-                if tx.data.cmd_keyboard.button[i] == key as char {
-                    tx.data.cmd_keyboard.button[i] = 0 as char;
-                }
+        let keyboard = &mut self.keyboard;
+        match key.as_kmbox_net_u8() {
+            code @ MODIFIER_FIRST..=MODIFIER_LAST => {
+                keyboard.ctrl &= !(1 << (code - MODIFIER_FIRST))
             }
+            code => keyboard
+                .button
+                .iter_mut()
+                .filter(|button| **button == code)
+                .for_each(|button| *button = 0),
         }
-        debug!("Keyboard key set tx\n{:?}", unsafe { tx.data.cmd_keyboard });
+        debug!("Keyboard key set\n{:?}", self.keyboard);
         self.send(CMD::KEYBOARD_ALL)?;
         Ok(())
     }
 
+    /// Set or clear the bit of a button, the other held buttons are kept
+    fn mouse_button(
+        &mut self,
+        button: MouseButton,
+        state: ButtonState,
+        cmd: CMD,
+    ) -> Result<(), KMBoxNetSendError> {
+        match state {
+            ButtonState::Pressed => self.mouse.button |= button.bit(),
+            ButtonState::Released => self.mouse.button &= !button.bit(),
+        }
+        debug!("Mouse {:?} button set\n{:?}", button, self.mouse);
+        self.send(cmd)?;
+        Ok(())
+    }
+
     /// mouse left click
     pub fn mouse_left_click(
         &mut self,
         state: impl Into<ButtonState>,
     ) -> Result<(), KMBoxNetSendError> {
-        let tx = unsafe { self.tx.assume_init_mut() };
-        tx.data.cmd_mouse.button = Into::into(state.into());
-        debug!("Mouse left button set tx\n{:?}", unsafe {
-            tx.data.cmd_mouse
-        });
-        self.send(CMD::MOUSE_LEFT)?;
-        Ok(())
+        self.mouse_button(MouseButton::Left, state.into(), CMD::MOUSE_LEFT)
     }
 
     /// mouse right click
@@ -267,13 +431,7 @@ impl KMBoxNet {
         &mut self,
         state: impl Into<ButtonState>,
     ) -> Result<(), KMBoxNetSendError> {
-        let tx = unsafe { self.tx.assume_init_mut() };
-        tx.data.cmd_mouse.button = Into::into(state.into());
-        debug!("Mouse right button set tx\n{:?}", unsafe {
-            tx.data.cmd_mouse
-        });
-        self.send(CMD::MOUSE_RIGHT)?;
-        Ok(())
+        self.mouse_button(MouseButton::Right, state.into(), CMD::MOUSE_RIGHT)
     }
 
     /// mouse middle wheel click
@@ -281,40 +439,194 @@ impl KMBoxNet {
         &mut self,
         state: impl Into<ButtonState>,
     ) -> Result<(), KMBoxNetSendError> {
-        let tx = unsafe { self.tx.assume_init_mut() };
-        tx.data.cmd_mouse.button = Into::into(state.into());
-        debug!("Mouse right button set tx\n{:?}", unsafe {
-            tx.data.cmd_mouse
-        });
-        self.send(CMD::MOUSE_MIDDLE)?;
-        Ok(())
+        self.mouse_button(MouseButton::Middle, state.into(), CMD::MOUSE_MIDDLE)
+    }
+
+    /// mouse side button 1 click, sent with a move command since there is no side button
+    /// command and every mouse command carries the whole button mask
+    pub fn mouse_side1_click(
+        &mut self,
+        state: impl Into<ButtonState>,
+    ) -> Result<(), KMBoxNetSendError> {
+        self.mouse_button(MouseButton::Side1, state.into(), CMD::MOUSE_MOVE)
+    }
+
+    /// mouse side button 2 click, see [`mouse_side1_click`](Self::mouse_side1_click)
+    pub fn mouse_side2_click(
+        &mut self,
+        state: impl Into<ButtonState>,
+    ) -> Result<(), KMBoxNetSendError> {
+        self.mouse_button(MouseButton::Side2, state.into(), CMD::MOUSE_MOVE)
     }
 
     /// use the mouse scroll wheel
     pub fn mouse_wheel(&mut self, state: impl Into<MwheelState>) -> Result<(), KMBoxNetSendError> {
-        let tx = unsafe { self.tx.assume_init_mut() };
-        tx.data.cmd_mouse.wheel = Into::into(state.into());
-        debug!("Mouse wheel set tx\n{:?}", unsafe { tx.data.cmd_mouse });
+        self.mouse.wheel = Into::into(state.into());
+        debug!("Mouse wheel set\n{:?}", self.mouse);
         self.send(CMD::MOUSE_WHEEL)?;
         Ok(())
     }
 
     /// Move the mouse to the specified position relative to the current position
-    /// +x is right, +y is down
+    /// +x is right, +y is down.
+    /// Moves over `max_mouse_move` of the config are split into several reports
+    /// that are `move_pacing` apart.
     pub fn mouse_move(&mut self, position: impl Into<[i32; 2]>) -> Result<(), KMBoxNetSendError> {
-        let tx = unsafe { self.tx.assume_init_mut() };
-        let data = position.into();
-        tx.data.cmd_mouse.x = data[0];
-        tx.data.cmd_mouse.y = data[1];
-        info!("Mouse move set tx\n{:?}", unsafe { tx.data.cmd_mouse });
-        self.send(CMD::MOUSE_MOVE)?;
+        let position = position.into();
+        for (i, [x, y]) in split_mouse_move(position, self.config.max_mouse_move).enumerate() {
+            if i > 0 && !self.config.move_pacing.is_zero() {
+                std::thread::sleep(self.config.move_pacing);
+            }
+            self.mouse.x = x;
+            self.mouse.y = y;
+            trace!("Mouse move set\n{:?}", self.mouse);
+            self.send(CMD::MOUSE_MOVE)?;
+        }
         Ok(())
     }
 
-    /// Reboot the KMBoxNet
-    pub fn reboot(&mut self) -> Result<(), KMBoxNetSendError> {
+    /// Release every held key and mouse button
+    pub fn release_all(&mut self) -> Result<(), KMBoxNetSendError> {
+        let mut result = Ok(());
+        if self.keyboard.ctrl != 0 || self.keyboard.button.iter().any(|b| *b != 0) {
+            debug!("Release all keys {:?}", self.pressed_keys());
+            self.keyboard = SoftKeyboard::default();
+            result = self.send(CMD::KEYBOARD_ALL);
+        }
+        if self.mouse.button != 0 {
+            debug!("Release all buttons {:?}", self.pressed_buttons());
+            // every mouse command carries the whole button mask, an empty move clears it
+            self.mouse.button = 0;
+            let released = self.send(CMD::MOUSE_MOVE);
+            if result.is_ok() {
+                result = released;
+            }
+        }
+        result
+    }
+
+    /// Reboot the KMBoxNet without waiting for an answer, the kmbox may never send one.
+    /// [`KMBoxNetReboot::wait`] connects again once it is back, to the address of
+    /// [`set_config`](Self::set_config) if one was set.
+    pub fn reboot(mut self) -> Result<KMBoxNetReboot, KMBoxNetSendError> {
         debug!("Rebooting KMBoxNet");
-        self.send(CMD::REBOOT)?;
+        self.prepare(CMD::REBOOT, rand::random::<u32>());
+        self.transmit(CMD::REBOOT, false)?;
+        // the kmbox comes back with everything released and unmasked
+        self.rebooted = true;
+        let mut config = self.config.clone();
+        if let Some(addr) = self.next_addr {
+            config.ip = addr.ip().to_string();
+            config.port = addr.port();
+        }
+        Ok(KMBoxNetReboot::new(
+            config,
+            self.mask,
+            self.monitor_port,
+            self.debug_port,
+        ))
+    }
+
+    /// Change the ip and port of the KMBoxNet, it has to be rebooted to use them
+    pub fn set_config(&mut self, ip: Ipv4Addr, port: u16) -> Result<(), KMBoxNetSendError> {
+        debug!("Set KMBoxNet address to {ip}:{port}");
+        let tx = unsafe { self.tx.assume_init_mut() };
+        // the port is sent big endian in the first two data bytes
+        unsafe { tx.data.u8buff[..2].copy_from_slice(&port.to_be_bytes()) };
+        // the ip is sent in network byte order in place of the random value
+        self.send_with_rand(CMD::SETCONFIG, u32::from_ne_bytes(ip.octets()))?;
+        self.next_addr = Some(SocketAddrV4::new(ip, port));
+        Ok(())
+    }
+
+    /// Stop forwarding the masked inputs of the physical mouse, the mask replaces the previous one
+    pub fn mask_mouse(&mut self, mask: KMBoxNetMask) -> Result<(), KMBoxNetSendError> {
+        self.mask = (self.mask & !0xff) | u32::from(mask.bits());
+        debug!("Mask mouse {:?}", mask);
+        self.send_with_rand(CMD::MASK_MOUSE, self.mask)
+    }
+
+    /// Stop forwarding a key of the physical keyboard, `None` forwards every key again
+    pub fn mask_keyboard(&mut self, key: Option<KeyboardKey>) -> Result<(), KMBoxNetSendError> {
+        let code = key.map(|key| key.hid_usage()).unwrap_or(0);
+        self.mask = (self.mask & 0xff) | (u32::from(code) << 8);
+        debug!("Mask keyboard {:?}", key);
+        self.send_with_rand(CMD::MASK_MOUSE, self.mask)
+    }
+
+    /// The masked inputs of the physical mouse
+    pub fn mouse_mask(&self) -> KMBoxNetMask {
+        KMBoxNetMask::from_bits(self.mask as u8)
+    }
+
+    /// Forward every input of the physical mouse and keyboard again
+    pub fn unmask_all(&mut self) -> Result<(), KMBoxNetSendError> {
+        self.mask = 0;
+        debug!("Unmask all");
+        self.send_with_rand(CMD::UNMASK_ALL, 0)
+    }
+
+    /// Round trip time of a connect request, the connection is kept as is
+    pub fn ping(&mut self) -> Result<Duration, KMBoxNetSendError> {
+        let begin = std::time::Instant::now();
+        self.send(CMD::CONNECT)?;
+        Ok(begin.elapsed())
+    }
+
+    /// Connect again and collect the uuid the kmbox answers with together with the
+    /// addresses this connection uses
+    pub fn device_info(&mut self) -> Result<DeviceInfo, KMBoxNetSendError> {
+        let begin = Instant::now();
+        // an empty payload, only the head of the answer is used
+        self.send_raw(CMD::CONNECT, rand::random::<u32>(), &[])?;
+        let latency = begin.elapsed();
+        let rx = unsafe { self.rx.assume_init_ref() };
+        Ok(DeviceInfo {
+            uuid: format!("{:08X}", rx.head.mac),
+            mac: rx.head.mac,
+            addr: self.socket_addr,
+            local_addr: self.socket.local_addr().ok().and_then(|a| a.as_socket()),
+            monitor_addr: self.config.monitor_addr(self.socket_addr)?,
+            debug_addr: self.config.debug_addr(self.socket_addr)?,
+            encrypted: self.key.is_some(),
+            latency,
+        })
+    }
+
+    /// Tell the KMBoxNet to send the input of the attached devices to a [`KMBoxNetMonitor`]
+    /// on the monitor address of the config, the connection can still be used
+    pub fn monitor(&mut self) -> Result<KMBoxNetMonitor, KMBoxNetSendError> {
+        debug!("Monitor KMBoxNet");
+        let monitor_addr = self.config.monitor_addr(self.socket_addr)?;
+        self.send_with_rand(
+            CMD::MONITOR,
+            monitor_addr.port() as u32 | (0xaa55_u32 << 16_u32),
+        )?;
+        self.monitor_port = Some(monitor_addr.port());
+        KMBoxNetMonitor::with_options(monitor_addr, &self.config.socket)
+            .map_err(|e| KMBoxNetSendError(e.0))
+    }
+
+    /// Enable the firmware debug output and receive it on the debug address of the config.
+    /// The output is disabled again on drop.
+    pub fn debug_log(&mut self) -> Result<KMBoxNetDebugLog, KMBoxNetSendError> {
+        let debug_addr = self.config.debug_addr(self.socket_addr)?;
+        // bound first so no line of the firmware is lost
+        let log = KMBoxNetDebugLog::bind(debug_addr, &self.config.socket)
+            .map_err(|e| KMBoxNetSendError(e.0))?;
+        let port = log.local_addr().port();
+        debug!("Enable KMBoxNet debug log on port {port}");
+        self.send_with_rand(CMD::DEBUG, u32::from(port) | (1 << 16))?;
+        self.debug_port = Some(port);
+        Ok(log)
+    }
+
+    /// Stop the firmware debug output
+    pub fn disable_debug_log(&mut self) -> Result<(), KMBoxNetSendError> {
+        let port = self.debug_port.unwrap_or_default();
+        debug!("Disable KMBoxNet debug log");
+        self.send_with_rand(CMD::DEBUG, u32::from(port))?;
+        self.debug_port = None;
         Ok(())
     }
 
@@ -322,31 +634,7 @@ impl KMBoxNet {
     /// This will return a new KMBoxNet instance that can be used to monitor the KMBoxNet
     /// This is useful for getting the current state of the KMBoxNet attached devices
     pub fn into_monitor(mut self) -> Result<KMBoxNetMonitor, KMBoxNetSendError> {
-        debug!("Monitor KMBoxNet");
-        let tx = unsafe { self.tx.assume_init_mut() };
-        tx.head.indexpts += 1;
-        tx.head.cmd = CMD::MONITOR.into();
-        tx.head.rand = self.socket_addr.port() as u32 + 1_u32 | 0xaa55_u32 << 16_u32;
-        self.socket
-            .send_to(
-                unsafe {
-                    std::slice::from_raw_parts(
-                        tx as *const structs::ClientTx as *const u8,
-                        std::mem::size_of::<structs::ClientTx>(),
-                    )
-                },
-                &self.socket_addr.into(),
-            )
-            .map_err(KMBoxNetSendError)?;
-        self.socket
-            .recv_from(unsafe {
-                std::slice::from_raw_parts_mut(
-                    self.rx.as_mut_ptr() as *mut _,
-                    std::mem::size_of::<structs::ClientTx>(),
-                )
-            })
-            .map_err(KMBoxNetSendError)?;
-        Ok(KMBoxNetMonitor::new(self.socket_addr))
+        self.monitor()
     }
 
     /// # Safety
@@ -354,11 +642,39 @@ impl KMBoxNet {
     /// The caller must ensure that the pointer is valid.
     /// The Pointer is valid when the struct is constructed the tx is init
     fn send(&mut self, cmd: CMD) -> Result<(), KMBoxNetSendError> {
+        self.send_with_rand(cmd, rand::random::<u32>())
+    }
+
+    /// Send a command with a fixed value in the `rand` field of the header,
+    /// some commands carry their argument there
+    fn send_with_rand(&mut self, cmd: CMD, rand: u32) -> Result<(), KMBoxNetSendError> {
+        self.prepare(cmd, rand);
+        self.exchange(cmd)
+    }
+
+    /// Fill tx with the command and the tracked mouse or keyboard state
+    fn prepare(&mut self, cmd: CMD, rand: u32) {
         let tx = unsafe { self.tx.assume_init_mut() };
         tx.head.indexpts += 1;
         tx.head.cmd = cmd.into();
-        tx.head.rand = rand::random::<u32>();
-        debug!("Send command tx.head\n{:?}", tx.head);
+        tx.head.rand = rand;
+        match cmd {
+            CMD::KEYBOARD_ALL => tx.data.cmd_keyboard = self.keyboard,
+            CMD::MOUSE_MOVE
+            | CMD::MOUSE_LEFT
+            | CMD::MOUSE_MIDDLE
+            | CMD::MOUSE_RIGHT
+            | CMD::MOUSE_WHEEL
+            | CMD::MOUSE_AUTOMOVE => {
+                tx.data.cmd_mouse = self.mouse;
+                // movement and wheel are relative and only sent once, the buttons stay held
+                self.mouse.x = 0;
+                self.mouse.y = 0;
+                self.mouse.wheel = 0;
+            }
+            _ => {}
+        }
+        trace!("Send command tx.head\n{:?}", tx.head);
         unsafe {
             match cmd {
                 CMD::MOUSE_MOVE
@@ -369,52 +685,241 @@ impl KMBoxNet {
                 | CMD::MOUSE_AUTOMOVE => {
                     let CmdData { cmd_mouse } = tx.data;
                     {
-                        debug!("Send Mouse data tx.data\n{:?}", cmd_mouse);
+                        trace!("Send Mouse data tx.data\n{:?}", cmd_mouse);
                     }
                 }
                 CMD::KEYBOARD_ALL => {
                     let CmdData { cmd_keyboard } = tx.data;
                     {
-                        debug!("Send Keyboard data tx.data\n{:?}", cmd_keyboard);
+                        trace!("Send Keyboard data tx.data\n{:?}", cmd_keyboard);
                     }
                 }
                 CMD::CONNECT => {} // no logging needed
                 CMD::REBOOT => {}  // no logging needed
                 CMD::BAZER_MOVE => unimplemented!("bazer move not implemented"),
-                CMD::MONITOR => unimplemented!("monitor not implemented"),
-                CMD::DEBUG => unimplemented!("debug not implemented"),
-                CMD::MASK_MOUSE => unimplemented!("mask mouse not implemented"),
-                CMD::UNMASK_ALL => unimplemented!("unmask all not implemented"),
-                CMD::SETCONFIG => unimplemented!("setconfig not implemented"),
+                CMD::MONITOR => {}
+                CMD::DEBUG => {}
+                CMD::MASK_MOUSE | CMD::UNMASK_ALL => {}
+                CMD::SETCONFIG => {}
                 CMD::SHOWPIC => unimplemented!("showpic not implemented"),
+                CMD::Unknown(_) => {}
             }
         }
-        self.socket
-            .send_to(
-                unsafe {
-                    std::slice::from_raw_parts(
-                        tx as *const structs::ClientTx as *const u8,
-                        std::mem::size_of::<structs::ClientTx>(),
-                    )
-                },
-                &self.socket_addr.into(),
+    }
+
+    /// Send a command with the payload as is, the tracked mouse and keyboard state is not used
+    fn send_raw(&mut self, cmd: CMD, rand: u32, payload: &[u8]) -> Result<(), KMBoxNetSendError> {
+        let tx = unsafe { self.tx.assume_init_mut() };
+        tx.head.indexpts += 1;
+        tx.head.cmd = cmd.into();
+        tx.head.rand = rand;
+        let buffer = unsafe { &mut tx.data.u8buff };
+        let length = payload.len().min(buffer.len());
+        buffer[..length].copy_from_slice(&payload[..length]);
+        buffer[length..].fill(0);
+        self.exchange(cmd)
+    }
+
+    /// Send the prepared tx and wait for the answer
+    fn exchange(&mut self, cmd: CMD) -> Result<(), KMBoxNetSendError> {
+        self.transmit(cmd, true)
+    }
+
+    /// Send the prepared tx, without `reply` it counts as acknowledged once it is sent
+    fn transmit(&mut self, cmd: CMD, reply: bool) -> Result<(), KMBoxNetSendError> {
+        let tx = unsafe { self.tx.assume_init_ref() };
+        let head = tx.head;
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::trace_span!("kmbox_net_send", %cmd, indexpts = head.indexpts).entered();
+        let mut plain = unsafe {
+            std::slice::from_raw_parts(
+                tx as *const structs::ClientTx as *const u8,
+                std::mem::size_of::<structs::ClientTx>(),
             )
-            .map_err(KMBoxNetSendError)?;
-        self.socket
-            .recv_from(unsafe {
+        };
+        let mut encrypted = None;
+        let key = self.key.filter(|_| crypto::is_encrypted(cmd));
+        if let Some(key) = key {
+            plain = &plain[..crypto::ENCRYPTED_PACKET_SIZE];
+            let mut block = [0u8; crypto::ENCRYPTED_PACKET_SIZE];
+            block.copy_from_slice(plain);
+            crypto::encrypt_packet(&mut block, &key);
+            encrypted = Some(block);
+        }
+        // the capture holds the plain text so it can be decoded
+        capture_packet(&mut self.capture, CaptureDirection::Sent, plain);
+        let packet = encrypted.as_ref().map_or(plain, |block| &block[..]);
+        let begin = Instant::now();
+        let mut stale = 0;
+        let result = self
+            .socket
+            .send_to(packet, &self.socket_addr.into())
+            .and_then(|sent| match reply {
+                true => self.recv_reply(head, key, begin, &mut stale),
+                false => Ok(sent),
+            });
+        let rtt = begin.elapsed();
+        let outcome = match &result {
+            Ok(_) => SendOutcome::Ok,
+            // only late answers to earlier commands arrived
+            Err(_) if stale > 0 => SendOutcome::Mismatch,
+            Err(e)
+                if matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut
+                ) =>
+            {
+                SendOutcome::Timeout
+            }
+            Err(_) => SendOutcome::Error,
+        };
+        self.stats.record(cmd, outcome, rtt);
+        log_send(cmd, head.indexpts, outcome, rtt);
+        result.map(|_| ()).map_err(KMBoxNetSendError)
+    }
+
+    /// Receive the answer to the request with `head`. A late answer to an earlier command
+    /// that timed out is dropped and the next one is read, until the answer arrives or the
+    /// read timeout counted from `begin` runs out. `stale` counts the dropped answers.
+    /// The answer to a request sent with `key` may be plain or encrypted.
+    fn recv_reply(
+        &mut self,
+        head: structs::CmdHead,
+        key: Option<[u32; 4]>,
+        begin: Instant,
+        stale: &mut u32,
+    ) -> std::io::Result<usize> {
+        let timeout = self.socket.read_timeout()?;
+        let result = loop {
+            let length = match self.socket.recv_from(unsafe {
                 std::slice::from_raw_parts_mut(
                     self.rx.as_mut_ptr() as *mut _,
                     std::mem::size_of::<structs::ClientTx>(),
                 )
-            })
-            .map_err(KMBoxNetSendError)?;
-        let rx = unsafe { self.rx.assume_init() };
-        info!(
-            "Command send successful rx res\n{:?}\n{:?}",
-            rx.head,
-            unsafe { rx.data.cmd_mouse }
-        );
-        Ok(())
+            }) {
+                Ok((length, _)) => length,
+                Err(e) => break Err(e),
+            };
+            self.rx_length = length;
+            if let Some(key) = key.filter(|_| length >= crypto::ENCRYPTED_PACKET_SIZE) {
+                self.decrypt_reply(head, &key);
+            }
+            let received =
+                unsafe { std::slice::from_raw_parts(self.rx.as_ptr() as *const u8, length) };
+            capture_packet(&mut self.capture, CaptureDirection::Received, received);
+            let rx = unsafe { self.rx.assume_init_ref() };
+            if rx.head.cmd == head.cmd && rx.head.indexpts == head.indexpts {
+                break Ok(length);
+            }
+            warn!(
+                "Dropping a late response, sent {} #{} got {} #{}",
+                CMD::from_code(head.cmd),
+                head.indexpts,
+                CMD::from_code(rx.head.cmd),
+                rx.head.indexpts
+            );
+            *stale += 1;
+            // the rest of the timeout is left for the answer
+            if let Some(timeout) = timeout {
+                match timeout.checked_sub(begin.elapsed()) {
+                    Some(left) if !left.is_zero() => self.socket.set_read_timeout(Some(left))?,
+                    _ => {
+                        break Err(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            "no matching response within the timeout",
+                        ))
+                    }
+                }
+            }
+        };
+        if *stale > 0 {
+            self.socket.set_read_timeout(timeout)?;
+        }
+        result
+    }
+
+    /// Decrypt the start of the received packet in place if it is not the plain answer to
+    /// `head` but decrypts to it
+    fn decrypt_reply(&mut self, head: structs::CmdHead, key: &[u32; 4]) {
+        let received = unsafe {
+            std::slice::from_raw_parts_mut(
+                self.rx.as_mut_ptr() as *mut u8,
+                crypto::ENCRYPTED_PACKET_SIZE,
+            )
+        };
+        let answers = |packet: &[u8]| {
+            let word = |i: usize| u32::from_le_bytes(packet[i..i + 4].try_into().unwrap());
+            word(8) == head.indexpts && word(12) == head.cmd
+        };
+        if answers(received) {
+            return;
+        }
+        let mut block = [0u8; crypto::ENCRYPTED_PACKET_SIZE];
+        block.copy_from_slice(received);
+        crypto::decrypt_packet(&mut block, key);
+        if answers(&block) {
+            received.copy_from_slice(&block);
+        }
+    }
+}
+
+/// A capture that fails to write is stopped, the connection keeps working
+fn capture_packet(
+    capture: &mut Option<CaptureWriter<Box<dyn Write + Send>>>,
+    direction: CaptureDirection,
+    packet: &[u8],
+) {
+    let Some(writer) = capture else {
+        return;
+    };
+    if let Err(e) = writer.write_packet(direction, SystemTime::now(), packet) {
+        error!("Failed to write capture, capturing stopped: {e}");
+        *capture = None;
+    }
+}
+
+/// One event per sent command, acknowledged commands are only traced
+fn log_send(cmd: CMD, indexpts: u32, outcome: SendOutcome, rtt: Duration) {
+    #[cfg(feature = "tracing")]
+    {
+        let rtt_us = rtt.as_micros() as u64;
+        let result = outcome.as_str();
+        match outcome {
+            SendOutcome::Ok => tracing::trace!(%cmd, indexpts, rtt_us, result, "command sent"),
+            _ => tracing::debug!(%cmd, indexpts, rtt_us, result, "command failed"),
+        }
+    }
+    #[cfg(not(feature = "tracing"))]
+    match outcome {
+        SendOutcome::Ok => trace!("{cmd} #{indexpts} sent in {rtt:?}"),
+        _ => debug!("{cmd} #{indexpts} {} after {rtt:?}", outcome.as_str()),
+    }
+}
+
+impl Drop for KMBoxNet {
+    /// Never leave a key or button stuck or masked on the target PC
+    fn drop(&mut self) {
+        // a rebooting kmbox does not answer and comes back with nothing held or masked
+        if !self.rebooted {
+            if let Err(e) = self.release_all() {
+                error!("Failed to release held input on drop: {e}");
+            }
+            // a mask outliving the program would lock the physical mouse
+            if self.mask != 0 {
+                if let Err(e) = self.unmask_all() {
+                    error!("Failed to unmask input on drop: {e}");
+                }
+            }
+            if self.debug_port.is_some() {
+                if let Err(e) = self.disable_debug_log() {
+                    error!("Failed to disable the debug log on drop: {e}");
+                }
+            }
+        }
+        if let Err(e) = self.stop_capture() {
+            error!("Failed to flush capture on drop: {e}");
+        }
     }
 }
 
@@ -456,16 +961,16 @@ impl InputMiddlewareDeviceAction for KMBoxNet {
 
     fn mouse_side1_click(
         &mut self,
-        _: ButtonState,
+        state: ButtonState,
     ) -> Result<(), crate::errors::InputMiddlewareSendError> {
-        unimplemented!("not possible on kmbox net")
+        self.mouse_side1_click(state).map_err(|e| e.into())
     }
 
     fn mouse_side2_click(
         &mut self,
-        _: ButtonState,
+        state: ButtonState,
     ) -> Result<(), crate::errors::InputMiddlewareSendError> {
-        unimplemented!("not possible on kmbox net")
+        self.mouse_side2_click(state).map_err(|e| e.into())
     }
 
     fn mouse_wheel_click(
@@ -485,4 +990,16 @@ impl InputMiddlewareDeviceAction for KMBoxNet {
     fn mouse_move(&mut self, pos: [i32; 2]) -> Result<(), crate::errors::InputMiddlewareSendError> {
         self.mouse_move(pos).map_err(|e| e.into())
     }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        DeviceCapabilities {
+            max_mouse_move: self.config.max_mouse_move,
+            side_buttons: true,
+            ..Default::default()
+        }
+    }
+
+    fn release_all(&mut self) -> Result<(), crate::errors::InputMiddlewareSendError> {
+        self.release_all().map_err(|e| e.into())
+    }
 }
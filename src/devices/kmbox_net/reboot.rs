@@ -0,0 +1,158 @@
+//! Connecting to a kmbox again after [`KMBoxNet::reboot`](super::KMBoxNet::reboot)
+
+use std::{
+    io::ErrorKind,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::logging::{debug, info};
+
+use super::{
+    cmd::CMD,
+    errors::{KMBoxNetConnectionError, KMBoxNetSendError},
+    KMBoxNet, KMBoxNetConfig,
+};
+
+/// the kmbox can still answer right after the reboot command, nothing is tried before this
+const DOWNTIME: Duration = Duration::from_millis(500);
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A kmbox that was told to reboot. [`wait`](Self::wait) connects again once it answers
+/// connect requests and reapplies the mask, monitor and debug log of the old connection.
+#[derive(Debug)]
+#[must_use = "the kmbox is only connected again by `wait`"]
+pub struct KMBoxNetReboot {
+    config: KMBoxNetConfig,
+    since: Instant,
+    timeout: Duration,
+    poll_interval: Duration,
+    restore: bool,
+    mask: u32,
+    monitor_port: Option<u16>,
+    debug_port: Option<u16>,
+}
+
+impl KMBoxNetReboot {
+    pub(crate) fn new(
+        config: KMBoxNetConfig,
+        mask: u32,
+        monitor_port: Option<u16>,
+        debug_port: Option<u16>,
+    ) -> Self {
+        Self {
+            timeout: config.reboot_timeout,
+            config,
+            since: Instant::now(),
+            poll_interval: POLL_INTERVAL,
+            restore: true,
+            mask,
+            monitor_port,
+            debug_port,
+        }
+    }
+
+    /// How long [`wait`](Self::wait) tries, counted from the reboot, default is the
+    /// `reboot_timeout` of the config
+    pub fn set_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Pause between two connect attempts, also the time an attempt waits for the answer
+    pub fn set_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval.max(Duration::from_millis(1));
+        self
+    }
+
+    /// Reapply the mask, monitor and debug log of the old connection, on by default.
+    /// The [`KMBoxNetMonitor`](super::KMBoxNetMonitor) and
+    /// [`KMBoxNetDebugLog`](super::KMBoxNetDebugLog) of the old connection keep receiving.
+    pub fn set_restore(mut self, restore: bool) -> Self {
+        self.restore = restore;
+        self
+    }
+
+    /// The config used to connect again
+    pub fn config(&self) -> &KMBoxNetConfig {
+        &self.config
+    }
+
+    /// Time since the reboot command was sent
+    pub fn elapsed(&self) -> Duration {
+        self.since.elapsed()
+    }
+
+    /// Try to connect once, `None` while the kmbox does not answer
+    pub fn try_connect(&self) -> Result<Option<KMBoxNet>, KMBoxNetConnectionError> {
+        if self.elapsed() < DOWNTIME {
+            return Ok(None);
+        }
+        let attempt = self.config.clone().set_timeout(self.poll_interval);
+        let mut km = match KMBoxNet::new(attempt) {
+            Ok(km) => km,
+            Err(e) if is_down(e.0.kind()) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        km.set_timeout(self.config.timeout)
+            .map_err(KMBoxNetConnectionError)?;
+        km.config = self.config.clone();
+        if self.restore {
+            self.reapply(&mut km)
+                .map_err(|e| KMBoxNetConnectionError(e.0))?;
+        }
+        Ok(Some(km))
+    }
+
+    /// Block until the kmbox answers again, a timeout is returned as [`ErrorKind::TimedOut`]
+    pub fn wait(self) -> Result<KMBoxNet, KMBoxNetConnectionError> {
+        debug!("Waiting for the KMBoxNet to reboot");
+        thread::sleep(DOWNTIME.saturating_sub(self.elapsed()));
+        loop {
+            let attempt = Instant::now();
+            if let Some(km) = self.try_connect()? {
+                info!("KMBoxNet is back after {:?}", self.elapsed());
+                return Ok(km);
+            }
+            if self.elapsed() >= self.timeout {
+                return Err(KMBoxNetConnectionError(std::io::Error::new(
+                    ErrorKind::TimedOut,
+                    format!(
+                        "kmbox did not answer within {:?} of the reboot",
+                        self.timeout
+                    ),
+                )));
+            }
+            thread::sleep(self.poll_interval.saturating_sub(attempt.elapsed()));
+        }
+    }
+
+    fn reapply(&self, km: &mut KMBoxNet) -> Result<(), KMBoxNetSendError> {
+        if self.mask != 0 {
+            km.send_with_rand(CMD::MASK_MOUSE, self.mask)?;
+            km.mask = self.mask;
+        }
+        if let Some(port) = self.monitor_port {
+            km.send_with_rand(CMD::MONITOR, u32::from(port) | (0xaa55 << 16))?;
+            km.monitor_port = Some(port);
+        }
+        if let Some(port) = self.debug_port {
+            km.send_with_rand(CMD::DEBUG, u32::from(port) | (1 << 16))?;
+            km.debug_port = Some(port);
+        }
+        Ok(())
+    }
+}
+
+/// Errors of a kmbox that is still booting, anything else will not go away by waiting
+fn is_down(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::WouldBlock
+            | ErrorKind::TimedOut
+            | ErrorKind::ConnectionRefused
+            | ErrorKind::ConnectionReset
+            | ErrorKind::HostUnreachable
+            | ErrorKind::NetworkUnreachable
+    )
+}
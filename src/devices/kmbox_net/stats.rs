@@ -0,0 +1,282 @@
+//! Round trip times and results of the commands sent to a [`KMBoxNet`](super::KMBoxNet)
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    time::{Duration, Instant},
+};
+
+use super::cmd::CMD;
+
+/// Upper bounds of the round trip time buckets, a last bucket catches everything slower
+pub const RTT_BUCKETS: [Duration; 12] = [
+    Duration::from_micros(100),
+    Duration::from_micros(250),
+    Duration::from_micros(500),
+    Duration::from_millis(1),
+    Duration::from_micros(2500),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+];
+
+/// How a command sent to the kmbox ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SendOutcome {
+    /// the kmbox acknowledged the command
+    Ok,
+    /// no answer within the read timeout
+    Timeout,
+    /// only late answers to earlier commands arrived within the read timeout
+    Mismatch,
+    /// the socket failed for any other reason
+    Error,
+}
+
+impl SendOutcome {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SendOutcome::Ok => "ok",
+            SendOutcome::Timeout => "timeout",
+            SendOutcome::Mismatch => "mismatch",
+            SendOutcome::Error => "error",
+        }
+    }
+}
+
+/// Histogram of the round trip times over the [`RTT_BUCKETS`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RttHistogram {
+    counts: [u64; RTT_BUCKETS.len() + 1],
+    sum: Duration,
+    min: Option<Duration>,
+    max: Duration,
+}
+
+impl RttHistogram {
+    pub fn record(&mut self, rtt: Duration) {
+        let bucket = RTT_BUCKETS
+            .iter()
+            .position(|bound| rtt <= *bound)
+            .unwrap_or(RTT_BUCKETS.len());
+        self.counts[bucket] += 1;
+        self.sum += rtt;
+        self.min = Some(self.min.map_or(rtt, |min| min.min(rtt)));
+        self.max = self.max.max(rtt);
+    }
+
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    pub fn sum(&self) -> Duration {
+        self.sum
+    }
+
+    pub fn min(&self) -> Option<Duration> {
+        self.min
+    }
+
+    pub fn max(&self) -> Option<Duration> {
+        self.min.map(|_| self.max)
+    }
+
+    pub fn mean(&self) -> Option<Duration> {
+        match self.count() {
+            0 => None,
+            count => Some(self.sum.div_f64(count as f64)),
+        }
+    }
+
+    /// Upper bound of the bucket that holds the quantile `q` (0.0 to 1.0),
+    /// the slowest bucket is reported as the largest rtt seen
+    pub fn quantile(&self, q: f64) -> Option<Duration> {
+        let count = self.count();
+        if count == 0 {
+            return None;
+        }
+        let rank = ((q.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (bucket, n) in self.counts.iter().enumerate() {
+            seen += n;
+            if seen >= rank {
+                let bound = RTT_BUCKETS.get(bucket).copied().unwrap_or(self.max);
+                return Some(bound.min(self.max));
+            }
+        }
+        Some(self.max)
+    }
+
+    /// Upper bound and count of every bucket, the last bound is [`Duration::MAX`]
+    pub fn buckets(&self) -> impl Iterator<Item = (Duration, u64)> + '_ {
+        RTT_BUCKETS
+            .iter()
+            .copied()
+            .chain([Duration::MAX])
+            .zip(self.counts.iter().copied())
+    }
+
+    pub fn merge(&mut self, other: &RttHistogram) {
+        for (count, other) in self.counts.iter_mut().zip(other.counts) {
+            *count += other;
+        }
+        self.sum += other.sum;
+        self.min = match (self.min, other.min) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        self.max = self.max.max(other.max);
+    }
+}
+
+/// Counters of a single command
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandStats {
+    pub sent: u64,
+    pub ok: u64,
+    pub timeouts: u64,
+    pub mismatches: u64,
+    pub errors: u64,
+    /// round trip time of the acknowledged commands
+    pub rtt: RttHistogram,
+}
+
+impl CommandStats {
+    pub fn record(&mut self, outcome: SendOutcome, rtt: Duration) {
+        self.sent += 1;
+        match outcome {
+            SendOutcome::Ok => {
+                self.ok += 1;
+                self.rtt.record(rtt);
+            }
+            SendOutcome::Timeout => self.timeouts += 1,
+            SendOutcome::Mismatch => self.mismatches += 1,
+            SendOutcome::Error => self.errors += 1,
+        }
+    }
+
+    pub fn merge(&mut self, other: &CommandStats) {
+        self.sent += other.sent;
+        self.ok += other.ok;
+        self.timeouts += other.timeouts;
+        self.mismatches += other.mismatches;
+        self.errors += other.errors;
+        self.rtt.merge(&other.rtt);
+    }
+}
+
+/// Snapshot of the statistics of a connection, see [`KMBoxNet::stats`](super::KMBoxNet::stats)
+#[derive(Debug, Clone)]
+pub struct KMBoxNetStats {
+    /// when the connection was made or the stats were reset
+    pub since: Instant,
+    /// time from `since` until the snapshot was taken
+    pub elapsed: Duration,
+    pub commands: BTreeMap<CMD, CommandStats>,
+}
+
+impl Default for KMBoxNetStats {
+    fn default() -> Self {
+        Self {
+            since: Instant::now(),
+            elapsed: Duration::ZERO,
+            commands: BTreeMap::new(),
+        }
+    }
+}
+
+impl KMBoxNetStats {
+    pub(crate) fn record(&mut self, cmd: CMD, outcome: SendOutcome, rtt: Duration) {
+        self.commands.entry(cmd).or_default().record(outcome, rtt);
+        #[cfg(feature = "metrics")]
+        {
+            let name = cmd.to_string();
+            metrics::counter!(
+                "kmbox_net_commands_total",
+                "cmd" => name.clone(),
+                "result" => outcome.as_str()
+            )
+            .increment(1);
+            if outcome == SendOutcome::Ok {
+                metrics::histogram!("kmbox_net_rtt_seconds", "cmd" => name)
+                    .record(rtt.as_secs_f64());
+            }
+        }
+    }
+
+    /// The counters of all commands added up
+    pub fn total(&self) -> CommandStats {
+        let mut total = CommandStats::default();
+        for stats in self.commands.values() {
+            total.merge(stats);
+        }
+        total
+    }
+
+    /// Average packets sent per second since the stats were started
+    pub fn packets_per_sec(&self) -> f64 {
+        match self.elapsed.as_secs_f64() {
+            secs if secs > 0.0 => self.total().sent as f64 / secs,
+            _ => 0.0,
+        }
+    }
+
+    /// Render the counters and histograms in the Prometheus text format
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+        out.push_str("# HELP kmbox_net_commands_total Commands sent to the kmbox by result\n");
+        out.push_str("# TYPE kmbox_net_commands_total counter\n");
+        for (cmd, stats) in &self.commands {
+            for (outcome, count) in [
+                (SendOutcome::Ok, stats.ok),
+                (SendOutcome::Timeout, stats.timeouts),
+                (SendOutcome::Mismatch, stats.mismatches),
+                (SendOutcome::Error, stats.errors),
+            ] {
+                let _ = writeln!(
+                    out,
+                    "kmbox_net_commands_total{{cmd=\"{cmd}\",result=\"{}\"}} {count}",
+                    outcome.as_str()
+                );
+            }
+        }
+        out.push_str("# HELP kmbox_net_rtt_seconds Round trip time of acknowledged commands\n");
+        out.push_str("# TYPE kmbox_net_rtt_seconds histogram\n");
+        for (cmd, stats) in &self.commands {
+            let mut cumulative = 0;
+            for (bound, count) in stats.rtt.buckets() {
+                cumulative += count;
+                let le = match bound {
+                    Duration::MAX => "+Inf".to_string(),
+                    bound => bound.as_secs_f64().to_string(),
+                };
+                let _ = writeln!(
+                    out,
+                    "kmbox_net_rtt_seconds_bucket{{cmd=\"{cmd}\",le=\"{le}\"}} {cumulative}"
+                );
+            }
+            let _ = writeln!(
+                out,
+                "kmbox_net_rtt_seconds_sum{{cmd=\"{cmd}\"}} {}",
+                stats.rtt.sum().as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "kmbox_net_rtt_seconds_count{{cmd=\"{cmd}\"}} {}",
+                stats.rtt.count()
+            );
+        }
+        out.push_str("# HELP kmbox_net_packets_per_second Average packets sent per second\n");
+        out.push_str("# TYPE kmbox_net_packets_per_second gauge\n");
+        let _ = writeln!(
+            out,
+            "kmbox_net_packets_per_second {}",
+            self.packets_per_sec()
+        );
+        out
+    }
+}
@@ -51,17 +51,17 @@ pub struct SoftMouse {
 #[repr(C)]
 #[derive(Debug, Default, Clone, Copy)]
 pub struct SoftKeyboard {
-    pub ctrl: char,
-    pub resvel: char,
-    pub button: [char; 10],
+    pub ctrl: u8, // modifier bits, bit 0 is left ctrl and bit 7 right gui
+    pub resvel: u8,
+    pub button: [u8; 10],
 }
 
 #[repr(C)]
 #[derive(Debug, Default, Clone, Copy)]
 pub struct MonitorKeyboardData {
     pub report_id: u8,
-    pub buttons: u8,
-    pub data: [i16; 10],
+    pub buttons: u8, // modifier bits like SoftKeyboard.ctrl
+    pub data: [u8; 10],
 }
 #[repr(C)]
 #[derive(Debug, Default, Clone, Copy)]
@@ -1,2 +1,3 @@
 #[cfg(feature = "kmbox_net")]
 pub mod kmbox_net;
+pub mod recorder;
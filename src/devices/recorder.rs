@@ -0,0 +1,139 @@
+//! A device that records everything into a [`Macro`] instead of sending it, e.g. for dry runs.
+
+use std::time::Duration;
+
+use crate::{
+    button_state::{ButtonState, MouseButton, MwheelState},
+    errors::InputMiddlewareSendError,
+    input_macro::{Macro, MacroEvent, TimedEvent},
+    keyboardkeys::KeyboardKey,
+    InputMiddlewareDeviceAction,
+};
+
+/// Records the calls with a virtual clock that only moves with [`RecorderDevice::advance`]
+#[derive(Debug, Default)]
+pub struct RecorderDevice {
+    now: Duration,
+    events: Vec<TimedEvent>,
+    held_keys: Vec<KeyboardKey>,
+    held_buttons: Vec<MouseButton>,
+}
+
+impl RecorderDevice {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Move the virtual clock forward
+    pub fn advance(&mut self, duration: Duration) {
+        self.now += duration;
+    }
+
+    /// The virtual time since the start
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    pub fn events(&self) -> &[TimedEvent] {
+        &self.events
+    }
+
+    pub fn into_macro(self) -> Macro {
+        Macro {
+            events: self.events,
+            ..Default::default()
+        }
+    }
+
+    fn push(&mut self, event: MacroEvent) -> Result<(), InputMiddlewareSendError> {
+        self.events.push(TimedEvent {
+            at: self.now,
+            event,
+        });
+        Ok(())
+    }
+
+    fn button(
+        &mut self,
+        button: MouseButton,
+        state: ButtonState,
+    ) -> Result<(), InputMiddlewareSendError> {
+        let pressed = matches!(state, ButtonState::Pressed);
+        self.held_buttons.retain(|b| *b != button);
+        if pressed {
+            self.held_buttons.push(button);
+        }
+        self.push(MacroEvent::Button { button, pressed })
+    }
+
+    fn key(&mut self, key: KeyboardKey, pressed: bool) -> Result<(), InputMiddlewareSendError> {
+        self.held_keys.retain(|k| *k != key);
+        if pressed {
+            self.held_keys.push(key);
+        }
+        self.push(MacroEvent::Key { key, pressed })
+    }
+}
+
+impl InputMiddlewareDeviceAction for RecorderDevice {
+    fn keyboard_keydown(&mut self, key: KeyboardKey) -> Result<(), InputMiddlewareSendError> {
+        self.key(key, true)
+    }
+
+    fn keyboard_keyup(&mut self, key: KeyboardKey) -> Result<(), InputMiddlewareSendError> {
+        self.key(key, false)
+    }
+
+    fn mouse_left_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Left, state)
+    }
+
+    fn mouse_right_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Right, state)
+    }
+
+    fn mouse_middle_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Middle, state)
+    }
+
+    fn mouse_side1_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Side1, state)
+    }
+
+    fn mouse_side2_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Side2, state)
+    }
+
+    fn mouse_wheel_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Middle, state)
+    }
+
+    fn mouse_wheel(&mut self, state: MwheelState) -> Result<(), InputMiddlewareSendError> {
+        self.push(MacroEvent::Wheel {
+            delta: state.into(),
+        })
+    }
+
+    fn mouse_move(&mut self, pos: [i32; 2]) -> Result<(), InputMiddlewareSendError> {
+        self.push(MacroEvent::Move {
+            x: pos[0],
+            y: pos[1],
+        })
+    }
+
+    fn release_all(&mut self) -> Result<(), InputMiddlewareSendError> {
+        for key in std::mem::take(&mut self.held_keys).into_iter().rev() {
+            self.push(MacroEvent::Key {
+                key,
+                pressed: false,
+            })?;
+        }
+        for button in std::mem::take(&mut self.held_buttons) {
+            self.push(MacroEvent::Button {
+                button,
+                pressed: false,
+            })?;
+        }
+        Ok(())
+    }
+}
@@ -1,5 +1,10 @@
+use std::path::PathBuf;
+
 use thiserror::Error;
 
+#[cfg(feature = "kmbox_net")]
+use crate::devices::kmbox_net::errors::InvalidConfig;
+
 #[derive(Error, Debug)]
 #[error(transparent)]
 pub struct InputMiddlewareConnectionError(#[from] pub std::io::Error);
@@ -7,3 +12,95 @@ pub struct InputMiddlewareConnectionError(#[from] pub std::io::Error);
 #[derive(Error, Debug)]
 #[error(transparent)]
 pub struct InputMiddlewareSendError(#[from] pub std::io::Error);
+
+#[derive(Error, Debug)]
+pub enum InputMiddlewareConfigError {
+    #[error("failed to read config file: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "serde")]
+    #[error("failed to parse TOML config: {0}")]
+    Toml(#[from] toml::de::Error),
+    #[cfg(feature = "serde")]
+    #[error("failed to parse JSON config: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("unsupported config file format {0:?} (expected .toml or .json)")]
+    UnsupportedFormat(PathBuf),
+    #[error("invalid value {value:?} for environment variable {name}")]
+    InvalidEnv { name: &'static str, value: String },
+    #[cfg(feature = "kmbox_net")]
+    #[error(transparent)]
+    Invalid(#[from] InvalidConfig),
+    #[error(transparent)]
+    Connection(#[from] InputMiddlewareConnectionError),
+}
+
+#[derive(Error, Debug)]
+pub enum InputMiddlewareTypingError {
+    #[error("characters {chars:?} can not be typed on the {layout} layout")]
+    Unmappable {
+        layout: String,
+        /// the characters with their char index in the text
+        chars: Vec<(usize, char)>,
+    },
+    #[error(transparent)]
+    Send(#[from] InputMiddlewareSendError),
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum InputMiddlewareParseError {
+    #[error("unknown key {0:?}")]
+    UnknownKey(String),
+    #[error("unknown HID usage {0:#04x}")]
+    UnknownKeyCode(u16),
+    #[error("empty key chord")]
+    EmptyChord,
+    #[error("key {0:?} appears more than once in the chord")]
+    DuplicateKey(String),
+}
+
+#[derive(Error, Debug)]
+pub enum InputMiddlewareMacroError {
+    #[error("failed to read or write macro: {0}")]
+    Io(#[from] std::io::Error),
+    #[cfg(feature = "serde")]
+    #[error("failed to parse JSON macro: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("not a macro file")]
+    InvalidMagic,
+    #[error("unsupported macro format version {0}")]
+    UnsupportedVersion(u16),
+    #[error("invalid macro event {index}: {reason}")]
+    InvalidEvent { index: usize, reason: String },
+    #[error(transparent)]
+    Send(#[from] InputMiddlewareSendError),
+}
+
+#[derive(Error, Debug)]
+pub enum InputMiddlewareScriptError {
+    #[error("line {line}: {message}")]
+    Syntax { line: usize, message: String },
+    #[error("line {line}: {message}")]
+    Runtime { line: usize, message: String },
+    #[error("line {line}: {source}")]
+    Send {
+        line: usize,
+        source: InputMiddlewareSendError,
+    },
+    #[error("line {line}: {source}")]
+    Typing {
+        line: usize,
+        source: InputMiddlewareTypingError,
+    },
+}
+
+impl InputMiddlewareScriptError {
+    /// The 1 based line of the script the error is on
+    pub fn line(&self) -> usize {
+        match self {
+            Self::Syntax { line, .. }
+            | Self::Runtime { line, .. }
+            | Self::Send { line, .. }
+            | Self::Typing { line, .. } => *line,
+        }
+    }
+}
@@ -0,0 +1,611 @@
+//! Record input as a macro and play it back on any [`InputMiddlewareDeviceAction`].
+//!
+//! A [`Macro`] is a versioned list of timestamped [`MacroEvent`]s. It is stored as JSON with
+//! the `serde` feature or in a compact binary encoding, see [`Macro::to_bytes`].
+//! [`MacroRecorder`] builds a macro from the reports of a
+//! [`KMBoxNetMonitor`](crate::devices::kmbox_net::KMBoxNetMonitor),
+//! [`MacroPlayer`] plays it back with speed scaling, looping and cancellation.
+
+use std::{
+    fs,
+    io::{Read, Write},
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::logging::debug;
+#[cfg(feature = "kmbox_net")]
+use crate::logging::warn;
+
+#[cfg(feature = "kmbox_net")]
+use crate::devices::kmbox_net::MonitorData;
+use crate::{
+    button_state::{ButtonState, MouseButton, MwheelState},
+    errors::{InputMiddlewareMacroError, InputMiddlewareSendError},
+    keyboardkeys::{ConsumerKey, KeyboardKey},
+    InputMiddlewareDeviceAction,
+};
+
+/// Version written into new macros, older versions are still read
+pub const MACRO_FORMAT_VERSION: u16 = 1;
+
+/// First bytes of the binary encoding
+const MAGIC: &[u8; 4] = b"IMMC";
+
+/// A single input, moves and wheel are relative
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", rename_all = "lowercase"))]
+pub enum MacroEvent {
+    Move {
+        x: i32,
+        y: i32,
+    },
+    Button {
+        button: MouseButton,
+        pressed: bool,
+    },
+    /// positive is up
+    Wheel {
+        delta: i32,
+    },
+    Key {
+        key: KeyboardKey,
+        pressed: bool,
+    },
+}
+
+/// Something to send to a device, the [`MacroEvent`]s and the inputs a macro does not record
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputAction {
+    Event(MacroEvent),
+    Consumer { key: ConsumerKey, pressed: bool },
+    ReleaseAll,
+}
+
+impl InputAction {
+    /// Send it to the device
+    pub fn apply<D: InputMiddlewareDeviceAction + ?Sized>(
+        self,
+        device: &mut D,
+    ) -> Result<(), InputMiddlewareSendError> {
+        match self {
+            InputAction::Event(event) => apply(device, event),
+            InputAction::Consumer { key, pressed } => {
+                let state = match pressed {
+                    true => ButtonState::Pressed,
+                    false => ButtonState::Released,
+                };
+                device.consumer_key(key, state)
+            }
+            InputAction::ReleaseAll => device.release_all(),
+        }
+    }
+}
+
+impl From<MacroEvent> for InputAction {
+    fn from(event: MacroEvent) -> Self {
+        InputAction::Event(event)
+    }
+}
+
+/// An event with its time since the start of the macro
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TimedEvent {
+    /// time since the start in microseconds
+    #[cfg_attr(feature = "serde", serde(rename = "at_us", with = "micros"))]
+    pub at: Duration,
+    #[cfg_attr(feature = "serde", serde(flatten))]
+    pub event: MacroEvent,
+}
+
+#[cfg(feature = "serde")]
+mod micros {
+    use std::time::Duration;
+
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(at: &Duration, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u64(at.as_micros().try_into().unwrap_or(u64::MAX))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+        u64::deserialize(deserializer).map(Duration::from_micros)
+    }
+}
+
+/// Timestamped events sorted by time
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Macro {
+    pub version: u16,
+    pub events: Vec<TimedEvent>,
+}
+
+impl Default for Macro {
+    fn default() -> Self {
+        Self {
+            version: MACRO_FORMAT_VERSION,
+            events: Vec::new(),
+        }
+    }
+}
+
+// tags of the binary encoding
+const TAG_MOVE: u8 = 0;
+const TAG_BUTTON_DOWN: u8 = 1;
+const TAG_BUTTON_UP: u8 = 2;
+const TAG_WHEEL: u8 = 3;
+const TAG_KEY_DOWN: u8 = 4;
+const TAG_KEY_UP: u8 = 5;
+
+const BUTTONS: [MouseButton; 5] = [
+    MouseButton::Left,
+    MouseButton::Right,
+    MouseButton::Middle,
+    MouseButton::Side1,
+    MouseButton::Side2,
+];
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn write_signed(out: &mut Vec<u8>, value: i32) {
+    // zigzag so small negative values stay short
+    write_varint(out, ((value << 1) ^ (value >> 31)) as u32 as u64);
+}
+
+/// Reads the binary encoding, errors carry the index of the event
+struct Decoder<'a> {
+    bytes: &'a [u8],
+    index: usize,
+}
+
+impl Decoder<'_> {
+    fn error(&self, reason: impl Into<String>) -> InputMiddlewareMacroError {
+        InputMiddlewareMacroError::InvalidEvent {
+            index: self.index,
+            reason: reason.into(),
+        }
+    }
+
+    fn byte(&mut self) -> Result<u8, InputMiddlewareMacroError> {
+        let (first, rest) = self
+            .bytes
+            .split_first()
+            .ok_or_else(|| self.error("unexpected end of data"))?;
+        self.bytes = rest;
+        Ok(*first)
+    }
+
+    fn varint(&mut self) -> Result<u64, InputMiddlewareMacroError> {
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.error("varint is too long"))
+    }
+
+    fn signed(&mut self) -> Result<i32, InputMiddlewareMacroError> {
+        let value = u32::try_from(self.varint()?).map_err(|_| self.error("value out of range"))?;
+        Ok((value >> 1) as i32 ^ -((value & 1) as i32))
+    }
+}
+
+impl Macro {
+    /// Encode as `IMMC`, the version as u16 LE and the number of events as a varint, then
+    /// per event the microseconds since the previous event as a varint, a tag byte and the
+    /// payload (zigzag varints for moves and wheel, the button index or the HID usage of keys)
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(8 + self.events.len() * 4);
+        out.extend_from_slice(MAGIC);
+        out.extend_from_slice(&MACRO_FORMAT_VERSION.to_le_bytes());
+        write_varint(&mut out, self.events.len() as u64);
+        let mut previous = Duration::ZERO;
+        for timed in &self.events {
+            let delta = timed.at.saturating_sub(previous).as_micros();
+            write_varint(&mut out, delta.try_into().unwrap_or(u64::MAX));
+            previous = previous.max(timed.at);
+            match timed.event {
+                MacroEvent::Move { x, y } => {
+                    out.push(TAG_MOVE);
+                    write_signed(&mut out, x);
+                    write_signed(&mut out, y);
+                }
+                MacroEvent::Button { button, pressed } => {
+                    out.push(if pressed {
+                        TAG_BUTTON_DOWN
+                    } else {
+                        TAG_BUTTON_UP
+                    });
+                    out.push(BUTTONS.iter().position(|b| *b == button).unwrap_or(0) as u8);
+                }
+                MacroEvent::Wheel { delta } => {
+                    out.push(TAG_WHEEL);
+                    write_signed(&mut out, delta);
+                }
+                MacroEvent::Key { key, pressed } => {
+                    out.push(if pressed { TAG_KEY_DOWN } else { TAG_KEY_UP });
+                    out.push(key.hid_usage());
+                }
+            }
+        }
+        out
+    }
+
+    /// Decode the binary encoding of [`to_bytes`](Self::to_bytes)
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, InputMiddlewareMacroError> {
+        let rest = bytes
+            .strip_prefix(MAGIC)
+            .ok_or(InputMiddlewareMacroError::InvalidMagic)?;
+        if rest.len() < 2 {
+            return Err(InputMiddlewareMacroError::InvalidMagic);
+        }
+        let version = u16::from_le_bytes([rest[0], rest[1]]);
+        if version == 0 || version > MACRO_FORMAT_VERSION {
+            return Err(InputMiddlewareMacroError::UnsupportedVersion(version));
+        }
+        let mut decoder = Decoder {
+            bytes: &rest[2..],
+            index: 0,
+        };
+        let count = decoder.varint()?;
+        let mut events = Vec::with_capacity(count.min(1 << 16) as usize);
+        let mut at = Duration::ZERO;
+        for index in 0..count as usize {
+            decoder.index = index;
+            at = at
+                .checked_add(Duration::from_micros(decoder.varint()?))
+                .ok_or_else(|| decoder.error("event time overflows"))?;
+            let event = match decoder.byte()? {
+                TAG_MOVE => MacroEvent::Move {
+                    x: decoder.signed()?,
+                    y: decoder.signed()?,
+                },
+                tag @ (TAG_BUTTON_DOWN | TAG_BUTTON_UP) => {
+                    let index = decoder.byte()?;
+                    MacroEvent::Button {
+                        button: *BUTTONS
+                            .get(usize::from(index))
+                            .ok_or_else(|| decoder.error(format!("unknown button {index}")))?,
+                        pressed: tag == TAG_BUTTON_DOWN,
+                    }
+                }
+                TAG_WHEEL => MacroEvent::Wheel {
+                    delta: decoder.signed()?,
+                },
+                tag @ (TAG_KEY_DOWN | TAG_KEY_UP) => MacroEvent::Key {
+                    key: KeyboardKey::try_from(decoder.byte()?)
+                        .map_err(|e| decoder.error(e.to_string()))?,
+                    pressed: tag == TAG_KEY_DOWN,
+                },
+                tag => return Err(decoder.error(format!("unknown tag {tag}"))),
+            };
+            events.push(TimedEvent { at, event });
+        }
+        if !decoder.bytes.is_empty() {
+            decoder.index = events.len();
+            return Err(decoder.error("trailing data after the last event"));
+        }
+        Ok(Self { version, events })
+    }
+
+    pub fn write_binary(&self, mut writer: impl Write) -> Result<(), InputMiddlewareMacroError> {
+        writer.write_all(&self.to_bytes())?;
+        Ok(())
+    }
+
+    pub fn read_binary(mut reader: impl Read) -> Result<Self, InputMiddlewareMacroError> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+        Self::from_bytes(&bytes)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> Result<String, InputMiddlewareMacroError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn from_json(json: &str) -> Result<Self, InputMiddlewareMacroError> {
+        let parsed: Self = serde_json::from_str(json)?;
+        if parsed.version == 0 || parsed.version > MACRO_FORMAT_VERSION {
+            return Err(InputMiddlewareMacroError::UnsupportedVersion(
+                parsed.version,
+            ));
+        }
+        Ok(parsed)
+    }
+
+    /// Save as JSON if the extension is `.json`, otherwise in the binary encoding
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), InputMiddlewareMacroError> {
+        let path = path.as_ref();
+        #[cfg(feature = "serde")]
+        if is_json(path) {
+            fs::write(path, self.to_json()?)?;
+            return Ok(());
+        }
+        fs::write(path, self.to_bytes())?;
+        Ok(())
+    }
+
+    /// Load a macro written by [`save`](Self::save)
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, InputMiddlewareMacroError> {
+        let path = path.as_ref();
+        #[cfg(feature = "serde")]
+        if is_json(path) {
+            return Self::from_json(&fs::read_to_string(path)?);
+        }
+        Self::from_bytes(&fs::read(path)?)
+    }
+
+    /// Time of the last event
+    pub fn duration(&self) -> Duration {
+        self.events.last().map(|e| e.at).unwrap_or_default()
+    }
+}
+
+#[cfg(feature = "serde")]
+fn is_json(path: &Path) -> bool {
+    path.extension().and_then(|e| e.to_str()) == Some("json")
+}
+
+/// Turns monitor reports into macro events by comparing each report with the previous one
+#[cfg(feature = "kmbox_net")]
+#[derive(Debug)]
+pub struct MacroRecorder {
+    start: Instant,
+    previous: MonitorData,
+    events: Vec<TimedEvent>,
+}
+
+#[cfg(feature = "kmbox_net")]
+impl Default for MacroRecorder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "kmbox_net")]
+impl MacroRecorder {
+    /// Start recording now, nothing is assumed to be held down
+    pub fn new() -> Self {
+        Self {
+            start: Instant::now(),
+            previous: MonitorData::default(),
+            events: Vec::new(),
+        }
+    }
+
+    /// Record a report received now
+    pub fn record(&mut self, data: &MonitorData) {
+        self.record_at(self.start.elapsed(), data);
+    }
+
+    /// Record a report received `at` after the start
+    pub fn record_at(&mut self, at: Duration, data: &MonitorData) {
+        let mut push = |event| self.events.push(TimedEvent { at, event });
+
+        let (mouse, previous_mouse) = (data.mouse, self.previous.mouse);
+        for (bit, button) in BUTTONS.iter().enumerate() {
+            let pressed = mouse.buttons & (1 << bit) != 0;
+            if pressed != (previous_mouse.buttons & (1 << bit) != 0) {
+                push(MacroEvent::Button {
+                    button: *button,
+                    pressed,
+                });
+            }
+        }
+        if mouse.x != 0 || mouse.y != 0 {
+            push(MacroEvent::Move {
+                x: mouse.x.into(),
+                y: mouse.y.into(),
+            });
+        }
+        if mouse.wheel != 0 {
+            push(MacroEvent::Wheel {
+                delta: mouse.wheel.into(),
+            });
+        }
+
+        let (keyboard, previous_keyboard) = (data.keyboard, self.previous.keyboard);
+        let modifier = KeyboardKey::KEY_LEFTCONTROL.hid_usage();
+        for bit in 0..8 {
+            let pressed = keyboard.buttons & (1 << bit) != 0;
+            if pressed != (previous_keyboard.buttons & (1 << bit) != 0) {
+                if let Ok(key) = KeyboardKey::try_from(modifier + bit) {
+                    push(MacroEvent::Key { key, pressed });
+                }
+            }
+        }
+        let keys = |codes: [u8; 10]| codes.into_iter().filter(|code| *code != 0);
+        for code in keys(previous_keyboard.data).filter(|c| !keyboard.data.contains(c)) {
+            match KeyboardKey::try_from(code) {
+                Ok(key) => push(MacroEvent::Key {
+                    key,
+                    pressed: false,
+                }),
+                Err(e) => warn!("Skipping released key: {e}"),
+            }
+        }
+        for code in keys(keyboard.data).filter(|c| !previous_keyboard.data.contains(c)) {
+            match KeyboardKey::try_from(code) {
+                Ok(key) => push(MacroEvent::Key { key, pressed: true }),
+                Err(e) => warn!("Skipping pressed key: {e}"),
+            }
+        }
+        self.previous = *data;
+    }
+
+    /// The events recorded so far
+    pub fn events(&self) -> &[TimedEvent] {
+        &self.events
+    }
+
+    pub fn finish(self) -> Macro {
+        Macro {
+            version: MACRO_FORMAT_VERSION,
+            events: self.events,
+        }
+    }
+}
+
+/// Stops a [`MacroPlayer`] from another thread
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// How a playback ended
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Playback {
+    Finished,
+    /// cancelled with the [`CancelToken`], everything held was released
+    Cancelled,
+}
+
+/// Plays a [`Macro`] on a device
+#[derive(Debug, Clone)]
+pub struct MacroPlayer {
+    /// playback speed, 2.0 plays twice as fast, default is 1.0
+    pub speed: f64,
+    /// how often the macro is played, 0 repeats until cancelled, default is 1
+    pub loops: u32,
+    cancel: CancelToken,
+}
+
+impl Default for MacroPlayer {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            loops: 1,
+            cancel: CancelToken::default(),
+        }
+    }
+}
+
+/// Longest sleep before the cancel token is checked again
+const CANCEL_POLL: Duration = Duration::from_millis(10);
+
+impl MacroPlayer {
+    pub fn set_speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    pub fn set_loops(mut self, loops: u32) -> Self {
+        self.loops = loops;
+        self
+    }
+
+    /// Token to cancel the playback from another thread
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// Play the macro, blocking until it is finished or cancelled
+    pub fn play<D: InputMiddlewareDeviceAction + ?Sized>(
+        &self,
+        device: &mut D,
+        input_macro: &Macro,
+    ) -> Result<Playback, InputMiddlewareSendError> {
+        let speed = match self.speed.is_finite() && self.speed > 0.0 {
+            true => self.speed,
+            false => 1.0,
+        };
+        if input_macro.events.is_empty() {
+            return Ok(Playback::Finished);
+        }
+        let mut played = 0;
+        while self.loops == 0 || played < self.loops {
+            if self.cancel.is_cancelled() {
+                device.release_all()?;
+                return Ok(Playback::Cancelled);
+            }
+            debug!("Playing macro loop {}", played + 1);
+            let begin = Instant::now();
+            for timed in &input_macro.events {
+                let deadline = Duration::try_from_secs_f64(timed.at.as_secs_f64() / speed)
+                    .ok()
+                    .and_then(|offset| begin.checked_add(offset))
+                    .ok_or_else(|| {
+                        InputMiddlewareSendError(std::io::Error::new(
+                            std::io::ErrorKind::InvalidInput,
+                            format!("event time {:?} is out of range", timed.at),
+                        ))
+                    })?;
+                while let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+                    if self.cancel.is_cancelled() {
+                        break;
+                    }
+                    thread::sleep(wait.min(CANCEL_POLL));
+                }
+                if self.cancel.is_cancelled() {
+                    device.release_all()?;
+                    return Ok(Playback::Cancelled);
+                }
+                apply(device, timed.event)?;
+            }
+            played += 1;
+        }
+        Ok(Playback::Finished)
+    }
+}
+
+pub(crate) fn apply<D: InputMiddlewareDeviceAction + ?Sized>(
+    device: &mut D,
+    event: MacroEvent,
+) -> Result<(), InputMiddlewareSendError> {
+    match event {
+        MacroEvent::Move { x, y } => device.mouse_move([x, y]),
+        MacroEvent::Button { button, pressed } => {
+            let state = match pressed {
+                true => ButtonState::Pressed,
+                false => ButtonState::Released,
+            };
+            match button {
+                MouseButton::Left => device.mouse_left_click(state),
+                MouseButton::Right => device.mouse_right_click(state),
+                MouseButton::Middle => device.mouse_middle_click(state),
+                MouseButton::Side1 => device.mouse_side1_click(state),
+                MouseButton::Side2 => device.mouse_side2_click(state),
+            }
+        }
+        MacroEvent::Wheel { delta } if delta < 0 => match delta.checked_neg() {
+            Some(down) => device.mouse_wheel(MwheelState::Down(down)),
+            None => Err(InputMiddlewareSendError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("wheel delta {delta} is out of range"),
+            ))),
+        },
+        MacroEvent::Wheel { delta } => device.mouse_wheel(MwheelState::Up(delta)),
+        MacroEvent::Key { key, pressed: true } => device.keyboard_keydown(key),
+        MacroEvent::Key {
+            key,
+            pressed: false,
+        } => device.keyboard_keyup(key),
+    }
+}
@@ -0,0 +1,523 @@
+//! Keyboard layouts map characters to the HID keys that produce them on the target PC.
+//!
+//! The HID usage only describes the physical key, which character ends up on screen depends
+//! on the layout the target PC has configured. Pick the layout matching the target or
+//! implement [`KeyboardLayout`] for one that is not built in.
+
+use crate::{
+    chord::Chord,
+    keyboardkeys::KeyboardKey::{self, *},
+};
+
+/// A single key press with the modifiers that have to be held while pressing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyStroke {
+    pub key: KeyboardKey,
+    pub shift: bool,
+    /// right alt, called AltGr on most european layouts
+    pub altgr: bool,
+}
+
+impl KeyStroke {
+    pub const fn plain(key: KeyboardKey) -> Self {
+        Self {
+            key,
+            shift: false,
+            altgr: false,
+        }
+    }
+
+    pub const fn shift(key: KeyboardKey) -> Self {
+        Self {
+            key,
+            shift: true,
+            altgr: false,
+        }
+    }
+
+    pub const fn altgr(key: KeyboardKey) -> Self {
+        Self {
+            key,
+            shift: false,
+            altgr: true,
+        }
+    }
+
+    /// The modifier keys held while the key is pressed
+    pub fn modifiers(&self) -> impl Iterator<Item = KeyboardKey> {
+        [
+            self.shift.then_some(KEY_LEFTSHIFT),
+            self.altgr.then_some(KEY_RIGHTALT),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    /// The modifiers and the key as a chord
+    pub fn chord(&self) -> Chord {
+        Chord::new(self.modifiers().chain([self.key]))
+            .expect("a stroke never repeats a key and is never empty")
+    }
+}
+
+pub trait KeyboardLayout: std::fmt::Debug {
+    fn name(&self) -> &str;
+
+    /// The key strokes that produce `c`, characters behind a dead key need more than one.
+    /// Returns `None` when the character can not be typed on this layout.
+    fn strokes(&self, c: char) -> Option<Vec<KeyStroke>>;
+}
+
+/// A dead key and the characters it composes with the following base character
+struct DeadKey {
+    stroke: KeyStroke,
+    /// the character typed by the dead key followed by space
+    spacing: char,
+    /// (base, composed)
+    compose: &'static [(char, char)],
+}
+
+/// Look up `c` in the direct mapping and fall back to the dead key compositions
+fn strokes_with_dead_keys(
+    c: char,
+    direct: fn(char) -> Option<KeyStroke>,
+    dead_keys: &[DeadKey],
+) -> Option<Vec<KeyStroke>> {
+    if let Some(stroke) = direct(c) {
+        return Some(vec![stroke]);
+    }
+    for dead_key in dead_keys {
+        if c == dead_key.spacing {
+            return Some(vec![dead_key.stroke, KeyStroke::plain(KEY_SPACEBAR)]);
+        }
+        if let Some((base, _)) = dead_key.compose.iter().find(|(_, composed)| *composed == c) {
+            return direct(*base).map(|stroke| vec![dead_key.stroke, stroke]);
+        }
+    }
+    None
+}
+
+/// Keys that are the same on every built in layout
+fn common(c: char) -> Option<KeyStroke> {
+    Some(match c {
+        ' ' => KeyStroke::plain(KEY_SPACEBAR),
+        '\n' => KeyStroke::plain(KEY_ENTER),
+        '\t' => KeyStroke::plain(KEY_TAB),
+        _ => return None,
+    })
+}
+
+/// The letter key at the position of `c` on a qwerty layout
+fn qwerty_letter(c: char) -> Option<KeyboardKey> {
+    const LETTERS: [KeyboardKey; 26] = [
+        KEY_A, KEY_B, KEY_C, KEY_D, KEY_E, KEY_F, KEY_G, KEY_H, KEY_I, KEY_J, KEY_K, KEY_L, KEY_M,
+        KEY_N, KEY_O, KEY_P, KEY_Q, KEY_R, KEY_S, KEY_T, KEY_U, KEY_V, KEY_W, KEY_X, KEY_Y, KEY_Z,
+    ];
+    c.is_ascii_alphabetic()
+        .then(|| LETTERS[(c.to_ascii_lowercase() as u8 - b'a') as usize])
+}
+
+/// The digit row key at the position of `c`
+fn digit(c: char) -> Option<KeyboardKey> {
+    const DIGITS: [KeyboardKey; 10] = [
+        KEY_0_CPARENTHESIS,
+        KEY_1_EXCLAMATION_MARK,
+        KEY_2_AT,
+        KEY_3_NUMBER_SIGN,
+        KEY_4_DOLLAR,
+        KEY_5_PERCENT,
+        KEY_6_CARET,
+        KEY_7_AMPERSAND,
+        KEY_8_ASTERISK,
+        KEY_9_OPARENTHESIS,
+    ];
+    c.to_digit(10).map(|d| DIGITS[d as usize])
+}
+
+/// Letters on a qwerty layout, upper case is typed with shift
+fn qwerty_letters(c: char) -> Option<KeyStroke> {
+    let key = qwerty_letter(c)?;
+    Some(match c.is_ascii_uppercase() {
+        true => KeyStroke::shift(key),
+        false => KeyStroke::plain(key),
+    })
+}
+
+/// US ANSI layout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UsLayout;
+
+impl UsLayout {
+    fn direct(c: char) -> Option<KeyStroke> {
+        if let Some(stroke) = common(c).or_else(|| qwerty_letters(c)) {
+            return Some(stroke);
+        }
+        if let Some(key) = digit(c) {
+            return Some(KeyStroke::plain(key));
+        }
+        Some(match c {
+            '!' => KeyStroke::shift(KEY_1_EXCLAMATION_MARK),
+            '@' => KeyStroke::shift(KEY_2_AT),
+            '#' => KeyStroke::shift(KEY_3_NUMBER_SIGN),
+            '$' => KeyStroke::shift(KEY_4_DOLLAR),
+            '%' => KeyStroke::shift(KEY_5_PERCENT),
+            '^' => KeyStroke::shift(KEY_6_CARET),
+            '&' => KeyStroke::shift(KEY_7_AMPERSAND),
+            '*' => KeyStroke::shift(KEY_8_ASTERISK),
+            '(' => KeyStroke::shift(KEY_9_OPARENTHESIS),
+            ')' => KeyStroke::shift(KEY_0_CPARENTHESIS),
+            '-' => KeyStroke::plain(KEY_MINUS_UNDERSCORE),
+            '_' => KeyStroke::shift(KEY_MINUS_UNDERSCORE),
+            '=' => KeyStroke::plain(KEY_EQUAL_PLUS),
+            '+' => KeyStroke::shift(KEY_EQUAL_PLUS),
+            '[' => KeyStroke::plain(KEY_OBRACKET_AND_OBRACE),
+            '{' => KeyStroke::shift(KEY_OBRACKET_AND_OBRACE),
+            ']' => KeyStroke::plain(KEY_CBRACKET_AND_CBRACE),
+            '}' => KeyStroke::shift(KEY_CBRACKET_AND_CBRACE),
+            '\\' => KeyStroke::plain(KEY_BACKSLASH_VERTICAL_BAR),
+            '|' => KeyStroke::shift(KEY_BACKSLASH_VERTICAL_BAR),
+            ';' => KeyStroke::plain(KEY_SEMICOLON_COLON),
+            ':' => KeyStroke::shift(KEY_SEMICOLON_COLON),
+            '\'' => KeyStroke::plain(KEY_SINGLE_AND_DOUBLE_QUOTE),
+            '"' => KeyStroke::shift(KEY_SINGLE_AND_DOUBLE_QUOTE),
+            '`' => KeyStroke::plain(KEY_GRAVE_ACCENT_AND_TILDE),
+            '~' => KeyStroke::shift(KEY_GRAVE_ACCENT_AND_TILDE),
+            ',' => KeyStroke::plain(KEY_COMMA_AND_LESS),
+            '<' => KeyStroke::shift(KEY_COMMA_AND_LESS),
+            '.' => KeyStroke::plain(KEY_DOT_GREATER),
+            '>' => KeyStroke::shift(KEY_DOT_GREATER),
+            '/' => KeyStroke::plain(KEY_SLASH_QUESTION),
+            '?' => KeyStroke::shift(KEY_SLASH_QUESTION),
+            _ => return None,
+        })
+    }
+}
+
+impl KeyboardLayout for UsLayout {
+    fn name(&self) -> &str {
+        "us"
+    }
+
+    fn strokes(&self, c: char) -> Option<Vec<KeyStroke>> {
+        Self::direct(c).map(|stroke| vec![stroke])
+    }
+}
+
+/// UK ISO layout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UkLayout;
+
+impl UkLayout {
+    fn direct(c: char) -> Option<KeyStroke> {
+        Some(match c {
+            '"' => KeyStroke::shift(KEY_2_AT),
+            '£' => KeyStroke::shift(KEY_3_NUMBER_SIGN),
+            '€' => KeyStroke::altgr(KEY_4_DOLLAR),
+            '\'' => KeyStroke::plain(KEY_SINGLE_AND_DOUBLE_QUOTE),
+            '@' => KeyStroke::shift(KEY_SINGLE_AND_DOUBLE_QUOTE),
+            '#' => KeyStroke::plain(KEY_NONUS_NUMBER_SIGN_TILDE),
+            '~' => KeyStroke::shift(KEY_NONUS_NUMBER_SIGN_TILDE),
+            '\\' => KeyStroke::plain(KEY_NONUS_BACK_SLASH_VERTICAL_BAR),
+            '|' => KeyStroke::shift(KEY_NONUS_BACK_SLASH_VERTICAL_BAR),
+            '`' => KeyStroke::plain(KEY_GRAVE_ACCENT_AND_TILDE),
+            '¬' => KeyStroke::shift(KEY_GRAVE_ACCENT_AND_TILDE),
+            _ => return UsLayout::direct(c),
+        })
+    }
+}
+
+impl KeyboardLayout for UkLayout {
+    fn name(&self) -> &str {
+        "uk"
+    }
+
+    fn strokes(&self, c: char) -> Option<Vec<KeyStroke>> {
+        Self::direct(c).map(|stroke| vec![stroke])
+    }
+}
+
+/// German QWERTZ layout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DeLayout;
+
+impl DeLayout {
+    const DEAD_KEYS: [DeadKey; 3] = [
+        DeadKey {
+            stroke: KeyStroke::plain(KEY_GRAVE_ACCENT_AND_TILDE),
+            spacing: '^',
+            compose: &[
+                ('a', 'â'),
+                ('e', 'ê'),
+                ('i', 'î'),
+                ('o', 'ô'),
+                ('u', 'û'),
+                ('A', 'Â'),
+                ('E', 'Ê'),
+                ('I', 'Î'),
+                ('O', 'Ô'),
+                ('U', 'Û'),
+            ],
+        },
+        DeadKey {
+            stroke: KeyStroke::plain(KEY_EQUAL_PLUS),
+            spacing: '´',
+            compose: &[
+                ('a', 'á'),
+                ('e', 'é'),
+                ('i', 'í'),
+                ('o', 'ó'),
+                ('u', 'ú'),
+                ('y', 'ý'),
+                ('A', 'Á'),
+                ('E', 'É'),
+                ('I', 'Í'),
+                ('O', 'Ó'),
+                ('U', 'Ú'),
+                ('Y', 'Ý'),
+            ],
+        },
+        DeadKey {
+            stroke: KeyStroke::shift(KEY_EQUAL_PLUS),
+            spacing: '`',
+            compose: &[
+                ('a', 'à'),
+                ('e', 'è'),
+                ('i', 'ì'),
+                ('o', 'ò'),
+                ('u', 'ù'),
+                ('A', 'À'),
+                ('E', 'È'),
+                ('I', 'Ì'),
+                ('O', 'Ò'),
+                ('U', 'Ù'),
+            ],
+        },
+    ];
+
+    fn direct(c: char) -> Option<KeyStroke> {
+        if let Some(stroke) = common(c) {
+            return Some(stroke);
+        }
+        // y and z swap places on qwertz
+        let letter = match c {
+            'y' | 'Y' => Some(KEY_Z),
+            'z' | 'Z' => Some(KEY_Y),
+            _ => qwerty_letter(c),
+        };
+        if let Some(key) = letter {
+            return Some(match c.is_ascii_uppercase() {
+                true => KeyStroke::shift(key),
+                false => KeyStroke::plain(key),
+            });
+        }
+        if let Some(key) = digit(c) {
+            return Some(KeyStroke::plain(key));
+        }
+        Some(match c {
+            '!' => KeyStroke::shift(KEY_1_EXCLAMATION_MARK),
+            '"' => KeyStroke::shift(KEY_2_AT),
+            '²' => KeyStroke::altgr(KEY_2_AT),
+            '§' => KeyStroke::shift(KEY_3_NUMBER_SIGN),
+            '³' => KeyStroke::altgr(KEY_3_NUMBER_SIGN),
+            '$' => KeyStroke::shift(KEY_4_DOLLAR),
+            '%' => KeyStroke::shift(KEY_5_PERCENT),
+            '&' => KeyStroke::shift(KEY_6_CARET),
+            '/' => KeyStroke::shift(KEY_7_AMPERSAND),
+            '{' => KeyStroke::altgr(KEY_7_AMPERSAND),
+            '(' => KeyStroke::shift(KEY_8_ASTERISK),
+            '[' => KeyStroke::altgr(KEY_8_ASTERISK),
+            ')' => KeyStroke::shift(KEY_9_OPARENTHESIS),
+            ']' => KeyStroke::altgr(KEY_9_OPARENTHESIS),
+            '=' => KeyStroke::shift(KEY_0_CPARENTHESIS),
+            '}' => KeyStroke::altgr(KEY_0_CPARENTHESIS),
+            'ß' => KeyStroke::plain(KEY_MINUS_UNDERSCORE),
+            '?' => KeyStroke::shift(KEY_MINUS_UNDERSCORE),
+            '\\' => KeyStroke::altgr(KEY_MINUS_UNDERSCORE),
+            'ü' => KeyStroke::plain(KEY_OBRACKET_AND_OBRACE),
+            'Ü' => KeyStroke::shift(KEY_OBRACKET_AND_OBRACE),
+            '+' => KeyStroke::plain(KEY_CBRACKET_AND_CBRACE),
+            '*' => KeyStroke::shift(KEY_CBRACKET_AND_CBRACE),
+            '~' => KeyStroke::altgr(KEY_CBRACKET_AND_CBRACE),
+            '#' => KeyStroke::plain(KEY_NONUS_NUMBER_SIGN_TILDE),
+            '\'' => KeyStroke::shift(KEY_NONUS_NUMBER_SIGN_TILDE),
+            'ö' => KeyStroke::plain(KEY_SEMICOLON_COLON),
+            'Ö' => KeyStroke::shift(KEY_SEMICOLON_COLON),
+            'ä' => KeyStroke::plain(KEY_SINGLE_AND_DOUBLE_QUOTE),
+            'Ä' => KeyStroke::shift(KEY_SINGLE_AND_DOUBLE_QUOTE),
+            '°' => KeyStroke::shift(KEY_GRAVE_ACCENT_AND_TILDE),
+            ',' => KeyStroke::plain(KEY_COMMA_AND_LESS),
+            ';' => KeyStroke::shift(KEY_COMMA_AND_LESS),
+            '.' => KeyStroke::plain(KEY_DOT_GREATER),
+            ':' => KeyStroke::shift(KEY_DOT_GREATER),
+            '-' => KeyStroke::plain(KEY_SLASH_QUESTION),
+            '_' => KeyStroke::shift(KEY_SLASH_QUESTION),
+            '<' => KeyStroke::plain(KEY_NONUS_BACK_SLASH_VERTICAL_BAR),
+            '>' => KeyStroke::shift(KEY_NONUS_BACK_SLASH_VERTICAL_BAR),
+            '|' => KeyStroke::altgr(KEY_NONUS_BACK_SLASH_VERTICAL_BAR),
+            '@' => KeyStroke::altgr(KEY_Q),
+            '€' => KeyStroke::altgr(KEY_E),
+            'µ' => KeyStroke::altgr(KEY_M),
+            _ => return None,
+        })
+    }
+}
+
+impl KeyboardLayout for DeLayout {
+    fn name(&self) -> &str {
+        "de"
+    }
+
+    fn strokes(&self, c: char) -> Option<Vec<KeyStroke>> {
+        strokes_with_dead_keys(c, Self::direct, &Self::DEAD_KEYS)
+    }
+}
+
+/// French AZERTY layout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FrLayout;
+
+impl FrLayout {
+    const DEAD_KEYS: [DeadKey; 4] = [
+        DeadKey {
+            stroke: KeyStroke::plain(KEY_OBRACKET_AND_OBRACE),
+            spacing: '^',
+            compose: &[
+                ('a', 'â'),
+                ('e', 'ê'),
+                ('i', 'î'),
+                ('o', 'ô'),
+                ('u', 'û'),
+                ('A', 'Â'),
+                ('E', 'Ê'),
+                ('I', 'Î'),
+                ('O', 'Ô'),
+                ('U', 'Û'),
+            ],
+        },
+        DeadKey {
+            stroke: KeyStroke::shift(KEY_OBRACKET_AND_OBRACE),
+            spacing: '¨',
+            compose: &[
+                ('a', 'ä'),
+                ('e', 'ë'),
+                ('i', 'ï'),
+                ('o', 'ö'),
+                ('u', 'ü'),
+                ('y', 'ÿ'),
+                ('A', 'Ä'),
+                ('E', 'Ë'),
+                ('I', 'Ï'),
+                ('O', 'Ö'),
+                ('U', 'Ü'),
+            ],
+        },
+        DeadKey {
+            stroke: KeyStroke::altgr(KEY_2_AT),
+            spacing: '~',
+            compose: &[
+                ('n', 'ñ'),
+                ('o', 'õ'),
+                ('a', 'ã'),
+                ('N', 'Ñ'),
+                ('O', 'Õ'),
+                ('A', 'Ã'),
+            ],
+        },
+        DeadKey {
+            stroke: KeyStroke::altgr(KEY_7_AMPERSAND),
+            spacing: '`',
+            compose: &[
+                ('a', 'à'),
+                ('e', 'è'),
+                ('i', 'ì'),
+                ('o', 'ò'),
+                ('u', 'ù'),
+                ('A', 'À'),
+                ('E', 'È'),
+                ('I', 'Ì'),
+                ('O', 'Ò'),
+                ('U', 'Ù'),
+            ],
+        },
+    ];
+
+    fn direct(c: char) -> Option<KeyStroke> {
+        if let Some(stroke) = common(c) {
+            return Some(stroke);
+        }
+        // a/q and z/w swap places and m moves next to l on azerty
+        let letter = match c.to_ascii_lowercase() {
+            'a' => Some(KEY_Q),
+            'q' => Some(KEY_A),
+            'z' => Some(KEY_W),
+            'w' => Some(KEY_Z),
+            'm' => Some(KEY_SEMICOLON_COLON),
+            _ => qwerty_letter(c),
+        };
+        if let Some(key) = letter {
+            return Some(match c.is_ascii_uppercase() {
+                true => KeyStroke::shift(key),
+                false => KeyStroke::plain(key),
+            });
+        }
+        // the digits need shift on azerty
+        if let Some(key) = digit(c) {
+            return Some(KeyStroke::shift(key));
+        }
+        Some(match c {
+            '&' => KeyStroke::plain(KEY_1_EXCLAMATION_MARK),
+            'é' => KeyStroke::plain(KEY_2_AT),
+            '"' => KeyStroke::plain(KEY_3_NUMBER_SIGN),
+            '#' => KeyStroke::altgr(KEY_3_NUMBER_SIGN),
+            '\'' => KeyStroke::plain(KEY_4_DOLLAR),
+            '{' => KeyStroke::altgr(KEY_4_DOLLAR),
+            '(' => KeyStroke::plain(KEY_5_PERCENT),
+            '[' => KeyStroke::altgr(KEY_5_PERCENT),
+            '-' => KeyStroke::plain(KEY_6_CARET),
+            '|' => KeyStroke::altgr(KEY_6_CARET),
+            'è' => KeyStroke::plain(KEY_7_AMPERSAND),
+            '_' => KeyStroke::plain(KEY_8_ASTERISK),
+            '\\' => KeyStroke::altgr(KEY_8_ASTERISK),
+            'ç' => KeyStroke::plain(KEY_9_OPARENTHESIS),
+            '^' => KeyStroke::altgr(KEY_9_OPARENTHESIS),
+            'à' => KeyStroke::plain(KEY_0_CPARENTHESIS),
+            '@' => KeyStroke::altgr(KEY_0_CPARENTHESIS),
+            ')' => KeyStroke::plain(KEY_MINUS_UNDERSCORE),
+            '°' => KeyStroke::shift(KEY_MINUS_UNDERSCORE),
+            ']' => KeyStroke::altgr(KEY_MINUS_UNDERSCORE),
+            '=' => KeyStroke::plain(KEY_EQUAL_PLUS),
+            '+' => KeyStroke::shift(KEY_EQUAL_PLUS),
+            '}' => KeyStroke::altgr(KEY_EQUAL_PLUS),
+            '$' => KeyStroke::plain(KEY_CBRACKET_AND_CBRACE),
+            '£' => KeyStroke::shift(KEY_CBRACKET_AND_CBRACE),
+            '¤' => KeyStroke::altgr(KEY_CBRACKET_AND_CBRACE),
+            '*' => KeyStroke::plain(KEY_NONUS_NUMBER_SIGN_TILDE),
+            'µ' => KeyStroke::shift(KEY_NONUS_NUMBER_SIGN_TILDE),
+            'ù' => KeyStroke::plain(KEY_SINGLE_AND_DOUBLE_QUOTE),
+            '%' => KeyStroke::shift(KEY_SINGLE_AND_DOUBLE_QUOTE),
+            '²' => KeyStroke::plain(KEY_GRAVE_ACCENT_AND_TILDE),
+            ',' => KeyStroke::plain(KEY_M),
+            '?' => KeyStroke::shift(KEY_M),
+            ';' => KeyStroke::plain(KEY_COMMA_AND_LESS),
+            '.' => KeyStroke::shift(KEY_COMMA_AND_LESS),
+            ':' => KeyStroke::plain(KEY_DOT_GREATER),
+            '/' => KeyStroke::shift(KEY_DOT_GREATER),
+            '!' => KeyStroke::plain(KEY_SLASH_QUESTION),
+            '§' => KeyStroke::shift(KEY_SLASH_QUESTION),
+            '<' => KeyStroke::plain(KEY_NONUS_BACK_SLASH_VERTICAL_BAR),
+            '>' => KeyStroke::shift(KEY_NONUS_BACK_SLASH_VERTICAL_BAR),
+            '€' => KeyStroke::altgr(KEY_E),
+            _ => return None,
+        })
+    }
+}
+
+impl KeyboardLayout for FrLayout {
+    fn name(&self) -> &str {
+        "fr"
+    }
+
+    fn strokes(&self, c: char) -> Option<Vec<KeyStroke>> {
+        strokes_with_dead_keys(c, Self::direct, &Self::DEAD_KEYS)
+    }
+}
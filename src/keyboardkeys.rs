@@ -1,223 +1,515 @@
-#[allow(non_camel_case_types)]
-#[derive(Debug, Clone, Copy)]
-pub enum KeyboardKey {
-    KEY_NONE,
-    KEY_ERRORROLLOVER,
-    KEY_POSTFAIL,
-    KEY_ERRORUNDEFINED,
-    KEY_A,
-    KEY_B,
-    KEY_C,
-    KEY_D,
-    KEY_E,
-    KEY_F,
-    KEY_G,
-    KEY_H,
-    KEY_I,
-    KEY_J,
-    KEY_K,
-    KEY_L,
-    KEY_M,
-    KEY_N,
-    KEY_O,
-    KEY_P,
-    KEY_Q,
-    KEY_R,
-    KEY_S,
-    KEY_T,
-    KEY_U,
-    KEY_V,
-    KEY_W,
-    KEY_X,
-    KEY_Y,
-    KEY_Z,
-    KEY_1_EXCLAMATION_MARK,
-    KEY_2_AT,
-    KEY_3_NUMBER_SIGN,
-    KEY_4_DOLLAR,
-    KEY_5_PERCENT,
-    KEY_6_CARET,
-    KEY_7_AMPERSAND,
-    KEY_8_ASTERISK,
-    KEY_9_OPARENTHESIS,
-    KEY_0_CPARENTHESIS,
-    KEY_ENTER,
-    KEY_ESCAPE,
-    KEY_BACKSPACE,
-    KEY_TAB,
-    KEY_SPACEBAR,
-    KEY_MINUS_UNDERSCORE,
-    KEY_EQUAL_PLUS,
-    KEY_OBRACKET_AND_OBRACE,
-    KEY_CBRACKET_AND_CBRACE,
-    KEY_BACKSLASH_VERTICAL_BAR,
-    KEY_NONUS_NUMBER_SIGN_TILDE,
-    KEY_SEMICOLON_COLON,
-    KEY_SINGLE_AND_DOUBLE_QUOTE,
-    KEY_GRAVE_ACCENT_AND_TILDE,
-    KEY_COMMA_AND_LESS,
-    KEY_DOT_GREATER,
-    KEY_SLASH_QUESTION,
-    KEY_CAPS_LOCK,
-    KEY_F1,
-    KEY_F2,
-    KEY_F3,
-    KEY_F4,
-    KEY_F5,
-    KEY_F6,
-    KEY_F7,
-    KEY_F8,
-    KEY_F9,
-    KEY_F10,
-    KEY_F11,
-    KEY_F12,
-    KEY_PRINTSCREEN,
-    KEY_SCROLL_LOCK,
-    KEY_PAUSE,
-    KEY_INSERT,
-    KEY_HOME,
-    KEY_PAGEUP,
-    KEY_DELETE,
-    KEY_END1,
-    KEY_PAGEDOWN,
-    KEY_RIGHTARROW,
-    KEY_LEFTARROW,
-    KEY_DOWNARROW,
-    KEY_UPARROW,
-    KEY_KEYPAD_NUM_LOCK_AND_CLEAR,
-    KEY_KEYPAD_SLASH,
-    KEY_KEYPAD_ASTERIKS,
-    KEY_KEYPAD_MINUS,
-    KEY_KEYPAD_PLUS,
-    KEY_KEYPAD_ENTER,
-    KEY_KEYPAD_1_END,
-    KEY_KEYPAD_2_DOWN_ARROW,
-    KEY_KEYPAD_3_PAGEDN,
-    KEY_KEYPAD_4_LEFT_ARROW,
-    KEY_KEYPAD_5,
-    KEY_KEYPAD_6_RIGHT_ARROW,
-    KEY_KEYPAD_7_HOME,
-    KEY_KEYPAD_8_UP_ARROW,
-    KEY_KEYPAD_9_PAGEUP,
-    KEY_KEYPAD_0_INSERT,
-    KEY_KEYPAD_DECIMAL_SEPARATOR_DELETE,
-    KEY_NONUS_BACK_SLASH_VERTICAL_BAR,
-    KEY_APPLICATION,
-    KEY_POWER,
-    KEY_KEYPAD_EQUAL,
-    KEY_F13,
-    KEY_F14,
-    KEY_F15,
-    KEY_F16,
-    KEY_F17,
-    KEY_F18,
-    KEY_F19,
-    KEY_F20,
-    KEY_F21,
-    KEY_F22,
-    KEY_F23,
-    KEY_F24,
-    KEY_EXECUTE,
-    KEY_HELP,
-    KEY_MENU,
-    KEY_SELECT,
-    KEY_STOP,
-    KEY_AGAIN,
-    KEY_UNDO,
-    KEY_CUT,
-    KEY_COPY,
-    KEY_PASTE,
-    KEY_FIND,
-    KEY_MUTE,
-    KEY_VOLUME_UP,
-    KEY_VOLUME_DOWN,
-    KEY_LOCKING_CAPS_LOCK,
-    KEY_LOCKING_NUM_LOCK,
-    KEY_LOCKING_SCROLL_LOCK,
-    KEY_KEYPAD_COMMA,
-    KEY_KEYPAD_EQUAL_SIGN,
-    KEY_INTERNATIONAL1,
-    KEY_INTERNATIONAL2,
-    KEY_INTERNATIONAL3,
-    KEY_INTERNATIONAL4,
-    KEY_INTERNATIONAL5,
-    KEY_INTERNATIONAL6,
-    KEY_INTERNATIONAL7,
-    KEY_INTERNATIONAL8,
-    KEY_INTERNATIONAL9,
-    KEY_LANG1,
-    KEY_LANG2,
-    KEY_LANG3,
-    KEY_LANG4,
-    KEY_LANG5,
-    KEY_LANG6,
-    KEY_LANG7,
-    KEY_LANG8,
-    KEY_LANG9,
-    KEY_ALTERNATE_ERASE,
-    KEY_SYSREQ,
-    KEY_CANCEL,
-    KEY_CLEAR,
-    KEY_PRIOR,
-    KEY_RETURN,
-    KEY_SEPARATOR,
-    KEY_OUT,
-    KEY_OPER,
-    KEY_CLEAR_AGAIN,
-    KEY_CRSEL,
-    KEY_EXSEL,
-    KEY_KEYPAD_00,
-    KEY_KEYPAD_000,
-    KEY_THOUSANDS_SEPARATOR,
-    KEY_DECIMAL_SEPARATOR,
-    KEY_CURRENCY_UNIT,
-    KEY_CURRENCY_SUB_UNIT,
-    KEY_KEYPAD_OPARENTHESIS,
-    KEY_KEYPAD_CPARENTHESIS,
-    KEY_KEYPAD_OBRACE,
-    KEY_KEYPAD_CBRACE,
-    KEY_KEYPAD_TAB,
-    KEY_KEYPAD_BACKSPACE,
-    KEY_KEYPAD_A,
-    KEY_KEYPAD_B,
-    KEY_KEYPAD_C,
-    KEY_KEYPAD_D,
-    KEY_KEYPAD_E,
-    KEY_KEYPAD_F,
-    KEY_KEYPAD_XOR,
-    KEY_KEYPAD_CARET,
-    KEY_KEYPAD_PERCENT,
-    KEY_KEYPAD_LESS,
-    KEY_KEYPAD_GREATER,
-    KEY_KEYPAD_AMPERSAND,
-    KEY_KEYPAD_LOGICAL_AND,
-    KEY_KEYPAD_VERTICAL_BAR,
-    KEY_KEYPAD_LOGICAL_OR,
-    KEY_KEYPAD_COLON,
-    KEY_KEYPAD_NUMBER_SIGN,
-    KEY_KEYPAD_SPACE,
-    KEY_KEYPAD_AT,
-    KEY_KEYPAD_EXCLAMATION_MARK,
-    KEY_KEYPAD_MEMORY_STORE,
-    KEY_KEYPAD_MEMORY_RECALL,
-    KEY_KEYPAD_MEMORY_CLEAR,
-    KEY_KEYPAD_MEMORY_ADD,
-    KEY_KEYPAD_MEMORY_SUBTRACT,
-    KEY_KEYPAD_MEMORY_MULTIPLY,
-    KEY_KEYPAD_MEMORY_DIVIDE,
-    KEY_KEYPAD_PLUSMINUS,
-    KEY_KEYPAD_CLEAR,
-    KEY_KEYPAD_CLEAR_ENTRY,
-    KEY_KEYPAD_BINARY,
-    KEY_KEYPAD_OCTAL,
-    KEY_KEYPAD_DECIMAL,
-    KEY_KEYPAD_HEXADECIMAL,
-    KEY_LEFTCONTROL,
-    KEY_LEFTSHIFT,
-    KEY_LEFTALT,
-    KEY_LEFT_GUI,
-    KEY_RIGHTCONTROL,
-    KEY_RIGHTSHIFT,
-    KEY_RIGHTALT,
-    KEY_RIGHT_GUI,
+//! HID usage codes of the keyboard page (0x07) and the consumer page (0x0C).
+//!
+//! The discriminant of every [`KeyboardKey`] is its HID usage, so `key as u8` and
+//! [`KeyboardKey::hid_usage`] are what ends up in the keyboard report.
+
+use std::{fmt, str::FromStr};
+
+use crate::errors::InputMiddlewareParseError;
+
+/// Declare a HID usage enum from a single `NAME = code` table together with the lookups
+/// in both directions and the list of all usages
+macro_rules! hid_usages {
+    ($(#[$meta:meta])* $enum:ident: $repr:ty { $($name:ident = $code:literal,)* }) => {
+        $(#[$meta])*
+        #[allow(non_camel_case_types)]
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        #[repr($repr)]
+        pub enum $enum {
+            $($name = $code,)*
+        }
+
+        impl $enum {
+            const ALL: &'static [$enum] = &[$($enum::$name,)*];
+
+            /// Every usage in ascending order
+            pub fn all() -> impl Iterator<Item = $enum> {
+                Self::ALL.iter().copied()
+            }
+
+            /// The HID usage code
+            pub const fn hid_usage(&self) -> $repr {
+                *self as $repr
+            }
+
+            /// The name of the variant, e.g. `KEY_A`
+            pub const fn name(&self) -> &'static str {
+                match self {
+                    $($enum::$name => stringify!($name),)*
+                }
+            }
+        }
+
+        impl TryFrom<$repr> for $enum {
+            type Error = InputMiddlewareParseError;
+
+            fn try_from(code: $repr) -> Result<Self, Self::Error> {
+                match code {
+                    $($code => Ok($enum::$name),)*
+                    _ => Err(InputMiddlewareParseError::UnknownKeyCode(code.into())),
+                }
+            }
+        }
+
+        impl From<$enum> for $repr {
+            fn from(key: $enum) -> Self {
+                key.hid_usage()
+            }
+        }
+
+        impl fmt::Display for $enum {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.name())
+            }
+        }
+
+        /// Serialized as the variant name, anything [`FromStr`] accepts can be deserialized
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $enum {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                serializer.serialize_str(self.name())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $enum {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let name = <std::borrow::Cow<'de, str>>::deserialize(deserializer)?;
+                name.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+hid_usages! {
+    /// Keys of the HID keyboard/keypad page
+    KeyboardKey: u8 {
+        KEY_NONE = 0x00,
+        KEY_ERRORROLLOVER = 0x01,
+        KEY_POSTFAIL = 0x02,
+        KEY_ERRORUNDEFINED = 0x03,
+        KEY_A = 0x04,
+        KEY_B = 0x05,
+        KEY_C = 0x06,
+        KEY_D = 0x07,
+        KEY_E = 0x08,
+        KEY_F = 0x09,
+        KEY_G = 0x0A,
+        KEY_H = 0x0B,
+        KEY_I = 0x0C,
+        KEY_J = 0x0D,
+        KEY_K = 0x0E,
+        KEY_L = 0x0F,
+        KEY_M = 0x10,
+        KEY_N = 0x11,
+        KEY_O = 0x12,
+        KEY_P = 0x13,
+        KEY_Q = 0x14,
+        KEY_R = 0x15,
+        KEY_S = 0x16,
+        KEY_T = 0x17,
+        KEY_U = 0x18,
+        KEY_V = 0x19,
+        KEY_W = 0x1A,
+        KEY_X = 0x1B,
+        KEY_Y = 0x1C,
+        KEY_Z = 0x1D,
+        KEY_1_EXCLAMATION_MARK = 0x1E,
+        KEY_2_AT = 0x1F,
+        KEY_3_NUMBER_SIGN = 0x20,
+        KEY_4_DOLLAR = 0x21,
+        KEY_5_PERCENT = 0x22,
+        KEY_6_CARET = 0x23,
+        KEY_7_AMPERSAND = 0x24,
+        KEY_8_ASTERISK = 0x25,
+        KEY_9_OPARENTHESIS = 0x26,
+        KEY_0_CPARENTHESIS = 0x27,
+        KEY_ENTER = 0x28,
+        KEY_ESCAPE = 0x29,
+        KEY_BACKSPACE = 0x2A,
+        KEY_TAB = 0x2B,
+        KEY_SPACEBAR = 0x2C,
+        KEY_MINUS_UNDERSCORE = 0x2D,
+        KEY_EQUAL_PLUS = 0x2E,
+        KEY_OBRACKET_AND_OBRACE = 0x2F,
+        KEY_CBRACKET_AND_CBRACE = 0x30,
+        KEY_BACKSLASH_VERTICAL_BAR = 0x31,
+        KEY_NONUS_NUMBER_SIGN_TILDE = 0x32,
+        KEY_SEMICOLON_COLON = 0x33,
+        KEY_SINGLE_AND_DOUBLE_QUOTE = 0x34,
+        KEY_GRAVE_ACCENT_AND_TILDE = 0x35,
+        KEY_COMMA_AND_LESS = 0x36,
+        KEY_DOT_GREATER = 0x37,
+        KEY_SLASH_QUESTION = 0x38,
+        KEY_CAPS_LOCK = 0x39,
+        KEY_F1 = 0x3A,
+        KEY_F2 = 0x3B,
+        KEY_F3 = 0x3C,
+        KEY_F4 = 0x3D,
+        KEY_F5 = 0x3E,
+        KEY_F6 = 0x3F,
+        KEY_F7 = 0x40,
+        KEY_F8 = 0x41,
+        KEY_F9 = 0x42,
+        KEY_F10 = 0x43,
+        KEY_F11 = 0x44,
+        KEY_F12 = 0x45,
+        KEY_PRINTSCREEN = 0x46,
+        KEY_SCROLL_LOCK = 0x47,
+        KEY_PAUSE = 0x48,
+        KEY_INSERT = 0x49,
+        KEY_HOME = 0x4A,
+        KEY_PAGEUP = 0x4B,
+        KEY_DELETE = 0x4C,
+        KEY_END1 = 0x4D,
+        KEY_PAGEDOWN = 0x4E,
+        KEY_RIGHTARROW = 0x4F,
+        KEY_LEFTARROW = 0x50,
+        KEY_DOWNARROW = 0x51,
+        KEY_UPARROW = 0x52,
+        KEY_KEYPAD_NUM_LOCK_AND_CLEAR = 0x53,
+        KEY_KEYPAD_SLASH = 0x54,
+        KEY_KEYPAD_ASTERIKS = 0x55,
+        KEY_KEYPAD_MINUS = 0x56,
+        KEY_KEYPAD_PLUS = 0x57,
+        KEY_KEYPAD_ENTER = 0x58,
+        KEY_KEYPAD_1_END = 0x59,
+        KEY_KEYPAD_2_DOWN_ARROW = 0x5A,
+        KEY_KEYPAD_3_PAGEDN = 0x5B,
+        KEY_KEYPAD_4_LEFT_ARROW = 0x5C,
+        KEY_KEYPAD_5 = 0x5D,
+        KEY_KEYPAD_6_RIGHT_ARROW = 0x5E,
+        KEY_KEYPAD_7_HOME = 0x5F,
+        KEY_KEYPAD_8_UP_ARROW = 0x60,
+        KEY_KEYPAD_9_PAGEUP = 0x61,
+        KEY_KEYPAD_0_INSERT = 0x62,
+        KEY_KEYPAD_DECIMAL_SEPARATOR_DELETE = 0x63,
+        KEY_NONUS_BACK_SLASH_VERTICAL_BAR = 0x64,
+        KEY_APPLICATION = 0x65,
+        KEY_POWER = 0x66,
+        KEY_KEYPAD_EQUAL = 0x67,
+        KEY_F13 = 0x68,
+        KEY_F14 = 0x69,
+        KEY_F15 = 0x6A,
+        KEY_F16 = 0x6B,
+        KEY_F17 = 0x6C,
+        KEY_F18 = 0x6D,
+        KEY_F19 = 0x6E,
+        KEY_F20 = 0x6F,
+        KEY_F21 = 0x70,
+        KEY_F22 = 0x71,
+        KEY_F23 = 0x72,
+        KEY_F24 = 0x73,
+        KEY_EXECUTE = 0x74,
+        KEY_HELP = 0x75,
+        KEY_MENU = 0x76,
+        KEY_SELECT = 0x77,
+        KEY_STOP = 0x78,
+        KEY_AGAIN = 0x79,
+        KEY_UNDO = 0x7A,
+        KEY_CUT = 0x7B,
+        KEY_COPY = 0x7C,
+        KEY_PASTE = 0x7D,
+        KEY_FIND = 0x7E,
+        KEY_MUTE = 0x7F,
+        KEY_VOLUME_UP = 0x80,
+        KEY_VOLUME_DOWN = 0x81,
+        KEY_LOCKING_CAPS_LOCK = 0x82,
+        KEY_LOCKING_NUM_LOCK = 0x83,
+        KEY_LOCKING_SCROLL_LOCK = 0x84,
+        KEY_KEYPAD_COMMA = 0x85,
+        KEY_KEYPAD_EQUAL_SIGN = 0x86,
+        KEY_INTERNATIONAL1 = 0x87,
+        KEY_INTERNATIONAL2 = 0x88,
+        KEY_INTERNATIONAL3 = 0x89,
+        KEY_INTERNATIONAL4 = 0x8A,
+        KEY_INTERNATIONAL5 = 0x8B,
+        KEY_INTERNATIONAL6 = 0x8C,
+        KEY_INTERNATIONAL7 = 0x8D,
+        KEY_INTERNATIONAL8 = 0x8E,
+        KEY_INTERNATIONAL9 = 0x8F,
+        KEY_LANG1 = 0x90,
+        KEY_LANG2 = 0x91,
+        KEY_LANG3 = 0x92,
+        KEY_LANG4 = 0x93,
+        KEY_LANG5 = 0x94,
+        KEY_LANG6 = 0x95,
+        KEY_LANG7 = 0x96,
+        KEY_LANG8 = 0x97,
+        KEY_LANG9 = 0x98,
+        KEY_ALTERNATE_ERASE = 0x99,
+        KEY_SYSREQ = 0x9A,
+        KEY_CANCEL = 0x9B,
+        KEY_CLEAR = 0x9C,
+        KEY_PRIOR = 0x9D,
+        KEY_RETURN = 0x9E,
+        KEY_SEPARATOR = 0x9F,
+        KEY_OUT = 0xA0,
+        KEY_OPER = 0xA1,
+        KEY_CLEAR_AGAIN = 0xA2,
+        KEY_CRSEL = 0xA3,
+        KEY_EXSEL = 0xA4,
+        KEY_KEYPAD_00 = 0xB0,
+        KEY_KEYPAD_000 = 0xB1,
+        KEY_THOUSANDS_SEPARATOR = 0xB2,
+        KEY_DECIMAL_SEPARATOR = 0xB3,
+        KEY_CURRENCY_UNIT = 0xB4,
+        KEY_CURRENCY_SUB_UNIT = 0xB5,
+        KEY_KEYPAD_OPARENTHESIS = 0xB6,
+        KEY_KEYPAD_CPARENTHESIS = 0xB7,
+        KEY_KEYPAD_OBRACE = 0xB8,
+        KEY_KEYPAD_CBRACE = 0xB9,
+        KEY_KEYPAD_TAB = 0xBA,
+        KEY_KEYPAD_BACKSPACE = 0xBB,
+        KEY_KEYPAD_A = 0xBC,
+        KEY_KEYPAD_B = 0xBD,
+        KEY_KEYPAD_C = 0xBE,
+        KEY_KEYPAD_D = 0xBF,
+        KEY_KEYPAD_E = 0xC0,
+        KEY_KEYPAD_F = 0xC1,
+        KEY_KEYPAD_XOR = 0xC2,
+        KEY_KEYPAD_CARET = 0xC3,
+        KEY_KEYPAD_PERCENT = 0xC4,
+        KEY_KEYPAD_LESS = 0xC5,
+        KEY_KEYPAD_GREATER = 0xC6,
+        KEY_KEYPAD_AMPERSAND = 0xC7,
+        KEY_KEYPAD_LOGICAL_AND = 0xC8,
+        KEY_KEYPAD_VERTICAL_BAR = 0xC9,
+        KEY_KEYPAD_LOGICAL_OR = 0xCA,
+        KEY_KEYPAD_COLON = 0xCB,
+        KEY_KEYPAD_NUMBER_SIGN = 0xCC,
+        KEY_KEYPAD_SPACE = 0xCD,
+        KEY_KEYPAD_AT = 0xCE,
+        KEY_KEYPAD_EXCLAMATION_MARK = 0xCF,
+        KEY_KEYPAD_MEMORY_STORE = 0xD0,
+        KEY_KEYPAD_MEMORY_RECALL = 0xD1,
+        KEY_KEYPAD_MEMORY_CLEAR = 0xD2,
+        KEY_KEYPAD_MEMORY_ADD = 0xD3,
+        KEY_KEYPAD_MEMORY_SUBTRACT = 0xD4,
+        KEY_KEYPAD_MEMORY_MULTIPLY = 0xD5,
+        KEY_KEYPAD_MEMORY_DIVIDE = 0xD6,
+        KEY_KEYPAD_PLUSMINUS = 0xD7,
+        KEY_KEYPAD_CLEAR = 0xD8,
+        KEY_KEYPAD_CLEAR_ENTRY = 0xD9,
+        KEY_KEYPAD_BINARY = 0xDA,
+        KEY_KEYPAD_OCTAL = 0xDB,
+        KEY_KEYPAD_DECIMAL = 0xDC,
+        KEY_KEYPAD_HEXADECIMAL = 0xDD,
+        KEY_LEFTCONTROL = 0xE0,
+        KEY_LEFTSHIFT = 0xE1,
+        KEY_LEFTALT = 0xE2,
+        KEY_LEFT_GUI = 0xE3,
+        KEY_RIGHTCONTROL = 0xE4,
+        KEY_RIGHTSHIFT = 0xE5,
+        KEY_RIGHTALT = 0xE6,
+        KEY_RIGHT_GUI = 0xE7,
+    }
+}
+
+hid_usages! {
+    /// Media and application keys of the HID consumer page, these are not part of the
+    /// keyboard report and need a backend that can send consumer reports
+    ConsumerKey: u16 {
+        BRIGHTNESS_UP = 0x006F,
+        BRIGHTNESS_DOWN = 0x0070,
+        MEDIA_NEXT_TRACK = 0x00B5,
+        MEDIA_PREVIOUS_TRACK = 0x00B6,
+        MEDIA_STOP = 0x00B7,
+        MEDIA_EJECT = 0x00B8,
+        MEDIA_PLAY_PAUSE = 0x00CD,
+        MEDIA_MUTE = 0x00E2,
+        MEDIA_VOLUME_UP = 0x00E9,
+        MEDIA_VOLUME_DOWN = 0x00EA,
+        APP_EMAIL = 0x018A,
+        APP_CALCULATOR = 0x0192,
+        APP_BROWSER = 0x0194,
+        BROWSER_SEARCH = 0x0221,
+        BROWSER_HOME = 0x0223,
+        BROWSER_BACK = 0x0224,
+        BROWSER_FORWARD = 0x0225,
+        BROWSER_REFRESH = 0x0227,
+        BROWSER_BOOKMARKS = 0x022A,
+    }
+}
+
+use KeyboardKey::*;
+
+/// Short names accepted next to the variant names, the first name of a key is its short name
+const ALIASES: &[(&str, KeyboardKey)] = &[
+    ("ctrl", KEY_LEFTCONTROL),
+    ("control", KEY_LEFTCONTROL),
+    ("lctrl", KEY_LEFTCONTROL),
+    ("rctrl", KEY_RIGHTCONTROL),
+    ("shift", KEY_LEFTSHIFT),
+    ("lshift", KEY_LEFTSHIFT),
+    ("rshift", KEY_RIGHTSHIFT),
+    ("alt", KEY_LEFTALT),
+    ("lalt", KEY_LEFTALT),
+    ("altgr", KEY_RIGHTALT),
+    ("ralt", KEY_RIGHTALT),
+    ("win", KEY_LEFT_GUI),
+    ("lwin", KEY_LEFT_GUI),
+    ("gui", KEY_LEFT_GUI),
+    ("super", KEY_LEFT_GUI),
+    ("meta", KEY_LEFT_GUI),
+    ("cmd", KEY_LEFT_GUI),
+    ("rwin", KEY_RIGHT_GUI),
+    ("esc", KEY_ESCAPE),
+    ("escape", KEY_ESCAPE),
+    ("enter", KEY_ENTER),
+    ("return", KEY_ENTER),
+    ("tab", KEY_TAB),
+    ("space", KEY_SPACEBAR),
+    ("backspace", KEY_BACKSPACE),
+    ("del", KEY_DELETE),
+    ("delete", KEY_DELETE),
+    ("ins", KEY_INSERT),
+    ("insert", KEY_INSERT),
+    ("home", KEY_HOME),
+    ("end", KEY_END1),
+    ("pgup", KEY_PAGEUP),
+    ("pageup", KEY_PAGEUP),
+    ("pgdn", KEY_PAGEDOWN),
+    ("pagedown", KEY_PAGEDOWN),
+    ("up", KEY_UPARROW),
+    ("down", KEY_DOWNARROW),
+    ("left", KEY_LEFTARROW),
+    ("right", KEY_RIGHTARROW),
+    ("capslock", KEY_CAPS_LOCK),
+    ("numlock", KEY_KEYPAD_NUM_LOCK_AND_CLEAR),
+    ("scrolllock", KEY_SCROLL_LOCK),
+    ("printscreen", KEY_PRINTSCREEN),
+    ("prtsc", KEY_PRINTSCREEN),
+    ("pause", KEY_PAUSE),
+    ("menu", KEY_APPLICATION),
+    ("minus", KEY_MINUS_UNDERSCORE),
+    ("-", KEY_MINUS_UNDERSCORE),
+    ("equal", KEY_EQUAL_PLUS),
+    ("=", KEY_EQUAL_PLUS),
+    ("lbracket", KEY_OBRACKET_AND_OBRACE),
+    ("[", KEY_OBRACKET_AND_OBRACE),
+    ("rbracket", KEY_CBRACKET_AND_CBRACE),
+    ("]", KEY_CBRACKET_AND_CBRACE),
+    ("backslash", KEY_BACKSLASH_VERTICAL_BAR),
+    ("\\", KEY_BACKSLASH_VERTICAL_BAR),
+    ("semicolon", KEY_SEMICOLON_COLON),
+    (";", KEY_SEMICOLON_COLON),
+    ("quote", KEY_SINGLE_AND_DOUBLE_QUOTE),
+    ("'", KEY_SINGLE_AND_DOUBLE_QUOTE),
+    ("grave", KEY_GRAVE_ACCENT_AND_TILDE),
+    ("`", KEY_GRAVE_ACCENT_AND_TILDE),
+    ("comma", KEY_COMMA_AND_LESS),
+    (",", KEY_COMMA_AND_LESS),
+    ("period", KEY_DOT_GREATER),
+    (".", KEY_DOT_GREATER),
+    ("slash", KEY_SLASH_QUESTION),
+    ("/", KEY_SLASH_QUESTION),
+    ("a", KEY_A),
+    ("b", KEY_B),
+    ("c", KEY_C),
+    ("d", KEY_D),
+    ("e", KEY_E),
+    ("f", KEY_F),
+    ("g", KEY_G),
+    ("h", KEY_H),
+    ("i", KEY_I),
+    ("j", KEY_J),
+    ("k", KEY_K),
+    ("l", KEY_L),
+    ("m", KEY_M),
+    ("n", KEY_N),
+    ("o", KEY_O),
+    ("p", KEY_P),
+    ("q", KEY_Q),
+    ("r", KEY_R),
+    ("s", KEY_S),
+    ("t", KEY_T),
+    ("u", KEY_U),
+    ("v", KEY_V),
+    ("w", KEY_W),
+    ("x", KEY_X),
+    ("y", KEY_Y),
+    ("z", KEY_Z),
+    ("1", KEY_1_EXCLAMATION_MARK),
+    ("2", KEY_2_AT),
+    ("3", KEY_3_NUMBER_SIGN),
+    ("4", KEY_4_DOLLAR),
+    ("5", KEY_5_PERCENT),
+    ("6", KEY_6_CARET),
+    ("7", KEY_7_AMPERSAND),
+    ("8", KEY_8_ASTERISK),
+    ("9", KEY_9_OPARENTHESIS),
+    ("0", KEY_0_CPARENTHESIS),
+    ("f1", KEY_F1),
+    ("f2", KEY_F2),
+    ("f3", KEY_F3),
+    ("f4", KEY_F4),
+    ("f5", KEY_F5),
+    ("f6", KEY_F6),
+    ("f7", KEY_F7),
+    ("f8", KEY_F8),
+    ("f9", KEY_F9),
+    ("f10", KEY_F10),
+    ("f11", KEY_F11),
+    ("f12", KEY_F12),
+    ("f13", KEY_F13),
+    ("f14", KEY_F14),
+    ("f15", KEY_F15),
+    ("f16", KEY_F16),
+    ("f17", KEY_F17),
+    ("f18", KEY_F18),
+    ("f19", KEY_F19),
+    ("f20", KEY_F20),
+    ("f21", KEY_F21),
+    ("f22", KEY_F22),
+    ("f23", KEY_F23),
+    ("f24", KEY_F24),
+];
+
+impl KeyboardKey {
+    /// ctrl, shift, alt and gui on either side
+    pub fn is_modifier(&self) -> bool {
+        (KEY_LEFTCONTROL..=KEY_RIGHT_GUI).contains(self)
+    }
+
+    /// A short lower case name like `ctrl` or `f4` if the key has one
+    pub fn short_name(&self) -> Option<&'static str> {
+        ALIASES
+            .iter()
+            .find(|(_, key)| key == self)
+            .map(|(name, _)| *name)
+    }
+}
+
+impl FromStr for KeyboardKey {
+    type Err = InputMiddlewareParseError;
+
+    /// Parse the variant name with or without the `KEY_` prefix or a short name,
+    /// case insensitive, e.g. `KEY_ESCAPE`, `escape` or `esc`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        let upper = name.to_ascii_uppercase();
+        let upper = upper.strip_prefix("KEY_").unwrap_or(&upper);
+        KeyboardKey::all()
+            .find(|key| &key.name()[4..] == upper)
+            .or_else(|| {
+                ALIASES
+                    .iter()
+                    .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+                    .map(|(_, key)| *key)
+            })
+            .ok_or_else(|| InputMiddlewareParseError::UnknownKey(name.to_string()))
+    }
+}
+
+impl FromStr for ConsumerKey {
+    type Err = InputMiddlewareParseError;
+
+    /// Parse the variant name, case insensitive, e.g. `MEDIA_PLAY_PAUSE`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        ConsumerKey::all()
+            .find(|key| key.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| InputMiddlewareParseError::UnknownKey(name.to_string()))
+    }
 }
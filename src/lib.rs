@@ -28,28 +28,62 @@
 //! ```
 
 use button_state::{ButtonState, MwheelState};
+use capabilities::DeviceCapabilities;
+use chord::Chord;
 use devices::kmbox_net::KMBoxNetConfig;
-use errors::{InputMiddlewareConnectionError, InputMiddlewareSendError};
-use keyboardkeys::KeyboardKey;
+use errors::{
+    InputMiddlewareConfigError, InputMiddlewareConnectionError, InputMiddlewareSendError,
+    InputMiddlewareTypingError,
+};
+use keyboardkeys::{ConsumerKey, KeyboardKey};
 
 pub mod button_state;
+pub mod capabilities;
+pub mod chord;
+#[cfg(feature = "serde")]
+pub mod config;
+pub mod cursor;
 pub mod devices;
 pub mod errors;
+pub mod input_macro;
+pub mod keyboard_layout;
 pub mod keyboardkeys;
+mod logging;
+pub mod motion;
+pub mod release;
+pub mod scheduler;
+pub mod script;
+pub mod subpixel;
+pub mod throttle;
+pub mod typing;
 use devices::kmbox_net::KMBoxNet;
+use typing::{TypingOptions, TypingReport};
 
 // The devices that are supported by this library.
 // TODO: Add more devices here.
 // add layer of abstraction to device initialization
 // pass enum to select device
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "device"))]
 pub enum InputDevice {
+    #[cfg_attr(feature = "serde", serde(rename = "kmbox_net"))]
     KMBoxNet(KMBoxNetConfig),
 }
 
+impl InputDevice {
+    /// Check the device config before connecting
+    pub fn validate(&self) -> Result<(), InputMiddlewareConfigError> {
+        match self {
+            InputDevice::KMBoxNet(config) => Ok(config.validate()?),
+        }
+    }
+}
+
 pub struct InputMiddleware;
 
 impl InputMiddleware {
+    #[allow(clippy::new_ret_no_self)]
     pub fn new(
         device: InputDevice,
     ) -> Result<Box<dyn InputMiddlewareDeviceAction>, InputMiddlewareConnectionError> {
@@ -74,5 +108,75 @@ pub trait InputMiddlewareDeviceAction {
     fn mouse_side2_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError>;
     fn mouse_wheel_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError>;
     fn mouse_wheel(&mut self, state: MwheelState) -> Result<(), InputMiddlewareSendError>;
+    /// Relative move, moves over [`DeviceCapabilities::max_mouse_move`] are split into several
+    /// reports by the device
     fn mouse_move(&mut self, pos: [i32; 2]) -> Result<(), InputMiddlewareSendError>;
+
+    /// Limits and features of the device, see [`DeviceCapabilities`]
+    fn capabilities(&self) -> DeviceCapabilities {
+        DeviceCapabilities::default()
+    }
+
+    /// Release every key and mouse button that is still held down, backends that do not track
+    /// held input return `Unsupported`
+    fn release_all(&mut self) -> Result<(), InputMiddlewareSendError> {
+        Err(InputMiddlewareSendError(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "held input can not be released by this device",
+        )))
+    }
+
+    /// Press or release a media key, backends without a consumer report return `Unsupported`
+    fn consumer_key(
+        &mut self,
+        key: ConsumerKey,
+        _state: ButtonState,
+    ) -> Result<(), InputMiddlewareSendError> {
+        Err(InputMiddlewareSendError(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            format!("{key} can not be sent by this device"),
+        )))
+    }
+
+    /// Press the keys of the chord, modifiers first.
+    /// If a key can not be pressed the keys pressed so far are released again.
+    fn press_chord(&mut self, chord: &Chord) -> Result<(), InputMiddlewareSendError> {
+        for (pressed, key) in chord.keys().iter().enumerate() {
+            if let Err(e) = self.keyboard_keydown(*key) {
+                for key in chord.keys()[..pressed].iter().rev() {
+                    let _ = self.keyboard_keyup(*key);
+                }
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Release the keys of the chord in reverse order.
+    /// Every key is released even if one fails, the first error is returned.
+    fn release_chord(&mut self, chord: &Chord) -> Result<(), InputMiddlewareSendError> {
+        let mut result = Ok(());
+        for key in chord.keys().iter().rev() {
+            let released = self.keyboard_keyup(*key);
+            if result.is_ok() {
+                result = released;
+            }
+        }
+        result
+    }
+
+    /// Press and release the chord
+    fn tap_chord(&mut self, chord: &Chord) -> Result<(), InputMiddlewareSendError> {
+        self.press_chord(chord)?;
+        self.release_chord(chord)
+    }
+
+    /// Type the text key by key on the layout from the options
+    fn type_text(
+        &mut self,
+        text: &str,
+        options: &TypingOptions,
+    ) -> Result<TypingReport, InputMiddlewareTypingError> {
+        typing::type_text(self, text, options)
+    }
 }
@@ -0,0 +1,12 @@
+//! Log through `tracing` with the `tracing` feature and through `log` otherwise
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) use log::{debug, error, trace, warn};
+#[cfg(feature = "tracing")]
+pub(crate) use tracing::{debug, error, trace, warn};
+
+// only the kmbox net backend logs at info level
+#[cfg(all(feature = "kmbox_net", not(feature = "tracing")))]
+pub(crate) use log::info;
+#[cfg(all(feature = "kmbox_net", feature = "tracing"))]
+pub(crate) use tracing::info;
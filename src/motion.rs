@@ -0,0 +1,230 @@
+//! Animate the mouse along a path, e.g. for demos of UI automation.
+//!
+//! A [`Motion`] samples a [`Curve`] from start to target with an [`Easing`] at a fixed
+//! sample rate and turns the samples into relative deltas that add up to the whole move.
+//!
+//! ```no_run
+//! use std::time::Duration;
+//! use input_middleware::motion::{Curve, Easing, Motion};
+//! # fn run(device: &mut dyn input_middleware::InputMiddlewareDeviceAction) {
+//! Motion::new([0.0, 0.0], [400.0, 120.0])
+//!     .set_curve(Curve::QuadraticBezier([200.0, -150.0]))
+//!     .set_easing(Easing::EaseInOut)
+//!     .set_duration(Duration::from_millis(500))
+//!     .play(device)
+//!     .unwrap();
+//! # }
+//! ```
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::logging::debug;
+
+use crate::{errors::InputMiddlewareSendError, InputMiddlewareDeviceAction};
+
+/// Shape of the path, control points and waypoints are in the coordinates of start and target
+#[derive(Debug, Clone, PartialEq)]
+pub enum Curve {
+    Linear,
+    QuadraticBezier([f64; 2]),
+    CubicBezier([f64; 2], [f64; 2]),
+    /// Catmull-Rom spline that passes through every waypoint
+    Spline(Vec<[f64; 2]>),
+}
+
+/// How the progress along the path changes over time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Easing {
+    /// constant speed
+    #[default]
+    Linear,
+    /// start slow
+    EaseIn,
+    /// end slow
+    EaseOut,
+    /// start and end slow
+    EaseInOut,
+}
+
+impl Easing {
+    /// Map the time `t` in `0..=1` to the progress along the path
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t * t,
+            Easing::EaseOut => 1.0 - (1.0 - t).powi(3),
+            Easing::EaseInOut => match t < 0.5 {
+                true => 4.0 * t * t * t,
+                false => 1.0 - (-2.0 * t + 2.0).powi(3) / 2.0,
+            },
+        }
+    }
+}
+
+fn lerp(a: [f64; 2], b: [f64; 2], t: f64) -> [f64; 2] {
+    [a[0] + (b[0] - a[0]) * t, a[1] + (b[1] - a[1]) * t]
+}
+
+fn catmull_rom(p: [[f64; 2]; 4], t: f64) -> [f64; 2] {
+    let (t2, t3) = (t * t, t * t * t);
+    let axis = |i: usize| {
+        0.5 * (2.0 * p[1][i]
+            + (p[2][i] - p[0][i]) * t
+            + (2.0 * p[0][i] - 5.0 * p[1][i] + 4.0 * p[2][i] - p[3][i]) * t2
+            + (3.0 * p[1][i] - p[0][i] - 3.0 * p[2][i] + p[3][i]) * t3)
+    };
+    [axis(0), axis(1)]
+}
+
+impl Curve {
+    /// The point at progress `t` in `0..=1` of the path from `start` to `target`
+    pub fn point(&self, start: [f64; 2], target: [f64; 2], t: f64) -> [f64; 2] {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Curve::Linear => lerp(start, target, t),
+            Curve::QuadraticBezier(control) => {
+                lerp(lerp(start, *control, t), lerp(*control, target, t), t)
+            }
+            Curve::CubicBezier(c1, c2) => {
+                let (a, b, c) = (lerp(start, *c1, t), lerp(*c1, *c2, t), lerp(*c2, target, t));
+                lerp(lerp(a, b, t), lerp(b, c, t), t)
+            }
+            Curve::Spline(waypoints) => {
+                let points: Vec<[f64; 2]> = std::iter::once(start)
+                    .chain(waypoints.iter().copied())
+                    .chain(std::iter::once(target))
+                    .collect();
+                let segments = points.len() - 1;
+                let position = t * segments as f64;
+                let segment = (position.floor() as usize).min(segments - 1);
+                let at = |i: isize| points[i.clamp(0, segments as isize) as usize];
+                let i = segment as isize;
+                catmull_rom(
+                    [at(i - 1), at(i), at(i + 1), at(i + 2)],
+                    position - segment as f64,
+                )
+            }
+        }
+    }
+}
+
+/// A move from `start` to `target` along a curve, played over `duration`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Motion {
+    pub start: [f64; 2],
+    pub target: [f64; 2],
+    pub curve: Curve,
+    pub easing: Easing,
+    /// default is 250ms
+    pub duration: Duration,
+    /// samples per second, default is 125 (8ms per report)
+    pub sample_rate: u32,
+}
+
+impl Motion {
+    pub fn new(start: [f64; 2], target: [f64; 2]) -> Self {
+        Self {
+            start,
+            target,
+            curve: Curve::Linear,
+            easing: Easing::Linear,
+            duration: Duration::from_millis(250),
+            sample_rate: 125,
+        }
+    }
+
+    /// A move by `delta` from the current position
+    pub fn relative(delta: [f64; 2]) -> Self {
+        Self::new([0.0, 0.0], delta)
+    }
+
+    pub fn set_curve(mut self, curve: Curve) -> Self {
+        self.curve = curve;
+        self
+    }
+
+    pub fn set_easing(mut self, easing: Easing) -> Self {
+        self.easing = easing;
+        self
+    }
+
+    pub fn set_duration(mut self, duration: Duration) -> Self {
+        self.duration = duration;
+        self
+    }
+
+    pub fn set_sample_rate(mut self, sample_rate: u32) -> Self {
+        self.sample_rate = sample_rate;
+        self
+    }
+
+    /// Number of samples, at least one
+    pub fn steps(&self) -> usize {
+        let steps = (self.duration.as_secs_f64() * f64::from(self.sample_rate)).round();
+        (steps as usize).max(1)
+    }
+
+    /// Time between two samples
+    pub fn interval(&self) -> Duration {
+        self.duration.div_f64(self.steps() as f64)
+    }
+
+    /// The relative delta of every sample. The deltas are taken between the rounded points
+    /// so they add up to exactly the rounded target minus the rounded start.
+    pub fn deltas(&self) -> Vec<[i32; 2]> {
+        let steps = self.steps();
+        let round = |p: [f64; 2]| p.map(|v| v.round() as i64);
+        let mut previous = round(self.start);
+        (1..=steps)
+            .map(|step| {
+                let t = match step == steps {
+                    true => 1.0,
+                    false => self.easing.apply(step as f64 / steps as f64),
+                };
+                let point = round(self.curve.point(self.start, self.target, t));
+                let delta = [point[0] - previous[0], point[1] - previous[1]];
+                previous = point;
+                delta.map(|d| d.clamp(i32::MIN.into(), i32::MAX.into()) as i32)
+            })
+            .collect()
+    }
+
+    /// Send the deltas on the device, one every [`interval`](Self::interval).
+    /// The samples are scheduled from the start so slow sends do not add up, the first one
+    /// goes out after one interval and the last one at the end of the duration.
+    /// Samples without movement are skipped.
+    pub fn play<D: InputMiddlewareDeviceAction + ?Sized>(
+        &self,
+        device: &mut D,
+    ) -> Result<(), InputMiddlewareSendError> {
+        // every sample number has to fit in u32 to schedule it
+        u32::try_from(self.steps()).map_err(|_| {
+            InputMiddlewareSendError(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("{} samples are too many for one motion", self.steps()),
+            ))
+        })?;
+        let deltas = self.deltas();
+        let interval = self.interval();
+        debug!(
+            "Playing motion of {} samples every {:?}",
+            deltas.len(),
+            interval
+        );
+        let begin = Instant::now();
+        for (step, delta) in deltas.into_iter().enumerate() {
+            let deadline = begin + interval * (step as u32 + 1);
+            if let Some(wait) = deadline.checked_duration_since(Instant::now()) {
+                thread::sleep(wait);
+            }
+            if delta != [0, 0] {
+                device.mouse_move(delta)?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,88 @@
+//! Release held input when the program panics so no key or button stays stuck on the target PC.
+
+use std::{
+    panic,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, Once, TryLockError, Weak,
+    },
+};
+
+use crate::logging::{debug, error, warn};
+
+use crate::InputMiddlewareDeviceAction;
+
+/// Releases one registered device
+type Release = Box<dyn Fn() + Send>;
+
+/// The devices the panic hook releases, by the id of their guard
+static DEVICES: Mutex<Vec<(u64, Release)>> = Mutex::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+static HOOK: Once = Once::new();
+
+/// Keeps a device registered with the panic hook, see [`release_all_on_panic`]
+#[derive(Debug)]
+#[must_use = "the device is only released on panic while the guard is alive"]
+pub struct ReleaseOnPanic {
+    id: u64,
+}
+
+impl Drop for ReleaseOnPanic {
+    fn drop(&mut self) {
+        let mut devices = DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+        devices.retain(|(id, _)| *id != self.id);
+    }
+}
+
+/// Call [`InputMiddlewareDeviceAction::release_all`] on the device when a thread panics,
+/// until the returned guard is dropped.
+///
+/// The panic hook is installed once for the process and runs before the hook that was set
+/// before it, later calls only register the device. The hook keeps a weak reference, so the
+/// device is still dropped normally. If the panicking thread holds the lock the device can
+/// not be reached from the hook, the `Drop` of the device releases the input once the lock
+/// is gone.
+pub fn release_all_on_panic<D>(device: &Arc<Mutex<D>>) -> ReleaseOnPanic
+where
+    D: InputMiddlewareDeviceAction + Send + ?Sized + 'static,
+{
+    HOOK.call_once(|| {
+        let previous = panic::take_hook();
+        panic::set_hook(Box::new(move |info| {
+            match DEVICES.try_lock() {
+                Ok(devices) => devices.iter().for_each(|(_, release)| release()),
+                Err(TryLockError::Poisoned(poisoned)) => poisoned
+                    .into_inner()
+                    .iter()
+                    .for_each(|(_, release)| release()),
+                Err(TryLockError::WouldBlock) => {
+                    warn!("Devices are being registered, not released")
+                }
+            }
+            previous(info);
+        }));
+    });
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let device = Arc::downgrade(device);
+    let mut devices = DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+    devices.push((id, Box::new(move || release(&device))));
+    ReleaseOnPanic { id }
+}
+
+fn release<D: InputMiddlewareDeviceAction + ?Sized>(device: &Weak<Mutex<D>>) {
+    let Some(device) = device.upgrade() else {
+        return;
+    };
+    let guard = match device.try_lock() {
+        Ok(guard) => Some(guard),
+        Err(TryLockError::Poisoned(poisoned)) => Some(poisoned.into_inner()),
+        Err(TryLockError::WouldBlock) => None,
+    };
+    match guard {
+        Some(mut device) => match device.release_all() {
+            Ok(()) => debug!("Released held input on panic"),
+            Err(e) => error!("Failed to release held input on panic: {e}"),
+        },
+        None => warn!("Device is locked, held input is released when it is dropped"),
+    }
+}
@@ -0,0 +1,251 @@
+//! Send inputs at given instants from a timing thread, see [`Scheduler`].
+//!
+//! The thread sleeps until shortly before the next instant and spins the rest of the way,
+//! every sent input is reported with how late it went out.
+
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, TryRecvError},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    errors::InputMiddlewareSendError,
+    input_macro::InputAction,
+    logging::{error, trace},
+    InputMiddlewareDeviceAction,
+};
+
+/// How long before an instant the thread stops sleeping and spins, sleeps of the OS
+/// overshoot by up to a millisecond
+pub const DEFAULT_SPIN: Duration = Duration::from_millis(2);
+
+/// A sent input
+#[derive(Debug)]
+pub struct EventReport {
+    /// as returned by [`Scheduler::schedule`]
+    pub id: u64,
+    pub action: InputAction,
+    pub scheduled: Instant,
+    /// when the device was called
+    pub sent: Instant,
+    /// how long the device took to send it
+    pub duration: Duration,
+    pub result: Result<(), InputMiddlewareSendError>,
+}
+
+impl EventReport {
+    /// How late the input went out, instants in the past are sent right away
+    pub fn jitter(&self) -> Duration {
+        self.sent.saturating_duration_since(self.scheduled)
+    }
+}
+
+enum Message {
+    Schedule(Scheduled),
+    /// stop once everything is sent
+    Finish,
+    /// stop right away and release everything held
+    Cancel,
+}
+
+#[derive(Debug)]
+struct Scheduled {
+    at: Instant,
+    id: u64,
+    action: InputAction,
+}
+
+/// inputs at the same instant go out in the order they were scheduled
+impl Ord for Scheduled {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.at, self.id).cmp(&(other.at, other.id))
+    }
+}
+
+impl PartialOrd for Scheduled {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Scheduled {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other).is_eq()
+    }
+}
+
+impl Eq for Scheduled {}
+
+/// Owns a device and sends the scheduled inputs to it from its own thread
+#[derive(Debug)]
+pub struct Scheduler<D> {
+    sender: Sender<Message>,
+    reports: Receiver<EventReport>,
+    thread: Option<JoinHandle<D>>,
+    next_id: u64,
+}
+
+impl<D: InputMiddlewareDeviceAction + Send + 'static> Scheduler<D> {
+    pub fn start(device: D) -> Self {
+        Self::start_with_spin(device, DEFAULT_SPIN)
+    }
+
+    /// Start with the time spent spinning before each instant, more spinning is more precise
+    /// and uses more cpu
+    pub fn start_with_spin(device: D, spin: Duration) -> Self {
+        let (sender, messages) = mpsc::channel();
+        let (report, reports) = mpsc::channel();
+        let thread = thread::Builder::new()
+            .name("input-scheduler".into())
+            .spawn(move || run(device, spin, &messages, &report))
+            .expect("failed to spawn the scheduler thread");
+        Self {
+            sender,
+            reports,
+            thread: Some(thread),
+            next_id: 0,
+        }
+    }
+
+    /// Send the action at `at`, returns the id of its report
+    pub fn schedule(&mut self, at: Instant, action: impl Into<InputAction>) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        let scheduled = Scheduled {
+            at,
+            id,
+            action: action.into(),
+        };
+        // the thread only stops with the scheduler
+        let _ = self.sender.send(Message::Schedule(scheduled));
+        id
+    }
+
+    pub fn schedule_all(
+        &mut self,
+        entries: impl IntoIterator<Item = (Instant, InputAction)>,
+    ) -> Vec<u64> {
+        entries
+            .into_iter()
+            .map(|(at, action)| self.schedule(at, action))
+            .collect()
+    }
+
+    /// The reports of the inputs sent since the last call
+    pub fn reports(&self) -> Vec<EventReport> {
+        self.reports.try_iter().collect()
+    }
+
+    /// Wait up to `timeout` for the next report
+    pub fn recv_report(&self, timeout: Duration) -> Option<EventReport> {
+        self.reports.recv_timeout(timeout).ok()
+    }
+
+    /// Wait until every scheduled input is sent and return the device with the reports
+    /// that were not taken yet
+    pub fn finish(mut self) -> (D, Vec<EventReport>) {
+        let device = self.stop(Message::Finish);
+        (device, self.reports())
+    }
+
+    /// Drop the inputs that are not sent yet, release everything held and return the device
+    pub fn cancel(mut self) -> D {
+        self.stop(Message::Cancel)
+    }
+
+    fn stop(&mut self, message: Message) -> D {
+        let _ = self.sender.send(message);
+        let thread = self
+            .thread
+            .take()
+            .expect("the scheduler is only stopped once");
+        thread
+            .join()
+            .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+    }
+}
+
+impl<D> Drop for Scheduler<D> {
+    /// Like [`cancel`](Scheduler::cancel)
+    fn drop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            let _ = self.sender.send(Message::Cancel);
+            let _ = thread.join();
+        }
+    }
+}
+
+fn run<D: InputMiddlewareDeviceAction>(
+    mut device: D,
+    spin: Duration,
+    messages: &Receiver<Message>,
+    report: &Sender<EventReport>,
+) -> D {
+    let mut queue = BinaryHeap::new();
+    let mut finishing = false;
+    loop {
+        let message = match queue.peek() {
+            Some(Reverse(Scheduled { at, .. })) => {
+                match at
+                    .saturating_duration_since(Instant::now())
+                    .checked_sub(spin)
+                {
+                    Some(sleep) if !sleep.is_zero() => match messages.recv_timeout(sleep) {
+                        Ok(message) => Some(message),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => Some(Message::Cancel),
+                    },
+                    _ => match messages.try_recv() {
+                        Ok(message) => Some(message),
+                        Err(TryRecvError::Empty) => None,
+                        Err(TryRecvError::Disconnected) => Some(Message::Cancel),
+                    },
+                }
+            }
+            None if finishing => return device,
+            None => Some(messages.recv().unwrap_or(Message::Cancel)),
+        };
+        match message {
+            Some(Message::Schedule(scheduled)) => queue.push(Reverse(scheduled)),
+            Some(Message::Finish) => finishing = true,
+            Some(Message::Cancel) => {
+                if let Err(e) = device.release_all() {
+                    error!("Failed to release held input when cancelling: {e}");
+                }
+                return device;
+            }
+            None => {}
+        }
+        while let Some(Reverse(next)) = queue.peek() {
+            if next.at.saturating_duration_since(Instant::now()) > spin {
+                break;
+            }
+            while Instant::now() < next.at {
+                std::hint::spin_loop();
+            }
+            let Some(Reverse(scheduled)) = queue.pop() else {
+                break;
+            };
+            let sent = Instant::now();
+            let result = scheduled.action.apply(&mut device);
+            let duration = sent.elapsed();
+            trace!(
+                "Scheduled input #{} sent {:?} late",
+                scheduled.id,
+                sent.saturating_duration_since(scheduled.at)
+            );
+            // nobody waiting for reports is fine
+            let _ = report.send(EventReport {
+                id: scheduled.id,
+                action: scheduled.action,
+                scheduled: scheduled.at,
+                sent,
+                duration,
+                result,
+            });
+        }
+    }
+}
@@ -0,0 +1,537 @@
+//! A small line based language to automate input without recompiling.
+//!
+//! One statement per line, `#` starts a comment:
+//!
+//! ```text
+//! let steps = 4
+//! repeat steps
+//!     move 10, -5          # relative move, the comma is optional
+//!     wait 50ms            # plain numbers are milliseconds, `s` is seconds
+//! end
+//! click left               # tap, or `click right down` / `click right up`
+//! key ctrl+shift+esc       # any chord, `key shift down` holds it
+//! wheel -3
+//! type "hello\n"
+//! let steps = steps * 2
+//! ```
+//!
+//! Numbers are integers, expressions support `+ - * /`, parentheses and variables.
+//! [`Script::dry_run`] runs a script against a [`RecorderDevice`] without waiting.
+
+use std::{collections::HashMap, thread, time::Duration};
+
+use crate::logging::debug;
+
+use crate::{
+    button_state::{ButtonState, MouseButton, MwheelState},
+    chord::Chord,
+    devices::recorder::RecorderDevice,
+    errors::InputMiddlewareScriptError,
+    input_macro::Macro,
+    typing::TypingOptions,
+    InputMiddlewareDeviceAction,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Number(i64),
+    Variable(String),
+    Neg(Box<Expr>),
+    Binary(Box<Expr>, char, Box<Expr>),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Tap,
+    Down,
+    Up,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Command {
+    Move(Expr, Expr),
+    Click(MouseButton, Action),
+    Key(Chord, Action),
+    Type(String),
+    /// milliseconds
+    Wait(Expr),
+    Wheel(Expr),
+    Let(String, Expr),
+    Repeat(Expr, Vec<Statement>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Statement {
+    line: usize,
+    command: Command,
+}
+
+/// A parsed script, see the [module docs](self) for the syntax
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    statements: Vec<Statement>,
+}
+
+fn syntax(line: usize, message: impl Into<String>) -> InputMiddlewareScriptError {
+    InputMiddlewareScriptError::Syntax {
+        line,
+        message: message.into(),
+    }
+}
+
+fn runtime(line: usize, message: impl Into<String>) -> InputMiddlewareScriptError {
+    InputMiddlewareScriptError::Runtime {
+        line,
+        message: message.into(),
+    }
+}
+
+/// Recursive descent parser for a single expression
+struct ExprParser<'a> {
+    chars: std::iter::Peekable<std::str::Chars<'a>>,
+    line: usize,
+}
+
+impl ExprParser<'_> {
+    fn parse(text: &str, line: usize) -> Result<Expr, InputMiddlewareScriptError> {
+        let mut parser = ExprParser {
+            chars: text.chars().peekable(),
+            line,
+        };
+        let expr = parser.sum()?;
+        parser.skip_whitespace();
+        match parser.chars.next() {
+            None => Ok(expr),
+            Some(c) => Err(syntax(line, format!("unexpected {c:?} in {text:?}"))),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+    }
+
+    fn sum(&mut self) -> Result<Expr, InputMiddlewareScriptError> {
+        let mut expr = self.product()?;
+        loop {
+            self.skip_whitespace();
+            match self.chars.next_if(|c| matches!(c, '+' | '-')) {
+                Some(op) => expr = Expr::Binary(Box::new(expr), op, Box::new(self.product()?)),
+                None => return Ok(expr),
+            }
+        }
+    }
+
+    fn product(&mut self) -> Result<Expr, InputMiddlewareScriptError> {
+        let mut expr = self.unary()?;
+        loop {
+            self.skip_whitespace();
+            match self.chars.next_if(|c| matches!(c, '*' | '/')) {
+                Some(op) => expr = Expr::Binary(Box::new(expr), op, Box::new(self.unary()?)),
+                None => return Ok(expr),
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<Expr, InputMiddlewareScriptError> {
+        self.skip_whitespace();
+        match self.chars.peek().copied() {
+            Some('-') => {
+                self.chars.next();
+                Ok(Expr::Neg(Box::new(self.unary()?)))
+            }
+            Some('(') => {
+                self.chars.next();
+                let expr = self.sum()?;
+                self.skip_whitespace();
+                match self.chars.next() {
+                    Some(')') => Ok(expr),
+                    _ => Err(syntax(self.line, "missing )")),
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut number = String::new();
+                while let Some(c) = self.chars.next_if(char::is_ascii_digit) {
+                    number.push(c);
+                }
+                number
+                    .parse()
+                    .map(Expr::Number)
+                    .map_err(|_| syntax(self.line, format!("number {number} is too large")))
+            }
+            Some(c) if c.is_alphabetic() || c == '_' => {
+                let mut name = String::new();
+                while let Some(c) = self.chars.next_if(|c| c.is_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                Ok(Expr::Variable(name))
+            }
+            Some(c) => Err(syntax(self.line, format!("unexpected {c:?}"))),
+            None => Err(syntax(self.line, "expected a number or variable")),
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_alphabetic() || c == '_')
+        && chars.all(|c| c.is_alphanumeric() || c == '_')
+}
+
+/// Parse a quoted string with `\n`, `\t`, `\"` and `\\` escapes
+fn parse_string(text: &str, line: usize) -> Result<String, InputMiddlewareScriptError> {
+    let inner = text
+        .strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .filter(|_| text.len() >= 2)
+        .ok_or_else(|| syntax(line, "expected a string in double quotes"))?;
+    let mut out = String::new();
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('n') => out.push('\n'),
+                Some('t') => out.push('\t'),
+                Some(c @ ('"' | '\\')) => out.push(c),
+                other => {
+                    return Err(syntax(
+                        line,
+                        format!("unknown escape \\{}", other.unwrap_or(' ')),
+                    ))
+                }
+            },
+            '"' => return Err(syntax(line, "unescaped \" in string")),
+            c => out.push(c),
+        }
+    }
+    Ok(out)
+}
+
+/// Strip a `#` comment that is not inside a string
+fn strip_comment(text: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &text[..i],
+            _ => {}
+        }
+    }
+    text
+}
+
+fn parse_action(word: Option<&str>, line: usize) -> Result<Action, InputMiddlewareScriptError> {
+    match word.map(str::to_ascii_lowercase).as_deref() {
+        None | Some("tap") => Ok(Action::Tap),
+        Some("down") => Ok(Action::Down),
+        Some("up") => Ok(Action::Up),
+        Some(other) => Err(syntax(line, format!("expected down or up, got {other:?}"))),
+    }
+}
+
+fn parse_button(word: &str, line: usize) -> Result<MouseButton, InputMiddlewareScriptError> {
+    match word.to_ascii_lowercase().as_str() {
+        "left" => Ok(MouseButton::Left),
+        "right" => Ok(MouseButton::Right),
+        "middle" => Ok(MouseButton::Middle),
+        "side1" => Ok(MouseButton::Side1),
+        "side2" => Ok(MouseButton::Side2),
+        other => Err(syntax(line, format!("unknown mouse button {other:?}"))),
+    }
+}
+
+/// A wait in milliseconds, `2s` is converted to milliseconds
+fn parse_wait(text: &str, line: usize) -> Result<Expr, InputMiddlewareScriptError> {
+    let ends_in_digit = |t: &str| t.trim_end().ends_with(|c: char| c.is_ascii_digit());
+    if let Some(ms) = text.strip_suffix("ms").filter(|t| ends_in_digit(t)) {
+        return ExprParser::parse(ms, line);
+    }
+    if let Some(s) = text.strip_suffix('s').filter(|t| ends_in_digit(t)) {
+        let seconds = ExprParser::parse(s, line)?;
+        return Ok(Expr::Binary(
+            Box::new(seconds),
+            '*',
+            Box::new(Expr::Number(1000)),
+        ));
+    }
+    ExprParser::parse(text, line)
+}
+
+fn parse_command(text: &str, line: usize) -> Result<Command, InputMiddlewareScriptError> {
+    let (word, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+    let rest = rest.trim();
+    let args: Vec<&str> = rest.split_whitespace().collect();
+    match word.to_ascii_lowercase().as_str() {
+        "move" => {
+            let parts: Vec<&str> = match rest.contains(',') {
+                true => rest.split(',').collect(),
+                false => args.clone(),
+            };
+            match parts.as_slice() {
+                [x, y] => Ok(Command::Move(
+                    ExprParser::parse(x, line)?,
+                    ExprParser::parse(y, line)?,
+                )),
+                _ => Err(syntax(line, "usage: move X, Y")),
+            }
+        }
+        "click" => match args.as_slice() {
+            [button] | [button, _] => Ok(Command::Click(
+                parse_button(button, line)?,
+                parse_action(args.get(1).copied(), line)?,
+            )),
+            _ => Err(syntax(line, "usage: click BUTTON [down|up]")),
+        },
+        "key" => match args.as_slice() {
+            [chord] | [chord, _] => Ok(Command::Key(
+                chord.parse().map_err(|e| syntax(line, format!("{e}")))?,
+                parse_action(args.get(1).copied(), line)?,
+            )),
+            _ => Err(syntax(line, "usage: key CHORD [down|up]")),
+        },
+        "type" => Ok(Command::Type(parse_string(rest, line)?)),
+        "wait" if !rest.is_empty() => Ok(Command::Wait(parse_wait(rest, line)?)),
+        "wait" => Err(syntax(line, "usage: wait MILLISECONDS")),
+        "wheel" if !rest.is_empty() => Ok(Command::Wheel(ExprParser::parse(rest, line)?)),
+        "wheel" => Err(syntax(line, "usage: wheel AMOUNT")),
+        "let" => {
+            let (name, value) = rest
+                .split_once('=')
+                .ok_or_else(|| syntax(line, "usage: let NAME = VALUE"))?;
+            let name = name.trim();
+            if !is_identifier(name) {
+                return Err(syntax(line, format!("invalid variable name {name:?}")));
+            }
+            Ok(Command::Let(
+                name.to_string(),
+                ExprParser::parse(value, line)?,
+            ))
+        }
+        other => Err(syntax(line, format!("unknown command {other:?}"))),
+    }
+}
+
+impl std::str::FromStr for Script {
+    type Err = InputMiddlewareScriptError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        Self::parse(source)
+    }
+}
+
+impl Script {
+    /// Parse the whole script, the first syntax error is returned with its line number
+    pub fn parse(source: &str) -> Result<Self, InputMiddlewareScriptError> {
+        // the statements of the open blocks, the outermost is the script itself
+        let mut blocks: Vec<(usize, Expr, Vec<Statement>)> = Vec::new();
+        let mut statements = Vec::new();
+        for (index, text) in source.lines().enumerate() {
+            let line = index + 1;
+            let text = strip_comment(text).trim();
+            if text.is_empty() {
+                continue;
+            }
+            let (word, rest) = text.split_once(char::is_whitespace).unwrap_or((text, ""));
+            match word.to_ascii_lowercase().as_str() {
+                "repeat" => {
+                    if rest.trim().is_empty() {
+                        return Err(syntax(line, "usage: repeat COUNT"));
+                    }
+                    let count = ExprParser::parse(rest, line)?;
+                    blocks.push((line, count, std::mem::take(&mut statements)));
+                }
+                "end" => {
+                    let (start, count, outer) = blocks
+                        .pop()
+                        .ok_or_else(|| syntax(line, "end without repeat"))?;
+                    let body = std::mem::replace(&mut statements, outer);
+                    statements.push(Statement {
+                        line: start,
+                        command: Command::Repeat(count, body),
+                    });
+                }
+                _ => statements.push(Statement {
+                    line,
+                    command: parse_command(text, line)?,
+                }),
+            }
+        }
+        if let Some((line, _, _)) = blocks.pop() {
+            return Err(syntax(line, "repeat without end"));
+        }
+        Ok(Self { statements })
+    }
+
+    /// Run the script on the device, waits sleep for real
+    pub fn run<D: InputMiddlewareDeviceAction + ?Sized>(
+        &self,
+        device: &mut D,
+        typing: &TypingOptions,
+    ) -> Result<(), InputMiddlewareScriptError> {
+        let mut runner = Runner {
+            variables: HashMap::new(),
+            typing,
+            wait: |_: &mut D, duration| thread::sleep(duration),
+        };
+        runner.block(device, &self.statements)
+    }
+
+    /// Run the script on a [`RecorderDevice`] without waiting or typing delays
+    /// and return what would have been sent, waits move the clock of the recorder
+    pub fn dry_run(&self) -> Result<Macro, InputMiddlewareScriptError> {
+        let mut device = RecorderDevice::new();
+        let typing = TypingOptions::default()
+            .set_key_hold(Duration::ZERO)
+            .set_key_delay(Duration::ZERO);
+        let mut runner = Runner {
+            variables: HashMap::new(),
+            typing: &typing,
+            wait: |device: &mut RecorderDevice, duration| device.advance(duration),
+        };
+        runner.block(&mut device, &self.statements)?;
+        Ok(device.into_macro())
+    }
+}
+
+struct Runner<'a, W> {
+    variables: HashMap<String, i64>,
+    typing: &'a TypingOptions,
+    /// how a wait is done, sleeping or moving a virtual clock
+    wait: W,
+}
+
+impl<W> Runner<'_, W> {
+    fn eval(&self, expr: &Expr, line: usize) -> Result<i64, InputMiddlewareScriptError> {
+        let overflow = || runtime(line, "number overflow");
+        match expr {
+            Expr::Number(n) => Ok(*n),
+            Expr::Variable(name) => self
+                .variables
+                .get(name)
+                .copied()
+                .ok_or_else(|| runtime(line, format!("unknown variable {name:?}"))),
+            Expr::Neg(expr) => self.eval(expr, line)?.checked_neg().ok_or_else(overflow),
+            Expr::Binary(left, op, right) => {
+                let (left, right) = (self.eval(left, line)?, self.eval(right, line)?);
+                match op {
+                    '+' => left.checked_add(right).ok_or_else(overflow),
+                    '-' => left.checked_sub(right).ok_or_else(overflow),
+                    '*' => left.checked_mul(right).ok_or_else(overflow),
+                    _ if right == 0 => Err(runtime(line, "division by zero")),
+                    _ => left.checked_div(right).ok_or_else(overflow),
+                }
+            }
+        }
+    }
+
+    fn eval_i32(&self, expr: &Expr, line: usize) -> Result<i32, InputMiddlewareScriptError> {
+        let value = self.eval(expr, line)?;
+        i32::try_from(value).map_err(|_| runtime(line, format!("{value} is out of range")))
+    }
+
+    fn count(&self, expr: &Expr, line: usize) -> Result<u64, InputMiddlewareScriptError> {
+        let count = self.eval(expr, line)?;
+        u64::try_from(count).map_err(|_| runtime(line, format!("repeat count {count} is negative")))
+    }
+
+    fn duration(&self, expr: &Expr, line: usize) -> Result<Duration, InputMiddlewareScriptError> {
+        let ms = self.eval(expr, line)?;
+        u64::try_from(ms)
+            .map(Duration::from_millis)
+            .map_err(|_| runtime(line, format!("wait of {ms}ms is negative")))
+    }
+
+    fn block<D>(
+        &mut self,
+        device: &mut D,
+        statements: &[Statement],
+    ) -> Result<(), InputMiddlewareScriptError>
+    where
+        D: InputMiddlewareDeviceAction + ?Sized,
+        W: FnMut(&mut D, Duration),
+    {
+        for statement in statements {
+            match &statement.command {
+                Command::Repeat(count, body) => {
+                    for _ in 0..self.count(count, statement.line)? {
+                        self.block(device, body)?;
+                    }
+                }
+                _ => self.statement(device, statement)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn statement<D>(
+        &mut self,
+        device: &mut D,
+        statement: &Statement,
+    ) -> Result<(), InputMiddlewareScriptError>
+    where
+        D: InputMiddlewareDeviceAction + ?Sized,
+        W: FnMut(&mut D, Duration),
+    {
+        let line = statement.line;
+        let send = |source| InputMiddlewareScriptError::Send { line, source };
+        debug!("Script line {line}: {:?}", statement.command);
+        match &statement.command {
+            Command::Move(x, y) => {
+                let pos = [self.eval_i32(x, line)?, self.eval_i32(y, line)?];
+                device.mouse_move(pos).map_err(send)
+            }
+            Command::Click(button, action) => {
+                let mut click = |state| match button {
+                    MouseButton::Left => device.mouse_left_click(state),
+                    MouseButton::Right => device.mouse_right_click(state),
+                    MouseButton::Middle => device.mouse_middle_click(state),
+                    MouseButton::Side1 => device.mouse_side1_click(state),
+                    MouseButton::Side2 => device.mouse_side2_click(state),
+                };
+                match action {
+                    Action::Down => click(ButtonState::Pressed),
+                    Action::Up => click(ButtonState::Released),
+                    Action::Tap => {
+                        click(ButtonState::Pressed).and_then(|_| click(ButtonState::Released))
+                    }
+                }
+                .map_err(send)
+            }
+            Command::Key(chord, action) => match action {
+                Action::Down => device.press_chord(chord),
+                Action::Up => device.release_chord(chord),
+                Action::Tap => device.tap_chord(chord),
+            }
+            .map_err(send),
+            Command::Type(text) => device
+                .type_text(text, self.typing)
+                .map(|_| ())
+                .map_err(|source| InputMiddlewareScriptError::Typing { line, source }),
+            Command::Wait(expr) => {
+                let duration = self.duration(expr, line)?;
+                (self.wait)(device, duration);
+                Ok(())
+            }
+            Command::Wheel(expr) => {
+                let amount = self.eval_i32(expr, line)?;
+                let state = match amount < 0 {
+                    true => MwheelState::Down(
+                        amount
+                            .checked_neg()
+                            .ok_or_else(|| runtime(line, format!("{amount} is out of range")))?,
+                    ),
+                    false => MwheelState::Up(amount),
+                };
+                device.mouse_wheel(state).map_err(send)
+            }
+            Command::Let(name, expr) => {
+                let value = self.eval(expr, line)?;
+                self.variables.insert(name.clone(), value);
+                Ok(())
+            }
+            Command::Repeat(..) => unreachable!("repeat is handled by the block"),
+        }
+    }
+}
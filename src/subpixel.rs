@@ -0,0 +1,205 @@
+//! Fractional mouse movement, see [`SubPixel`].
+
+use crate::{
+    button_state::{ButtonState, MwheelState},
+    capabilities::DeviceCapabilities,
+    errors::InputMiddlewareSendError,
+    keyboardkeys::{ConsumerKey, KeyboardKey},
+    InputMiddlewareDeviceAction,
+};
+
+/// Keeps the fractional part of relative mouse moves so smooth movement does not drift.
+/// Only whole counts are handed out, the rest is carried into the next move.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SubPixelAccumulator {
+    /// sensitivity the deltas are multiplied with before rounding, default is 1.0
+    pub scale: f64,
+    remainder: [f64; 2],
+}
+
+impl Default for SubPixelAccumulator {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            remainder: [0.0; 2],
+        }
+    }
+}
+
+impl SubPixelAccumulator {
+    pub fn new(scale: f64) -> Self {
+        Self {
+            scale,
+            ..Default::default()
+        }
+    }
+
+    /// The movement that was not sent yet
+    pub fn remainder(&self) -> [f64; 2] {
+        self.remainder
+    }
+
+    /// Add a scaled delta and take the whole counts, the fraction stays in the remainder.
+    /// Non finite deltas are ignored so they can not poison the remainder.
+    pub fn accumulate(&mut self, dx: f64, dy: f64) -> [i32; 2] {
+        let mut whole = [0; 2];
+        for (axis, delta) in [dx, dy].into_iter().enumerate() {
+            let total = self.remainder[axis] + delta * self.scale;
+            if !total.is_finite() {
+                continue;
+            }
+            let counts = total.trunc().clamp(i32::MIN.into(), i32::MAX.into());
+            self.remainder[axis] = total - counts;
+            whole[axis] = counts as i32;
+        }
+        whole
+    }
+
+    /// Round the remainder to whole counts and clear it
+    pub fn flush(&mut self) -> [i32; 2] {
+        let whole = self.remainder.map(|r| r.round() as i32);
+        self.reset();
+        whole
+    }
+
+    /// Drop the remainder without sending it
+    pub fn reset(&mut self) {
+        self.remainder = [0.0; 2];
+    }
+}
+
+/// Wraps a device so it can be moved by fractional deltas, the fraction is kept in its
+/// [`SubPixelAccumulator`]. Everything else is passed through unchanged.
+#[derive(Debug)]
+pub struct SubPixel<D> {
+    device: D,
+    accumulator: SubPixelAccumulator,
+}
+
+impl<D: InputMiddlewareDeviceAction> SubPixel<D> {
+    pub fn new(device: D) -> Self {
+        Self {
+            device,
+            accumulator: SubPixelAccumulator::default(),
+        }
+    }
+
+    /// Multiply the deltas of [`mouse_move_f`](Self::mouse_move_f) with `scale`, default is 1.0
+    pub fn set_scale(mut self, scale: f64) -> Self {
+        self.accumulator.scale = scale;
+        self
+    }
+
+    /// The fractional movement that was not sent yet
+    pub fn accumulator(&self) -> &SubPixelAccumulator {
+        &self.accumulator
+    }
+
+    /// Move the mouse by a fractional delta, the scale is applied first and only whole counts
+    /// are sent, the fraction is carried into the next move. The remainder is left as it was
+    /// when the move fails.
+    pub fn mouse_move_f(&mut self, dx: f64, dy: f64) -> Result<(), InputMiddlewareSendError> {
+        let mut accumulator = self.accumulator;
+        let pos = accumulator.accumulate(dx, dy);
+        self.send(pos, accumulator)
+    }
+
+    /// Send the rounded remainder of [`mouse_move_f`](Self::mouse_move_f), e.g. at the end of
+    /// a smoothed movement. The remainder is kept when the move fails.
+    pub fn mouse_move_flush(&mut self) -> Result<(), InputMiddlewareSendError> {
+        let mut accumulator = self.accumulator;
+        let pos = accumulator.flush();
+        self.send(pos, accumulator)
+    }
+
+    /// Drop the remainder of [`mouse_move_f`](Self::mouse_move_f) without sending it
+    pub fn mouse_move_reset(&mut self) {
+        self.accumulator.reset();
+    }
+
+    pub fn inner(&self) -> &D {
+        &self.device
+    }
+
+    pub fn inner_mut(&mut self) -> &mut D {
+        &mut self.device
+    }
+
+    /// The wrapped device, the remainder is dropped so
+    /// [`mouse_move_flush`](Self::mouse_move_flush) first
+    pub fn into_inner(self) -> D {
+        self.device
+    }
+
+    /// Send the whole counts and keep `accumulator` once they are sent
+    fn send(
+        &mut self,
+        pos: [i32; 2],
+        accumulator: SubPixelAccumulator,
+    ) -> Result<(), InputMiddlewareSendError> {
+        if pos != [0, 0] {
+            self.device.mouse_move(pos)?;
+        }
+        self.accumulator = accumulator;
+        Ok(())
+    }
+}
+
+impl<D: InputMiddlewareDeviceAction> InputMiddlewareDeviceAction for SubPixel<D> {
+    fn keyboard_keydown(&mut self, key: KeyboardKey) -> Result<(), InputMiddlewareSendError> {
+        self.device.keyboard_keydown(key)
+    }
+
+    fn keyboard_keyup(&mut self, key: KeyboardKey) -> Result<(), InputMiddlewareSendError> {
+        self.device.keyboard_keyup(key)
+    }
+
+    fn mouse_left_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.device.mouse_left_click(state)
+    }
+
+    fn mouse_right_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.device.mouse_right_click(state)
+    }
+
+    fn mouse_middle_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.device.mouse_middle_click(state)
+    }
+
+    fn mouse_side1_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.device.mouse_side1_click(state)
+    }
+
+    fn mouse_side2_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.device.mouse_side2_click(state)
+    }
+
+    fn mouse_wheel_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.device.mouse_wheel_click(state)
+    }
+
+    fn mouse_wheel(&mut self, state: MwheelState) -> Result<(), InputMiddlewareSendError> {
+        self.device.mouse_wheel(state)
+    }
+
+    /// Whole moves are sent right away and leave the remainder alone
+    fn mouse_move(&mut self, pos: [i32; 2]) -> Result<(), InputMiddlewareSendError> {
+        self.device.mouse_move(pos)
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.device.capabilities()
+    }
+
+    fn release_all(&mut self) -> Result<(), InputMiddlewareSendError> {
+        self.device.release_all()
+    }
+
+    fn consumer_key(
+        &mut self,
+        key: ConsumerKey,
+        state: ButtonState,
+    ) -> Result<(), InputMiddlewareSendError> {
+        self.device.consumer_key(key, state)
+    }
+}
@@ -0,0 +1,326 @@
+//! Rate limit the commands sent to a device, see [`Throttled`].
+
+use std::{
+    collections::VecDeque,
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    button_state::{ButtonState, MouseButton, MwheelState},
+    capabilities::{split_mouse_move, DeviceCapabilities},
+    errors::InputMiddlewareSendError,
+    input_macro::{self, InputAction, MacroEvent},
+    keyboardkeys::{ConsumerKey, KeyboardKey},
+    logging::error,
+    InputMiddlewareDeviceAction,
+};
+
+/// A command waiting for its turn
+#[derive(Debug, Clone, Copy)]
+enum Queued {
+    Event(MacroEvent),
+    Consumer(ConsumerKey, ButtonState),
+    ReleaseAll,
+}
+
+impl From<Queued> for InputAction {
+    fn from(queued: Queued) -> Self {
+        match queued {
+            Queued::Event(event) => InputAction::Event(event),
+            Queued::Consumer(key, state) => InputAction::Consumer {
+                key,
+                pressed: matches!(state, ButtonState::Pressed),
+            },
+            Queued::ReleaseAll => InputAction::ReleaseAll,
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Entry {
+    queued: Queued,
+    /// when the first call of a coalesced command was queued
+    since: Instant,
+}
+
+/// Sends the commands to the wrapped device at most `max_rate` times a second.
+///
+/// Commands over the rate are queued in order. A move or wheel delta is added to a move or
+/// wheel delta at the end of the queue, so a burst becomes one report per interval. Key and
+/// button transitions are never merged and never pass a move or each other.
+/// Queued commands go out with the next call, [`poll`](Self::poll) or [`flush`](Self::flush).
+/// Dropping it flushes the queue, if that fails everything held is released.
+#[derive(Debug)]
+pub struct Throttled<D: InputMiddlewareDeviceAction> {
+    /// only `None` once [`into_inner`](Self::into_inner) took it
+    device: Option<D>,
+    interval: Duration,
+    window: Duration,
+    queue: VecDeque<Entry>,
+    next_send: Instant,
+    peak_depth: usize,
+    coalesced: u64,
+}
+
+impl<D: InputMiddlewareDeviceAction> Throttled<D> {
+    /// Wrap the device, a `max_rate` that is not positive and finite does not limit
+    pub fn new(device: D, max_rate: f64) -> Self {
+        let interval = match max_rate.is_finite() && max_rate > 0.0 {
+            true => Duration::from_secs_f64(1.0 / max_rate),
+            false => Duration::ZERO,
+        };
+        Self {
+            device: Some(device),
+            interval,
+            window: Duration::ZERO,
+            queue: VecDeque::new(),
+            next_send: Instant::now(),
+            peak_depth: 0,
+            coalesced: 0,
+        }
+    }
+
+    /// Hold a move or wheel delta at the end of the queue this long so the following ones are
+    /// added to it even when the rate would allow sending it, default is none
+    pub fn set_coalesce_window(mut self, window: Duration) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Time between two commands
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// Commands waiting to be sent
+    pub fn queue_depth(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// The deepest the queue has been
+    pub fn peak_queue_depth(&self) -> usize {
+        self.peak_depth
+    }
+
+    /// Calls that were added to a queued move or wheel delta instead of sent on their own
+    pub fn coalesced(&self) -> u64 {
+        self.coalesced
+    }
+
+    /// When [`poll`](Self::poll) sends the next command, `None` if the queue is empty
+    pub fn next_due(&self) -> Option<Instant> {
+        self.due(false)
+    }
+
+    /// Send the commands that are due, returns how many were sent.
+    /// A command that fails is not retried.
+    pub fn poll(&mut self) -> Result<usize, InputMiddlewareSendError> {
+        let mut sent = 0;
+        while self.due(false).is_some_and(|due| due <= Instant::now()) {
+            self.send_front()?;
+            sent += 1;
+        }
+        Ok(sent)
+    }
+
+    /// Block until every queued command is sent, held moves are sent without waiting for the
+    /// coalesce window
+    pub fn flush(&mut self) -> Result<(), InputMiddlewareSendError> {
+        while let Some(due) = self.due(true) {
+            thread::sleep(due.saturating_duration_since(Instant::now()));
+            self.send_front()?;
+        }
+        Ok(())
+    }
+
+    pub fn inner(&self) -> &D {
+        self.device
+            .as_ref()
+            .expect("the device is only taken by into_inner")
+    }
+
+    pub fn inner_mut(&mut self) -> &mut D {
+        self.device
+            .as_mut()
+            .expect("the device is only taken by into_inner")
+    }
+
+    /// The wrapped device and the commands that were not sent yet, in order
+    pub fn into_inner(mut self) -> (D, Vec<InputAction>) {
+        let pending = self
+            .queue
+            .drain(..)
+            .map(|entry| entry.queued.into())
+            .collect();
+        let device = self
+            .device
+            .take()
+            .expect("the device is only taken by into_inner");
+        (device, pending)
+    }
+
+    fn due(&self, flushing: bool) -> Option<Instant> {
+        let front = self.queue.front()?;
+        let relative = matches!(
+            front.queued,
+            Queued::Event(MacroEvent::Move { .. } | MacroEvent::Wheel { .. })
+        );
+        // only the last command can still grow
+        let held = match relative && self.queue.len() == 1 && !flushing {
+            true => front.since + self.window,
+            false => front.since,
+        };
+        Some(held.max(self.next_send))
+    }
+
+    fn send_front(&mut self) -> Result<(), InputMiddlewareSendError> {
+        let Some(entry) = self.queue.pop_front() else {
+            return Ok(());
+        };
+        self.next_send = Instant::now() + self.interval;
+        match entry.queued {
+            Queued::Event(event) => input_macro::apply(self.inner_mut(), event),
+            Queued::Consumer(key, state) => self.inner_mut().consumer_key(key, state),
+            Queued::ReleaseAll => self.inner_mut().release_all(),
+        }
+    }
+
+    /// Queue the command or add it to the last queued one
+    fn enqueue(&mut self, queued: Queued) {
+        let limit = self.inner().capabilities().max_mouse_move;
+        if let Some(tail) = self.queue.back_mut() {
+            if let Some(merged) = coalesce(tail.queued, queued, limit) {
+                tail.queued = merged;
+                self.coalesced += 1;
+                return;
+            }
+        }
+        self.queue.push_back(Entry {
+            queued,
+            since: Instant::now(),
+        });
+        self.peak_depth = self.peak_depth.max(self.queue.len());
+    }
+
+    fn send(&mut self, queued: Queued) -> Result<(), InputMiddlewareSendError> {
+        self.enqueue(queued);
+        self.poll().map(|_| ())
+    }
+
+    fn button(
+        &mut self,
+        button: MouseButton,
+        state: ButtonState,
+    ) -> Result<(), InputMiddlewareSendError> {
+        let pressed = matches!(state, ButtonState::Pressed);
+        self.send(Queued::Event(MacroEvent::Button { button, pressed }))
+    }
+}
+
+/// The sum of two moves or two wheel deltas if it still fits in one report
+fn coalesce(queued: Queued, next: Queued, limit: i32) -> Option<Queued> {
+    let merged = match (queued, next) {
+        (
+            Queued::Event(MacroEvent::Move { x, y }),
+            Queued::Event(MacroEvent::Move { x: dx, y: dy }),
+        ) => {
+            let (x, y) = (x.checked_add(dx)?, y.checked_add(dy)?);
+            if x.abs() > limit || y.abs() > limit {
+                return None;
+            }
+            MacroEvent::Move { x, y }
+        }
+        (
+            Queued::Event(MacroEvent::Wheel { delta }),
+            Queued::Event(MacroEvent::Wheel { delta: next }),
+        ) => MacroEvent::Wheel {
+            delta: delta.checked_add(next)?,
+        },
+        _ => return None,
+    };
+    Some(Queued::Event(merged))
+}
+
+impl<D: InputMiddlewareDeviceAction> InputMiddlewareDeviceAction for Throttled<D> {
+    fn keyboard_keydown(&mut self, key: KeyboardKey) -> Result<(), InputMiddlewareSendError> {
+        self.send(Queued::Event(MacroEvent::Key { key, pressed: true }))
+    }
+
+    fn keyboard_keyup(&mut self, key: KeyboardKey) -> Result<(), InputMiddlewareSendError> {
+        self.send(Queued::Event(MacroEvent::Key {
+            key,
+            pressed: false,
+        }))
+    }
+
+    fn mouse_left_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Left, state)
+    }
+
+    fn mouse_right_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Right, state)
+    }
+
+    fn mouse_middle_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Middle, state)
+    }
+
+    fn mouse_side1_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Side1, state)
+    }
+
+    fn mouse_side2_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Side2, state)
+    }
+
+    fn mouse_wheel_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.button(MouseButton::Middle, state)
+    }
+
+    fn mouse_wheel(&mut self, state: MwheelState) -> Result<(), InputMiddlewareSendError> {
+        self.send(Queued::Event(MacroEvent::Wheel {
+            delta: state.into(),
+        }))
+    }
+
+    /// Split into reports of the device here so every report counts against the rate
+    fn mouse_move(&mut self, pos: [i32; 2]) -> Result<(), InputMiddlewareSendError> {
+        let limit = self.inner().capabilities().max_mouse_move;
+        for [x, y] in split_mouse_move(pos, limit) {
+            self.enqueue(Queued::Event(MacroEvent::Move { x, y }));
+        }
+        self.poll().map(|_| ())
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.inner().capabilities()
+    }
+
+    /// Queued like every other command so nothing queued before is pressed afterwards
+    fn release_all(&mut self) -> Result<(), InputMiddlewareSendError> {
+        self.send(Queued::ReleaseAll)
+    }
+
+    fn consumer_key(
+        &mut self,
+        key: ConsumerKey,
+        state: ButtonState,
+    ) -> Result<(), InputMiddlewareSendError> {
+        self.send(Queued::Consumer(key, state))
+    }
+}
+
+impl<D: InputMiddlewareDeviceAction> Drop for Throttled<D> {
+    fn drop(&mut self) {
+        if self.device.is_none() {
+            return;
+        }
+        if let Err(e) = self.flush() {
+            error!("Failed to flush the throttled commands: {e}");
+            if let Err(e) = self.inner_mut().release_all() {
+                error!("Failed to release held input after a failed flush: {e}");
+            }
+        }
+    }
+}
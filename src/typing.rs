@@ -0,0 +1,150 @@
+//! Type text through any [`InputMiddlewareDeviceAction`] by translating each character into
+//! key strokes on a [`KeyboardLayout`].
+
+use std::{sync::Arc, thread, time::Duration};
+
+use crate::logging::{debug, warn};
+use rand::Rng;
+
+use crate::{
+    errors::InputMiddlewareTypingError,
+    keyboard_layout::{KeyStroke, KeyboardLayout, UsLayout},
+    InputMiddlewareDeviceAction,
+};
+
+#[derive(Debug, Clone)]
+pub struct TypingOptions {
+    /// layout configured on the target PC, default is US
+    pub layout: Arc<dyn KeyboardLayout + Send + Sync>,
+    /// how long a key is held down
+    pub key_hold: Duration,
+    /// pause after a key is released before the next one is pressed
+    pub key_delay: Duration,
+    /// random extra time of up to this much is added to every hold and delay
+    pub jitter: Duration,
+    /// fail before sending anything if a character can not be typed on the layout,
+    /// otherwise those characters are skipped and reported
+    pub strict: bool,
+}
+
+impl Default for TypingOptions {
+    fn default() -> Self {
+        Self {
+            layout: Arc::new(UsLayout),
+            key_hold: Duration::from_millis(10),
+            key_delay: Duration::from_millis(20),
+            jitter: Duration::ZERO,
+            strict: false,
+        }
+    }
+}
+
+impl TypingOptions {
+    pub fn set_layout(mut self, layout: impl KeyboardLayout + Send + Sync + 'static) -> Self {
+        self.layout = Arc::new(layout);
+        self
+    }
+
+    pub fn set_key_hold(mut self, key_hold: Duration) -> Self {
+        self.key_hold = key_hold;
+        self
+    }
+
+    pub fn set_key_delay(mut self, key_delay: Duration) -> Self {
+        self.key_delay = key_delay;
+        self
+    }
+
+    pub fn set_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter;
+        self
+    }
+
+    pub fn set_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let jitter = match self.jitter.is_zero() {
+            true => Duration::ZERO,
+            false => rand::thread_rng().gen_range(Duration::ZERO..=self.jitter),
+        };
+        let duration = duration + jitter;
+        if !duration.is_zero() {
+            thread::sleep(duration);
+        }
+    }
+}
+
+/// What [`type_text`] did with the input
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TypingReport {
+    /// number of characters that were typed
+    pub typed: usize,
+    /// characters that can not be produced on the layout with their char index in the text
+    pub unmappable: Vec<(usize, char)>,
+}
+
+/// The key strokes for every character of `text`, `\r` is ignored so `\r\n` types one enter
+fn text_strokes(
+    text: &str,
+    layout: &dyn KeyboardLayout,
+) -> (Vec<Vec<KeyStroke>>, Vec<(usize, char)>) {
+    let mut strokes = Vec::new();
+    let mut unmappable = Vec::new();
+    for (index, c) in text.chars().enumerate().filter(|(_, c)| *c != '\r') {
+        match layout.strokes(c) {
+            Some(s) => strokes.push(s),
+            None => unmappable.push((index, c)),
+        }
+    }
+    (strokes, unmappable)
+}
+
+/// Type `text` on the device, see [`TypingOptions`] for the timing
+pub fn type_text<D: InputMiddlewareDeviceAction + ?Sized>(
+    device: &mut D,
+    text: &str,
+    options: &TypingOptions,
+) -> Result<TypingReport, InputMiddlewareTypingError> {
+    let (strokes, unmappable) = text_strokes(text, options.layout.as_ref());
+    if !unmappable.is_empty() {
+        if options.strict {
+            return Err(InputMiddlewareTypingError::Unmappable {
+                layout: options.layout.name().to_string(),
+                chars: unmappable,
+            });
+        }
+        warn!(
+            "Skipping characters not on the {} layout: {:?}",
+            options.layout.name(),
+            unmappable
+        );
+    }
+    debug!("Typing {} characters", strokes.len());
+    for char_strokes in &strokes {
+        for stroke in char_strokes {
+            tap_stroke(device, stroke, options)?;
+        }
+    }
+    Ok(TypingReport {
+        typed: strokes.len(),
+        unmappable,
+    })
+}
+
+/// Press the modifiers and the key, then release them in reverse order.
+/// The release is attempted even if a press failed so no key stays stuck.
+fn tap_stroke<D: InputMiddlewareDeviceAction + ?Sized>(
+    device: &mut D,
+    stroke: &KeyStroke,
+    options: &TypingOptions,
+) -> Result<(), InputMiddlewareTypingError> {
+    let chord = stroke.chord();
+    device.press_chord(&chord)?;
+    options.sleep(options.key_hold);
+    device.release_chord(&chord)?;
+    options.sleep(options.key_delay);
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use std::time::Duration;
+
+use input_middleware::{
+    capabilities::{split_mouse_move, MAX_MOUSE_MOVE},
+    devices::kmbox_net::{cmd::CMD, KMBoxNet, KMBoxNetConfig, KMBoxNetEmulator},
+    InputMiddlewareDeviceAction,
+};
+
+#[test]
+fn split_stays_within_limit_and_adds_up() {
+    for (pos, limit) in [
+        ([1000, -300], 127),
+        ([-70000, 5], MAX_MOUSE_MOVE),
+        ([127, 127], 127),
+        ([128, 0], 127),
+        ([i32::MAX, i32::MIN], MAX_MOUSE_MOVE),
+    ] {
+        let steps: Vec<_> = split_mouse_move(pos, limit).collect();
+        assert!(steps
+            .iter()
+            .all(|s| s[0].abs() <= limit && s[1].abs() <= limit));
+        let total = steps.iter().fold([0i64, 0], |t, s| {
+            [t[0] + i64::from(s[0]), t[1] + i64::from(s[1])]
+        });
+        assert_eq!(total, pos.map(i64::from), "{pos:?}");
+    }
+    assert_eq!(split_mouse_move([127, 0], 127).count(), 1);
+    assert_eq!(split_mouse_move([128, 0], 127).count(), 2);
+    assert_eq!(split_mouse_move([0, 0], 127).collect::<Vec<_>>(), [[0, 0]]);
+}
+
+#[test]
+fn kmbox_net_splits_large_moves() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let config = kmbox
+        .config()
+        .set_max_mouse_move(127)
+        .set_move_pacing(Duration::from_millis(1));
+    let mut km = KMBoxNet::new(config).unwrap();
+    assert_eq!(km.capabilities().max_mouse_move, 127);
+    km.mouse_move([300, -20]).unwrap();
+    let state = kmbox.state();
+    let cmds: Vec<_> = state.commands[1..].iter().map(|c| c.cmd).collect();
+    assert_eq!(cmds, [CMD::MOUSE_MOVE; 3]);
+    assert_eq!(state.position, [300, -20]);
+}
+
+#[test]
+fn max_mouse_move_is_validated() {
+    for limit in [0, -1, MAX_MOUSE_MOVE + 1] {
+        let err = KMBoxNetConfig::builder()
+            .uuid("ABCDEF12")
+            .max_mouse_move(limit)
+            .build()
+            .unwrap_err();
+        assert_eq!(err.field, "max_mouse_move");
+    }
+}
@@ -0,0 +1,96 @@
+use std::net::{Ipv4Addr, SocketAddrV4};
+use std::time::{Duration, UNIX_EPOCH};
+
+use input_middleware::button_state::ButtonState;
+use input_middleware::devices::kmbox_net::{
+    cmd::CMD, CaptureDirection, CaptureReader, CaptureWriter, KMBoxNet, KMBoxNetEmulator,
+};
+
+fn temp_capture(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("kmbox-{name}-{}.pcapng", std::process::id()))
+}
+
+#[test]
+fn writer_and_reader_round_trip() {
+    let local = SocketAddrV4::new(Ipv4Addr::new(192, 168, 2, 10), 50000);
+    let remote = SocketAddrV4::new(Ipv4Addr::new(192, 168, 2, 188), 16824);
+    let mut writer = CaptureWriter::new(Vec::new(), local, remote).unwrap();
+    let time = UNIX_EPOCH + Duration::from_micros(1_700_000_000_123_456);
+    writer
+        .write_packet(CaptureDirection::Sent, time, &[1, 2, 3])
+        .unwrap();
+    writer
+        .write_packet(CaptureDirection::Received, time, &[4; 17])
+        .unwrap();
+    let bytes = writer.into_inner();
+    assert_eq!(&bytes[..4], &[0x0a, 0x0d, 0x0d, 0x0a]);
+
+    let packets: Vec<_> = CaptureReader::new(bytes.as_slice())
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(packets.len(), 2);
+    assert_eq!(packets[0].direction, CaptureDirection::Sent);
+    assert_eq!(packets[0].data, vec![1, 2, 3]);
+    assert_eq!(packets[0].time, time);
+    assert_eq!(packets[1].direction, CaptureDirection::Received);
+    assert_eq!(packets[1].data, vec![4; 17]);
+}
+
+#[test]
+fn rejects_other_files() {
+    assert!(CaptureReader::new(&b"not a capture at all"[..]).is_err());
+}
+
+#[test]
+fn captures_sent_and_received_packets() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let path = temp_capture("capture");
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.start_capture(&path).unwrap();
+    km.mouse_move([10, -5]).unwrap();
+    km.stop_capture().unwrap();
+    km.mouse_move([1, 1]).unwrap();
+
+    let packets: Vec<_> = CaptureReader::open(&path)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(packets.len(), 2);
+    assert_eq!(packets[0].direction, CaptureDirection::Sent);
+    assert_eq!(packets[0].cmd(), Some(CMD::MOUSE_MOVE));
+    assert_eq!(packets[1].direction, CaptureDirection::Received);
+    assert_eq!(packets[0].data, packets[1].data);
+    let line = packets[0].to_string();
+    assert!(line.starts_with("> MOUSE_MOVE #1 "), "{line}");
+    assert!(line.ends_with("buttons=0x00 x=10 y=-5 wheel=0"), "{line}");
+}
+
+#[test]
+fn replay_resends_the_sent_packets() {
+    let recorded = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let path = temp_capture("replay");
+    let mut km = KMBoxNet::new(recorded.config()).unwrap();
+    km.start_capture(&path).unwrap();
+    km.mouse_left_click(ButtonState::Pressed).unwrap();
+    km.mouse_left_click(ButtonState::Released).unwrap();
+    let _reboot = km.reboot().unwrap();
+
+    let packets: Vec<_> = CaptureReader::open(&path)
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    // the packets carry the uuid they were recorded with
+    let target = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(target.config()).unwrap();
+    // the reboot is skipped
+    assert_eq!(km.replay_capture(packets, 10.0).unwrap(), 2);
+    let state = target.state();
+    let cmds: Vec<_> = state.commands.iter().map(|c| c.cmd).collect();
+    assert_eq!(cmds, [CMD::CONNECT, CMD::MOUSE_LEFT, CMD::MOUSE_LEFT]);
+    assert_eq!((state.reboots, state.rejected), (0, 0));
+    assert_eq!(state.buttons, 0x00);
+}
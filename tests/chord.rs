@@ -0,0 +1,75 @@
+mod common;
+
+use common::{events, held_keys, key, Failing};
+use input_middleware::chord::Chord;
+use input_middleware::devices::recorder::RecorderDevice;
+use input_middleware::errors::InputMiddlewareParseError;
+use input_middleware::keyboardkeys::KeyboardKey::*;
+use input_middleware::InputMiddlewareDeviceAction;
+
+#[test]
+fn parse_chords() {
+    let chord: Chord = "ctrl+shift+esc".parse().unwrap();
+    assert_eq!(chord.keys(), [KEY_LEFTCONTROL, KEY_LEFTSHIFT, KEY_ESCAPE]);
+    let chord: Chord = "F4 + Alt".parse().unwrap();
+    assert_eq!(chord.keys(), [KEY_LEFTALT, KEY_F4]);
+    assert_eq!(chord.to_string(), "alt+f4");
+}
+
+#[test]
+fn parse_errors() {
+    assert_eq!(
+        "ctrl+nope".parse::<Chord>(),
+        Err(InputMiddlewareParseError::UnknownKey("nope".into()))
+    );
+    assert_eq!(
+        "".parse::<Chord>(),
+        Err(InputMiddlewareParseError::EmptyChord)
+    );
+    assert!(matches!(
+        "ctrl+control".parse::<Chord>(),
+        Err(InputMiddlewareParseError::DuplicateKey(_))
+    ));
+    assert!(matches!(
+        "ctrl+".parse::<Chord>(),
+        Err(InputMiddlewareParseError::UnknownKey(_))
+    ));
+}
+
+#[test]
+fn tap_chord_order() {
+    let mut device = RecorderDevice::new();
+    device
+        .tap_chord(&"ctrl+shift+esc".parse().unwrap())
+        .unwrap();
+    assert_eq!(
+        events(&device),
+        vec![
+            key(KEY_LEFTCONTROL, true),
+            key(KEY_LEFTSHIFT, true),
+            key(KEY_ESCAPE, true),
+            key(KEY_ESCAPE, false),
+            key(KEY_LEFTSHIFT, false),
+            key(KEY_LEFTCONTROL, false),
+        ]
+    );
+}
+
+#[test]
+fn press_failure_releases_pressed_keys() {
+    let mut device = Failing::new(RecorderDevice::new(), 2);
+    assert!(device
+        .press_chord(&"ctrl+shift+esc".parse().unwrap())
+        .is_err());
+    assert!(held_keys(&device.device).is_empty());
+}
+
+#[test]
+fn release_continues_after_failure() {
+    let chord: Chord = "ctrl+shift+esc".parse().unwrap();
+    let mut device = Failing::<RecorderDevice>::default();
+    device.press_chord(&chord).unwrap();
+    device.fail_at = Some(device.calls);
+    assert!(device.release_chord(&chord).is_err());
+    assert_eq!(held_keys(&device.device), vec![KEY_ESCAPE]);
+}
@@ -0,0 +1,37 @@
+use input_middleware::devices::kmbox_net::{cmd::CMD, errors::KMBoxNetProtocolError};
+
+#[test]
+fn try_from_rejects_unknown_codes() {
+    assert_eq!(CMD::try_from(0xaf3c2828), Ok(CMD::CONNECT));
+    assert_eq!(
+        CMD::try_from(0xdeadbeef),
+        Err(KMBoxNetProtocolError::UnknownCommand(0xdeadbeef))
+    );
+}
+
+#[test]
+fn unknown_codes_are_kept() {
+    let cmd = CMD::from_code(0xdeadbeef);
+    assert_eq!(cmd, CMD::Unknown(0xdeadbeef));
+    assert_eq!(u32::from(cmd), 0xdeadbeef);
+    assert_eq!(cmd.to_string(), "UNKNOWN(0xdeadbeef)");
+    assert_eq!(cmd.name(), None);
+    assert_eq!("UNKNOWN(0xdeadbeef)".parse(), Ok(cmd));
+}
+
+#[test]
+fn names_round_trip() {
+    for cmd in CMD::ALL {
+        assert_eq!(CMD::from_code(u32::from(cmd)), cmd);
+        assert_eq!(cmd.to_string().parse(), Ok(cmd));
+    }
+    assert_eq!("mouse_move".parse(), Ok(CMD::MOUSE_MOVE));
+    // a raw code of a known command resolves to its name
+    assert_eq!("0xaede7345".parse(), Ok(CMD::MOUSE_MOVE));
+    assert_eq!(
+        "MOUSE_JUMP".parse::<CMD>(),
+        Err(KMBoxNetProtocolError::UnknownCommandName(
+            "MOUSE_JUMP".to_string()
+        ))
+    );
+}
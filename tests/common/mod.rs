@@ -0,0 +1,116 @@
+#![allow(dead_code)]
+
+use input_middleware::button_state::{ButtonState, MwheelState};
+use input_middleware::capabilities::DeviceCapabilities;
+use input_middleware::devices::recorder::RecorderDevice;
+use input_middleware::errors::InputMiddlewareSendError;
+use input_middleware::input_macro::MacroEvent;
+use input_middleware::keyboardkeys::KeyboardKey;
+use input_middleware::InputMiddlewareDeviceAction;
+
+/// The events a [`RecorderDevice`] recorded, without their times
+pub fn events(recorder: &RecorderDevice) -> Vec<MacroEvent> {
+    recorder.events().iter().map(|timed| timed.event).collect()
+}
+
+pub fn key(key: KeyboardKey, pressed: bool) -> MacroEvent {
+    MacroEvent::Key { key, pressed }
+}
+
+/// The keys that are still held after all recorded events
+pub fn held_keys(recorder: &RecorderDevice) -> Vec<KeyboardKey> {
+    let mut held = Vec::new();
+    for event in events(recorder) {
+        match event {
+            MacroEvent::Key { key, pressed: true } if !held.contains(&key) => held.push(key),
+            MacroEvent::Key {
+                key,
+                pressed: false,
+            } => held.retain(|k| *k != key),
+            _ => {}
+        }
+    }
+    held
+}
+
+/// Forwards to the wrapped device but fails the call number `fail_at` (counted from 0)
+#[derive(Debug, Default)]
+pub struct Failing<D> {
+    pub device: D,
+    pub fail_at: Option<usize>,
+    pub calls: usize,
+}
+
+impl<D> Failing<D> {
+    pub fn new(device: D, fail_at: usize) -> Self {
+        Self {
+            device,
+            fail_at: Some(fail_at),
+            calls: 0,
+        }
+    }
+
+    fn call(
+        &mut self,
+        f: impl FnOnce(&mut D) -> Result<(), InputMiddlewareSendError>,
+    ) -> Result<(), InputMiddlewareSendError> {
+        self.calls += 1;
+        if self.fail_at == Some(self.calls - 1) {
+            return Err(InputMiddlewareSendError(std::io::Error::new(
+                std::io::ErrorKind::TimedOut,
+                "injected device failure",
+            )));
+        }
+        f(&mut self.device)
+    }
+}
+
+impl<D: InputMiddlewareDeviceAction> InputMiddlewareDeviceAction for Failing<D> {
+    fn keyboard_keydown(&mut self, key: KeyboardKey) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.keyboard_keydown(key))
+    }
+
+    fn keyboard_keyup(&mut self, key: KeyboardKey) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.keyboard_keyup(key))
+    }
+
+    fn mouse_left_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.mouse_left_click(state))
+    }
+
+    fn mouse_right_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.mouse_right_click(state))
+    }
+
+    fn mouse_middle_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.mouse_middle_click(state))
+    }
+
+    fn mouse_side1_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.mouse_side1_click(state))
+    }
+
+    fn mouse_side2_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.mouse_side2_click(state))
+    }
+
+    fn mouse_wheel_click(&mut self, state: ButtonState) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.mouse_wheel_click(state))
+    }
+
+    fn mouse_wheel(&mut self, state: MwheelState) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.mouse_wheel(state))
+    }
+
+    fn mouse_move(&mut self, pos: [i32; 2]) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.mouse_move(pos))
+    }
+
+    fn capabilities(&self) -> DeviceCapabilities {
+        self.device.capabilities()
+    }
+
+    fn release_all(&mut self) -> Result<(), InputMiddlewareSendError> {
+        self.call(|device| device.release_all())
+    }
+}
@@ -0,0 +1,124 @@
+#![cfg(feature = "serde")]
+
+use std::time::Duration;
+
+use input_middleware::devices::kmbox_net::KMBoxNetConfig;
+use input_middleware::errors::InputMiddlewareConfigError;
+use input_middleware::InputDevice;
+use serial_test::serial;
+
+fn clear_env() {
+    for name in ["KMBOX_IP", "KMBOX_PORT", "KMBOX_UUID"] {
+        std::env::remove_var(name);
+    }
+}
+
+fn kmbox_config(device: InputDevice) -> KMBoxNetConfig {
+    match device {
+        InputDevice::KMBoxNet(config) => config,
+    }
+}
+
+#[test]
+fn parse_toml() {
+    let device = InputDevice::from_toml_str(
+        r#"
+        device = "kmbox_net"
+        ip = "10.0.0.2"
+        port = 1234
+        uuid = "ABCDEF12"
+        timeout = "250ms"
+        "#,
+    )
+    .unwrap();
+    let config = kmbox_config(device);
+    assert_eq!(config.ip, "10.0.0.2");
+    assert_eq!(config.port, 1234);
+    assert_eq!(config.uuid, "ABCDEF12");
+    assert_eq!(config.timeout, Duration::from_millis(250));
+}
+
+#[test]
+fn parse_json_with_defaults() {
+    let device =
+        InputDevice::from_json_str(r#"{ "device": "kmbox_net", "uuid": "ABCDEF12" }"#).unwrap();
+    let config = kmbox_config(device);
+    let default = KMBoxNetConfig::default();
+    assert_eq!(config.ip, default.ip);
+    assert_eq!(config.port, default.port);
+    assert_eq!(config.uuid, "ABCDEF12");
+    assert_eq!(config.timeout, default.timeout);
+}
+
+#[test]
+fn roundtrip_toml() {
+    let device = InputDevice::KMBoxNet(KMBoxNetConfig::new("10.0.0.3", 4321, "12345678"));
+    let content = toml::to_string(&device).unwrap();
+    let config = kmbox_config(InputDevice::from_toml_str(&content).unwrap());
+    assert_eq!(config.ip, "10.0.0.3");
+    assert_eq!(config.port, 4321);
+    assert_eq!(config.uuid, "12345678");
+}
+
+#[test]
+#[serial(env)]
+fn config_file_with_env_overrides() {
+    clear_env();
+    let path = std::env::temp_dir().join("input_middleware_config_test.json");
+    std::fs::write(
+        &path,
+        r#"{ "device": "kmbox_net", "ip": "10.0.0.2", "port": 1234, "uuid": "ABCDEF12" }"#,
+    )
+    .unwrap();
+    std::env::set_var("KMBOX_PORT", "4321");
+    std::env::set_var("KMBOX_UUID", "12345678");
+    let config = kmbox_config(InputDevice::from_config_file(&path).unwrap());
+    clear_env();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(config.ip, "10.0.0.2");
+    assert_eq!(config.port, 4321);
+    assert_eq!(config.uuid, "12345678");
+}
+
+#[test]
+#[serial(env)]
+fn invalid_env_port() {
+    clear_env();
+    std::env::set_var("KMBOX_PORT", "not a port");
+    let result = KMBoxNetConfig::default().with_env_overrides();
+    clear_env();
+    assert!(matches!(
+        result,
+        Err(InputMiddlewareConfigError::InvalidEnv {
+            name: "KMBOX_PORT",
+            ..
+        })
+    ));
+}
+
+#[test]
+fn unsupported_format() {
+    let path = std::env::temp_dir().join("input_middleware_config_test.yaml");
+    std::fs::write(&path, "device: kmbox_net").unwrap();
+    let result = InputDevice::from_config_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    assert!(matches!(
+        result,
+        Err(InputMiddlewareConfigError::UnsupportedFormat(_))
+    ));
+}
+
+#[test]
+#[serial(env)]
+fn parse_config_file_does_not_validate() {
+    clear_env();
+    let path = std::env::temp_dir().join("input_middleware_config_partial_test.toml");
+    std::fs::write(&path, "device = \"kmbox_net\"\nip = \"10.0.0.2\"\n").unwrap();
+    let parsed = InputDevice::parse_config_file(&path);
+    let loaded = InputDevice::from_config_file(&path);
+    std::fs::remove_file(&path).unwrap();
+    let mut config = kmbox_config(parsed.unwrap());
+    assert!(loaded.is_err());
+    config.uuid = "ABCDEF12".into();
+    assert!(config.validate().is_ok());
+}
@@ -0,0 +1,68 @@
+use input_middleware::devices::kmbox_net::crypto::{
+    decrypt_block, decrypt_packet, encrypt_block, encrypt_packet, is_encrypted, key_from_mac,
+    ENCRYPTED_PACKET_SIZE,
+};
+use input_middleware::devices::kmbox_net::{cmd::CMD, KMBoxNet, KMBoxNetEmulator};
+
+#[test]
+fn tea_reference_vector() {
+    // all zero key and plain text of the TEA reference implementation
+    assert_eq!(encrypt_block([0, 0], &[0; 4]), [0x41ea3a0a, 0x94baa940]);
+    assert_eq!(decrypt_block([0x41ea3a0a, 0x94baa940], &[0; 4]), [0, 0]);
+}
+
+#[test]
+fn key_is_the_uuid_in_big_endian() {
+    assert_eq!(key_from_mac(0x1234ABCD), [0xcdab3412, 0, 0, 0]);
+}
+
+fn mouse_move_packet() -> [u8; ENCRYPTED_PACKET_SIZE] {
+    let mut packet = [0u8; ENCRYPTED_PACKET_SIZE];
+    packet[0..4].copy_from_slice(&0x1234ABCDu32.to_le_bytes());
+    packet[8..12].copy_from_slice(&1u32.to_le_bytes());
+    packet[12..16].copy_from_slice(&u32::from(CMD::MOUSE_MOVE).to_le_bytes());
+    packet[20..24].copy_from_slice(&10i32.to_le_bytes());
+    packet[24..28].copy_from_slice(&(-5i32).to_le_bytes());
+    packet
+}
+
+/// Produced by this implementation to catch changes of the byte order or block layout, it is
+/// not a packet of the vendor SDK or a real kmbox
+#[test]
+fn packet_regression_vector() {
+    let key = key_from_mac(0x1234ABCD);
+    let mut packet = mouse_move_packet();
+    encrypt_packet(&mut packet, &key);
+    assert_eq!(
+        packet[..32],
+        [
+            0x47, 0x18, 0x95, 0x4d, 0x14, 0x7e, 0xb7, 0x8f, 0xf2, 0xad, 0x1d, 0x38, 0xa3, 0x7a,
+            0x8d, 0x67, 0x49, 0x61, 0x4d, 0xb7, 0xe7, 0x03, 0x50, 0xb1, 0x06, 0x04, 0x00, 0x75,
+            0xd5, 0x16, 0x4a, 0x5e,
+        ]
+    );
+    decrypt_packet(&mut packet, &key);
+    assert_eq!(packet, mouse_move_packet());
+}
+
+#[test]
+fn encrypted_replies_are_accepted() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    kmbox.set_encrypted_replies(true);
+    let mut km = KMBoxNet::new(kmbox.config().set_encrypted(true)).unwrap();
+    km.mouse_move([10, -5]).unwrap();
+    let state = kmbox.state();
+    assert_eq!(state.commands[1].cmd, CMD::MOUSE_MOVE);
+    assert!(state.commands[1].encrypted);
+    assert_eq!(state.position, [10, -5]);
+    let moves = &km.stats().commands[&CMD::MOUSE_MOVE];
+    assert_eq!((moves.ok, moves.mismatches), (1, 0));
+}
+
+#[test]
+fn only_input_is_encrypted() {
+    assert!(is_encrypted(CMD::MOUSE_MOVE));
+    assert!(is_encrypted(CMD::KEYBOARD_ALL));
+    assert!(!is_encrypted(CMD::CONNECT));
+    assert!(!is_encrypted(CMD::MASK_MOUSE));
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use common::events;
+use input_middleware::cursor::{Acceleration, CursorModel};
+use input_middleware::devices::recorder::RecorderDevice;
+use input_middleware::input_macro::MacroEvent;
+
+fn moves(device: &RecorderDevice) -> Vec<[i32; 2]> {
+    events(device)
+        .into_iter()
+        .filter_map(|e| match e {
+            MacroEvent::Move { x, y } => Some([x, y]),
+            _ => None,
+        })
+        .collect()
+}
+
+#[test]
+fn move_to_without_acceleration() {
+    let mut device = RecorderDevice::new();
+    let mut cursor = CursorModel::new(1920, 1080).set_speed(2.0);
+    assert_eq!(cursor.position(), [960.0, 540.0]);
+    let position = cursor.move_to(&mut device, 100.0, 1000.0).unwrap();
+    assert_eq!(position, [100.0, 1000.0]);
+    assert_eq!(moves(&device), [[-430, 230]]);
+}
+
+#[test]
+fn target_is_clamped_to_screen() {
+    let mut device = RecorderDevice::new();
+    let mut cursor = CursorModel::new(800, 600);
+    cursor.resync(10.0, 10.0);
+    let position = cursor.move_to(&mut device, -50.0, 5000.0).unwrap();
+    assert_eq!(position, [0.0, 599.0]);
+}
+
+#[test]
+fn acceleration_is_inverted() {
+    let acceleration = Acceleration::Curve(vec![[0.0, 0.5], [10.0, 1.0], [40.0, 2.0]]);
+    assert_eq!(acceleration.gain(5.0), 0.75);
+    assert_eq!(acceleration.gain(100.0), 2.0);
+    let cursor = CursorModel::new(1920, 1080).set_acceleration(acceleration);
+    let counts = cursor.pixels_to_counts([300.0, 0.0]);
+    assert_eq!(counts, [150, 0]);
+    assert_eq!(cursor.counts_to_pixels(counts), [300.0, 0.0]);
+}
+
+#[test]
+fn resync_corrects_the_estimate() {
+    let mut device = RecorderDevice::new();
+    let mut cursor = CursorModel::new(1920, 1080).set_tolerance(1.0);
+    cursor.resync(0.0, 0.0);
+    // the host moves the cursor 20% less than the model expects
+    let mut real = 0.0;
+    let position = cursor
+        .move_to_with(&mut device, 500.0, 0.0, || {
+            real += (500.0 - real) * 0.8;
+            Some([real, 0.0])
+        })
+        .unwrap();
+    assert!((position[0] - 500.0).abs() <= 1.0, "{position:?}");
+    let sent = moves(&device);
+    assert!(sent.len() > 1 && sent.len() <= 8);
+    assert_eq!(sent[0], [500, 0]);
+    assert_eq!(sent[1], [100, 0]);
+}
@@ -0,0 +1,45 @@
+use std::net::{SocketAddr, UdpSocket};
+
+use input_middleware::devices::kmbox_net::{
+    KMBoxNet, KMBoxNetConfig, KMBoxNetDebugLog, KMBoxNetEmulator, KMBoxNetSocketOptions,
+};
+
+fn localhost() -> SocketAddr {
+    "127.0.0.1:0".parse().unwrap()
+}
+
+#[test]
+fn default_debug_addr() {
+    let config = KMBoxNetConfig::default_with_uuid("ABCDEF12");
+    let socket_addr = config.socket_addr().unwrap();
+    assert_eq!(
+        config.debug_addr(socket_addr).unwrap(),
+        "0.0.0.0:16826".parse::<SocketAddr>().unwrap()
+    );
+}
+
+#[test]
+fn splits_datagrams_into_lines() {
+    let mut log = KMBoxNetDebugLog::bind(localhost(), &KMBoxNetSocketOptions::default()).unwrap();
+    let sender = UdpSocket::bind(localhost()).unwrap();
+    sender
+        .send_to(b"usb attached\r\n\r\nmouse 046d:c077\n\0", log.local_addr())
+        .unwrap();
+    assert_eq!(log.recv_line().unwrap(), "usb attached");
+    assert_eq!(log.next().unwrap().unwrap(), "mouse 046d:c077");
+}
+
+#[test]
+fn emulator_streams_the_log() {
+    let emulator = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(emulator.config().set_debug_addr(localhost())).unwrap();
+    let mut log = km.debug_log().unwrap();
+    assert_eq!(emulator.state().debug_port, Some(log.local_addr().port()));
+
+    km.mouse_move([1, 2]).unwrap();
+    assert!(log.recv_line().unwrap().starts_with("DEBUG #"));
+    assert!(log.recv_line().unwrap().starts_with("MOUSE_MOVE #"));
+
+    drop(km);
+    assert_eq!(emulator.state().debug_port, None);
+}
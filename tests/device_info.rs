@@ -0,0 +1,23 @@
+use input_middleware::devices::kmbox_net::{KMBoxNet, KMBoxNetEmulator};
+
+#[test]
+fn emulator_reports_its_uuid() {
+    let emulator = KMBoxNetEmulator::start("1234ABCD").unwrap();
+    let mut km = KMBoxNet::new(emulator.config().set_encrypted(true)).unwrap();
+    let info = km.device_info().unwrap();
+    assert_eq!(info.uuid, "1234ABCD");
+    assert_eq!(info.mac, 0x1234ABCD);
+    assert_eq!(info.addr, emulator.addr());
+    assert_eq!(info.monitor_addr.port(), emulator.addr().port() + 1);
+    assert!(info.encrypted);
+    assert!(info.to_string().contains("uuid       1234ABCD"));
+}
+
+#[test]
+fn echoed_reply_is_enough() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    let info = km.device_info().unwrap();
+    assert_eq!(info.uuid, "0000ABCD");
+    assert!(info.local_addr.is_some());
+}
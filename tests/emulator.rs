@@ -0,0 +1,65 @@
+use input_middleware::button_state::ButtonState;
+use input_middleware::devices::kmbox_net::{cmd::CMD, KMBoxNet, KMBoxNetEmulator, KMBoxNetMask};
+use input_middleware::keyboardkeys::KeyboardKey;
+
+#[test]
+fn plain_commands_change_the_state() {
+    let emulator = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(emulator.config()).unwrap();
+    km.mouse_move([10, -5]).unwrap();
+    km.mouse_move([1, 1]).unwrap();
+    km.mouse_left_click(ButtonState::Pressed).unwrap();
+    km.keyboard_keydown(KeyboardKey::KEY_A).unwrap();
+    km.mask_mouse(KMBoxNetMask::X).unwrap();
+
+    let state = emulator.state();
+    assert_eq!(state.position, [11, -4]);
+    assert_eq!(state.buttons, 0x01);
+    assert_eq!(state.keys[0], 0x04);
+    assert_eq!(state.mask, u32::from(KMBoxNetMask::X.bits()));
+    assert!(state.commands.iter().all(|command| !command.encrypted));
+
+    drop(km);
+    let state = emulator.state();
+    assert_eq!(state.buttons, 0);
+    assert_eq!(state.keys, [0; 10]);
+    assert_eq!(state.mask, 0);
+}
+
+#[test]
+fn encrypted_channel() {
+    let emulator = KMBoxNetEmulator::start("1234ABCD").unwrap();
+    let mut km = KMBoxNet::new(emulator.config().set_encrypted(true)).unwrap();
+    km.mouse_move([3, 4]).unwrap();
+    km.mouse_right_click(ButtonState::Pressed).unwrap();
+    km.unmask_all().unwrap();
+
+    let state = emulator.state();
+    assert_eq!(state.position, [3, 4]);
+    assert_eq!(state.buttons, 0x02);
+    let channels: Vec<_> = state
+        .commands
+        .iter()
+        .map(|command| (command.cmd, command.encrypted))
+        .collect();
+    assert_eq!(
+        channels,
+        [
+            (CMD::CONNECT, false),
+            (CMD::MOUSE_MOVE, true),
+            (CMD::MOUSE_RIGHT, true),
+            (CMD::UNMASK_ALL, false),
+        ]
+    );
+    assert_eq!(km.stats().total().ok, 3);
+}
+
+#[test]
+fn wrong_uuid_is_not_answered() {
+    let emulator = KMBoxNetEmulator::start("1234ABCD").unwrap();
+    let mut config = emulator.config();
+    config.uuid = "0000ABCD".into();
+    config.timeout = std::time::Duration::from_millis(50);
+    assert!(KMBoxNet::new(config).is_err());
+    assert_eq!(emulator.state().rejected, 1);
+}
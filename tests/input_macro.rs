@@ -0,0 +1,273 @@
+mod common;
+
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use common::{events, key};
+use input_middleware::{
+    button_state::MouseButton,
+    devices::{
+        kmbox_net::{KMBoxNet, KMBoxNetEmulator, MonitorData},
+        recorder::RecorderDevice,
+    },
+    errors::InputMiddlewareMacroError,
+    input_macro::{
+        Macro, MacroEvent, MacroPlayer, MacroRecorder, Playback, TimedEvent, MACRO_FORMAT_VERSION,
+    },
+    keyboardkeys::KeyboardKey,
+};
+
+fn sample() -> Macro {
+    let event = |ms, event| TimedEvent {
+        at: Duration::from_millis(ms),
+        event,
+    };
+    Macro {
+        version: MACRO_FORMAT_VERSION,
+        events: vec![
+            event(0, MacroEvent::Move { x: -300, y: 12 }),
+            event(
+                5,
+                MacroEvent::Button {
+                    button: MouseButton::Right,
+                    pressed: true,
+                },
+            ),
+            event(5, MacroEvent::Wheel { delta: -3 }),
+            event(
+                40,
+                MacroEvent::Key {
+                    key: KeyboardKey::KEY_LEFTSHIFT,
+                    pressed: true,
+                },
+            ),
+            event(
+                41,
+                MacroEvent::Key {
+                    key: KeyboardKey::KEY_LEFTSHIFT,
+                    pressed: false,
+                },
+            ),
+            event(
+                50,
+                MacroEvent::Button {
+                    button: MouseButton::Right,
+                    pressed: false,
+                },
+            ),
+        ],
+    }
+}
+
+#[test]
+fn binary_round_trip() {
+    let input_macro = sample();
+    let bytes = input_macro.to_bytes();
+    assert_eq!(&bytes[..4], b"IMMC");
+    assert_eq!(Macro::from_bytes(&bytes).unwrap(), input_macro);
+    assert!(bytes.len() < 40, "{} bytes", bytes.len());
+}
+
+#[test]
+fn binary_rejects_bad_input() {
+    let bytes = sample().to_bytes();
+    assert!(matches!(
+        Macro::from_bytes(b"nope"),
+        Err(InputMiddlewareMacroError::InvalidMagic)
+    ));
+    let mut newer = bytes.clone();
+    newer[4] = 99;
+    assert!(matches!(
+        Macro::from_bytes(&newer),
+        Err(InputMiddlewareMacroError::UnsupportedVersion(99))
+    ));
+    assert!(matches!(
+        Macro::from_bytes(&bytes[..bytes.len() - 1]),
+        Err(InputMiddlewareMacroError::InvalidEvent { index: 5, .. })
+    ));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn json_round_trip() {
+    let input_macro = sample();
+    let json = input_macro.to_json().unwrap();
+    assert!(json.contains(r#""type": "key""#), "{json}");
+    assert!(json.contains(r#""key": "KEY_LEFTSHIFT""#), "{json}");
+    assert!(json.contains(r#""at_us": 40000"#), "{json}");
+    assert_eq!(Macro::from_json(&json).unwrap(), input_macro);
+
+    let path = std::env::temp_dir().join(format!("macro-{}.json", std::process::id()));
+    input_macro.save(&path).unwrap();
+    assert_eq!(Macro::load(&path).unwrap(), input_macro);
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn recorder_diffs_monitor_reports() {
+    let mut recorder = MacroRecorder::new();
+    let mut data = MonitorData::default();
+    data.mouse.buttons = 0x01;
+    data.mouse.x = 4;
+    data.mouse.y = -2;
+    recorder.record_at(Duration::from_millis(1), &data);
+    data.mouse.x = 0;
+    data.mouse.y = 0;
+    data.keyboard.buttons = 0x02;
+    data.keyboard.data[0] = KeyboardKey::KEY_A.hid_usage();
+    recorder.record_at(Duration::from_millis(2), &data);
+    data.mouse.buttons = 0;
+    data.keyboard.buttons = 0;
+    data.keyboard.data[0] = 0;
+    recorder.record_at(Duration::from_millis(3), &data);
+
+    let events: Vec<_> = recorder.finish().events.iter().map(|e| e.event).collect();
+    let key = |key, pressed| MacroEvent::Key { key, pressed };
+    let left = |pressed| MacroEvent::Button {
+        button: MouseButton::Left,
+        pressed,
+    };
+    assert_eq!(
+        events,
+        vec![
+            left(true),
+            MacroEvent::Move { x: 4, y: -2 },
+            key(KeyboardKey::KEY_LEFTSHIFT, true),
+            key(KeyboardKey::KEY_A, true),
+            left(false),
+            key(KeyboardKey::KEY_LEFTSHIFT, false),
+            key(KeyboardKey::KEY_A, false),
+        ]
+    );
+}
+
+#[test]
+fn player_scales_speed_and_loops() {
+    let mut device = RecorderDevice::new();
+    let begin = Instant::now();
+    let playback = MacroPlayer::default()
+        .set_speed(2.0)
+        .set_loops(2)
+        .play(&mut device, &sample())
+        .unwrap();
+    let elapsed = begin.elapsed();
+    assert_eq!(playback, Playback::Finished);
+    assert!(elapsed >= Duration::from_millis(50), "{elapsed:?}");
+    let events = events(&device);
+    assert_eq!(events.len(), 12);
+    assert_eq!(events[0], MacroEvent::Move { x: -300, y: 12 });
+    assert_eq!(events[2], MacroEvent::Wheel { delta: -3 });
+}
+
+#[test]
+fn player_can_be_cancelled() {
+    let input_macro = Macro {
+        events: vec![
+            TimedEvent {
+                at: Duration::ZERO,
+                event: MacroEvent::Key {
+                    key: KeyboardKey::KEY_W,
+                    pressed: true,
+                },
+            },
+            TimedEvent {
+                at: Duration::from_secs(10),
+                event: MacroEvent::Key {
+                    key: KeyboardKey::KEY_W,
+                    pressed: false,
+                },
+            },
+        ],
+        ..Default::default()
+    };
+    let player = MacroPlayer::default().set_loops(0);
+    let cancel = player.cancel_token();
+    let handle = thread::spawn(move || {
+        let mut device = RecorderDevice::new();
+        let playback = player.play(&mut device, &input_macro).unwrap();
+        (playback, device)
+    });
+    thread::sleep(Duration::from_millis(50));
+    cancel.cancel();
+    let (playback, device) = handle.join().unwrap();
+    assert_eq!(playback, Playback::Cancelled);
+    // the release of the held key comes from the cancelled player
+    assert_eq!(
+        events(&device),
+        vec![
+            key(KeyboardKey::KEY_W, true),
+            key(KeyboardKey::KEY_W, false)
+        ]
+    );
+}
+
+#[test]
+fn player_finishes_empty_macro_right_away() {
+    let mut device = RecorderDevice::new();
+    let playback = MacroPlayer::default()
+        .set_loops(0)
+        .play(&mut device, &Macro::default())
+        .unwrap();
+    assert_eq!(playback, Playback::Finished);
+    assert!(device.events().is_empty());
+}
+
+#[test]
+fn player_rejects_wheel_delta_out_of_range() {
+    let input_macro = Macro {
+        events: vec![TimedEvent {
+            at: Duration::ZERO,
+            event: MacroEvent::Wheel { delta: i32::MIN },
+        }],
+        ..Default::default()
+    };
+    let mut device = RecorderDevice::new();
+    assert!(MacroPlayer::default()
+        .play(&mut device, &input_macro)
+        .is_err());
+    assert!(device.events().is_empty());
+}
+
+#[test]
+fn player_rejects_event_time_out_of_range() {
+    // as read from a corrupt file, the time is stored in microseconds
+    let bytes = Macro {
+        events: vec![TimedEvent {
+            at: Duration::from_micros(u64::MAX),
+            event: MacroEvent::Wheel { delta: 1 },
+        }],
+        ..Default::default()
+    }
+    .to_bytes();
+    let input_macro = Macro::from_bytes(&bytes).unwrap();
+    let mut device = RecorderDevice::new();
+    assert!(MacroPlayer::default()
+        .set_speed(1e-9)
+        .play(&mut device, &input_macro)
+        .is_err());
+    assert!(device.events().is_empty());
+}
+
+#[test]
+fn player_clicks_side_buttons_on_the_emulator() {
+    let emulator = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(emulator.config()).unwrap();
+    let press = |button| TimedEvent {
+        at: Duration::ZERO,
+        event: MacroEvent::Button {
+            button,
+            pressed: true,
+        },
+    };
+    let input_macro = Macro {
+        events: vec![press(MouseButton::Side1), press(MouseButton::Side2)],
+        ..Default::default()
+    };
+    let playback = MacroPlayer::default().play(&mut km, &input_macro).unwrap();
+    assert_eq!(playback, Playback::Finished);
+    assert_eq!(emulator.state().buttons, 0x18);
+    km.release_all().unwrap();
+    assert_eq!(emulator.state().buttons, 0);
+}
@@ -0,0 +1,56 @@
+use input_middleware::errors::InputMiddlewareParseError;
+use input_middleware::keyboardkeys::{ConsumerKey, KeyboardKey};
+
+#[test]
+fn hid_usage_roundtrip() {
+    let keys: Vec<_> = KeyboardKey::all().collect();
+    assert_eq!(keys.len(), 219);
+    for key in keys {
+        assert_eq!(KeyboardKey::try_from(key.hid_usage()), Ok(key));
+        assert_eq!(key.hid_usage(), key as u8);
+    }
+    assert_eq!(KeyboardKey::KEY_KEYPAD_00.hid_usage(), 0xB0);
+    assert_eq!(KeyboardKey::KEY_RIGHT_GUI.hid_usage(), 0xE7);
+    assert_eq!(
+        KeyboardKey::try_from(0xA5),
+        Err(InputMiddlewareParseError::UnknownKeyCode(0xA5))
+    );
+}
+
+#[test]
+fn names() {
+    for key in KeyboardKey::all() {
+        assert_eq!(key.to_string().parse::<KeyboardKey>(), Ok(key));
+    }
+    assert_eq!("key_a".parse(), Ok(KeyboardKey::KEY_A));
+    assert_eq!("escape".parse(), Ok(KeyboardKey::KEY_ESCAPE));
+    assert_eq!("Esc".parse(), Ok(KeyboardKey::KEY_ESCAPE));
+    assert_eq!(KeyboardKey::KEY_F4.to_string(), "KEY_F4");
+    assert_eq!(KeyboardKey::KEY_LEFTCONTROL.short_name(), Some("ctrl"));
+    assert!("nope".parse::<KeyboardKey>().is_err());
+}
+
+#[test]
+fn modifiers() {
+    let modifiers: Vec<_> = KeyboardKey::all().filter(|k| k.is_modifier()).collect();
+    assert_eq!(modifiers.len(), 8);
+    assert!(modifiers
+        .iter()
+        .all(|k| (0xE0..=0xE7).contains(&k.hid_usage())));
+}
+
+#[test]
+fn consumer_keys() {
+    assert_eq!(ConsumerKey::MEDIA_PLAY_PAUSE.hid_usage(), 0xCD);
+    assert_eq!(
+        ConsumerKey::try_from(0xE9),
+        Ok(ConsumerKey::MEDIA_VOLUME_UP)
+    );
+    assert_eq!(
+        "media_volume_down".parse(),
+        Ok(ConsumerKey::MEDIA_VOLUME_DOWN)
+    );
+    for key in ConsumerKey::all() {
+        assert_eq!(key.to_string().parse::<ConsumerKey>(), Ok(key));
+    }
+}
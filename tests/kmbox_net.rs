@@ -26,7 +26,8 @@ mod parallel_tests {
     fn connect_fail_wrong_uuid() {
         let km = KMBoxNet::new(KMBoxNetConfig::default_with_uuid("XXXXXXXX"));
         if let Err(e) = km {
-            assert!(e.to_string().contains("10060"))
+            assert_eq!(e.0.kind(), std::io::ErrorKind::InvalidInput);
+            assert!(e.to_string().contains("uuid"))
         }
     }
 
@@ -133,8 +134,8 @@ mod serial_test {
     fn reboot() {
         let km = KMBoxNet::new(KMBoxNetConfig::default_with_uuid(UUID));
         match km {
-            Ok(mut km) => {
-                km.reboot().unwrap();
+            Ok(km) => {
+                km.reboot().unwrap().wait().unwrap();
             }
             Err(e) => super::connection_fail_assert(e.0),
         }
@@ -0,0 +1,156 @@
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use input_middleware::devices::kmbox_net::{
+    KMBoxNet, KMBoxNetConfig, KMBoxNetMonitor, KMBoxNetSocketOptions,
+};
+
+#[test]
+fn builder_valid() {
+    let config = KMBoxNetConfig::builder()
+        .ip("10.0.0.2")
+        .port(1234)
+        .uuid("abcdef12")
+        .timeout(Duration::from_millis(500))
+        .local_addr("0.0.0.0:0".parse().unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(config.mac().unwrap(), 0xabcdef12);
+    assert_eq!(
+        config.socket_addr().unwrap(),
+        "10.0.0.2:1234".parse::<SocketAddr>().unwrap()
+    );
+}
+
+#[test]
+fn builder_resolves_hostname() {
+    let config = KMBoxNetConfig::builder()
+        .ip("localhost")
+        .uuid("ABCDEF12")
+        .build()
+        .unwrap();
+    assert!(config.socket_addr().unwrap().ip().is_loopback());
+}
+
+#[test]
+fn builder_invalid_uuid() {
+    for uuid in ["XXXXXXXX", "ABCDEF1", "ABCDEF123", "ABCDEF1ö"] {
+        let err = KMBoxNetConfig::builder().uuid(uuid).build().unwrap_err();
+        assert_eq!(err.field, "uuid", "{uuid}");
+    }
+}
+
+#[test]
+fn builder_invalid_ip() {
+    let err = KMBoxNetConfig::builder()
+        .ip("::1")
+        .uuid("ABCDEF12")
+        .build()
+        .unwrap_err();
+    assert_eq!(err.field, "ip");
+    let err = KMBoxNetConfig::builder()
+        .ip("not a host name")
+        .uuid("ABCDEF12")
+        .build()
+        .unwrap_err();
+    assert_eq!(err.field, "ip");
+}
+
+#[test]
+fn builder_invalid_port_timeout_local_addr() {
+    let builder = KMBoxNetConfig::builder().uuid("ABCDEF12");
+    let err = builder.clone().port(0).build().unwrap_err();
+    assert_eq!(err.field, "port");
+    let err = builder.clone().timeout(Duration::ZERO).build().unwrap_err();
+    assert_eq!(err.field, "timeout");
+    let err = builder
+        .local_addr("[::1]:0".parse().unwrap())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.field, "local_addr");
+}
+
+#[test]
+fn connect_with_invalid_config_does_not_panic() {
+    let err = KMBoxNet::new(KMBoxNetConfig::new("not an ip", 16824, "ABCDEF12")).unwrap_err();
+    assert_eq!(err.0.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn builder_socket_options() {
+    let config = KMBoxNetConfig::builder()
+        .uuid("ABCDEF12")
+        .dscp(46)
+        .send_buffer_size(1 << 16)
+        .recv_buffer_size(1 << 16)
+        .build()
+        .unwrap();
+    assert_eq!(config.socket.tos, Some(46 << 2));
+    let err = KMBoxNetConfig::builder()
+        .uuid("ABCDEF12")
+        .dscp(64)
+        .build()
+        .unwrap_err();
+    assert_eq!(err.field, "socket.tos");
+    let err = KMBoxNetConfig::builder()
+        .uuid("ABCDEF12")
+        .tos(0x10)
+        .dscp(46)
+        .build()
+        .unwrap_err();
+    assert_eq!(err.field, "socket.tos");
+    let err = KMBoxNetConfig::builder()
+        .uuid("ABCDEF12")
+        .recv_buffer_size(0)
+        .build()
+        .unwrap_err();
+    assert_eq!(err.field, "socket.recv_buffer_size");
+}
+
+#[test]
+fn monitor_addr() {
+    let config = KMBoxNetConfig::default_with_uuid("ABCDEF12");
+    let socket_addr = config.socket_addr().unwrap();
+    assert_eq!(
+        config.monitor_addr(socket_addr).unwrap(),
+        "0.0.0.0:16825".parse::<SocketAddr>().unwrap()
+    );
+    let config = config.set_monitor_addr("10.0.0.1:5000".parse().unwrap());
+    assert_eq!(
+        config.monitor_addr(socket_addr).unwrap(),
+        "10.0.0.1:5000".parse::<SocketAddr>().unwrap()
+    );
+    let err = KMBoxNetConfig::builder()
+        .uuid("ABCDEF12")
+        .monitor_addr("10.0.0.1:0".parse().unwrap())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.field, "monitor_addr");
+}
+
+#[test]
+fn default_local_ports_do_not_wrap() {
+    let config = KMBoxNetConfig::new("192.168.2.188", 65535, "ABCDEF12");
+    let socket_addr = config.socket_addr().unwrap();
+    assert_eq!(
+        config.monitor_addr(socket_addr).unwrap_err().field,
+        "monitor_addr"
+    );
+    assert_eq!(config.validate().unwrap_err().field, "monitor_addr");
+    let config = KMBoxNetConfig::new("192.168.2.188", 65534, "ABCDEF12");
+    assert_eq!(config.validate().unwrap_err().field, "debug_addr");
+    let config = config.set_debug_addr("0.0.0.0:0".parse().unwrap());
+    assert!(config.validate().is_ok());
+}
+
+#[test]
+fn monitor_with_options_binds() {
+    let options = KMBoxNetSocketOptions {
+        tos: Some(0x10),
+        recv_buffer_size: Some(1 << 16),
+        ..Default::default()
+    };
+    let mut monitor =
+        KMBoxNetMonitor::with_options("127.0.0.1:0".parse().unwrap(), &options).unwrap();
+    monitor.bind().unwrap();
+}
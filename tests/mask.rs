@@ -0,0 +1,48 @@
+use input_middleware::devices::kmbox_net::{cmd::CMD, KMBoxNet, KMBoxNetEmulator, KMBoxNetMask};
+
+#[test]
+fn mask_bits_combine_and_toggle() {
+    let mask = KMBoxNetMask::LEFT | KMBoxNetMask::WHEEL;
+    assert_eq!(mask.bits(), 0x81);
+    assert!(mask.contains(KMBoxNetMask::LEFT));
+    assert!(!mask.contains(KMBoxNetMask::RIGHT));
+    assert_eq!(mask.toggle(KMBoxNetMask::LEFT), KMBoxNetMask::WHEEL);
+    assert!(KMBoxNetMask::NONE.is_empty());
+}
+
+#[test]
+fn mask_and_unmask_are_sent() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.mask_mouse(KMBoxNetMask::X | KMBoxNetMask::Y).unwrap();
+    assert_eq!(km.mouse_mask(), KMBoxNetMask::X | KMBoxNetMask::Y);
+    assert_eq!(kmbox.state().mask, 0x60);
+    km.unmask_all().unwrap();
+    assert!(km.mouse_mask().is_empty());
+
+    let state = kmbox.state();
+    assert_eq!(state.mask, 0);
+    assert_eq!(state.commands[1].cmd, CMD::MASK_MOUSE);
+    assert_eq!(state.commands[1].rand, 0x60);
+    assert_eq!(state.commands[2].cmd, CMD::UNMASK_ALL);
+    assert_eq!(state.commands[2].rand, 0);
+}
+
+#[test]
+fn drop_unmasks() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.mask_mouse(KMBoxNetMask::LEFT).unwrap();
+    drop(km);
+    let state = kmbox.state();
+    assert_eq!(state.commands.last().unwrap().cmd, CMD::UNMASK_ALL);
+    assert_eq!(state.mask, 0);
+}
+
+#[test]
+fn ping_measures_round_trip() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.ping().unwrap();
+    assert_eq!(kmbox.state().commands[1].cmd, CMD::CONNECT);
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use std::time::{Duration, Instant};
+
+use common::events;
+use input_middleware::devices::recorder::RecorderDevice;
+use input_middleware::input_macro::MacroEvent;
+use input_middleware::motion::{Curve, Easing, Motion};
+
+fn sum(deltas: &[[i32; 2]]) -> [i32; 2] {
+    deltas
+        .iter()
+        .fold([0, 0], |t, d| [t[0] + d[0], t[1] + d[1]])
+}
+
+#[test]
+fn every_curve_ends_on_target() {
+    let curves = [
+        Curve::Linear,
+        Curve::QuadraticBezier([50.0, -300.0]),
+        Curve::CubicBezier([-100.0, 40.0], [500.0, 300.0]),
+        Curve::Spline(vec![[100.0, 100.0], [200.0, -50.0], [250.0, 0.0]]),
+    ];
+    for curve in curves {
+        for easing in [
+            Easing::Linear,
+            Easing::EaseIn,
+            Easing::EaseOut,
+            Easing::EaseInOut,
+        ] {
+            let motion = Motion::new([10.4, 20.0], [310.0, -79.6])
+                .set_curve(curve.clone())
+                .set_easing(easing);
+            assert_eq!(sum(&motion.deltas()), [300, -100], "{curve:?} {easing:?}");
+        }
+    }
+}
+
+#[test]
+fn samples_follow_duration_and_rate() {
+    let motion = Motion::relative([100.0, 0.0])
+        .set_duration(Duration::from_millis(500))
+        .set_sample_rate(100);
+    assert_eq!(motion.steps(), 50);
+    assert_eq!(motion.interval(), Duration::from_millis(10));
+    assert_eq!(motion.deltas(), vec![[2, 0]; 50]);
+    assert_eq!(
+        Motion::relative([5.0, 5.0])
+            .set_duration(Duration::ZERO)
+            .deltas(),
+        vec![[5, 5]]
+    );
+}
+
+#[test]
+fn easing_changes_speed() {
+    let deltas = Motion::relative([1000.0, 0.0])
+        .set_easing(Easing::EaseIn)
+        .deltas();
+    assert!(deltas[0][0] < deltas[deltas.len() - 1][0]);
+    let deltas = Motion::relative([1000.0, 0.0])
+        .set_easing(Easing::EaseOut)
+        .deltas();
+    assert!(deltas[0][0] > deltas[deltas.len() - 1][0]);
+}
+
+#[test]
+fn spline_passes_through_waypoints() {
+    let curve = Curve::Spline(vec![[100.0, 50.0]]);
+    assert_eq!(curve.point([0.0, 0.0], [200.0, 0.0], 0.5), [100.0, 50.0]);
+}
+
+#[test]
+fn play_sends_deltas_on_time() {
+    let mut device = RecorderDevice::new();
+    let motion = Motion::relative([40.0, -20.0])
+        .set_duration(Duration::from_millis(100))
+        .set_sample_rate(200);
+    let begin = Instant::now();
+    motion.play(&mut device).unwrap();
+    let elapsed = begin.elapsed();
+    // the last sample is due at the end of the duration
+    assert!(elapsed >= Duration::from_millis(100), "{elapsed:?}");
+    let moves: Vec<_> = events(&device)
+        .into_iter()
+        .map(|e| match e {
+            MacroEvent::Move { x, y } => [x, y],
+            e => panic!("unexpected {e:?}"),
+        })
+        .collect();
+    assert_eq!(sum(&moves), [40, -20]);
+}
+
+#[test]
+fn too_many_samples_are_rejected() {
+    let motion = Motion::relative([1.0, 0.0])
+        .set_duration(Duration::from_secs(u64::from(u32::MAX)))
+        .set_sample_rate(2);
+    // the interval does not truncate the sample count
+    assert_eq!(motion.interval(), Duration::from_millis(500));
+    let mut device = RecorderDevice::new();
+    assert!(motion.play(&mut device).is_err());
+    assert!(device.events().is_empty());
+}
@@ -0,0 +1,63 @@
+use std::time::{Duration, Instant};
+
+use input_middleware::devices::kmbox_net::{
+    cmd::CMD, KMBoxNet, KMBoxNetEmulator, KMBoxNetMask, EMULATOR_REBOOT_TIME,
+};
+
+#[test]
+fn reboot_reconnects_and_restores_the_state() {
+    let emulator = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(emulator.config()).unwrap();
+    km.mask_mouse(KMBoxNetMask::LEFT).unwrap();
+    let _monitor = km.monitor().unwrap();
+
+    let begin = Instant::now();
+    // the emulator does not answer a reboot like the kmbox
+    let reboot = km.reboot().unwrap();
+    assert!(begin.elapsed() < Duration::from_secs(1));
+    let km = reboot.wait().unwrap();
+    assert!(begin.elapsed() >= EMULATOR_REBOOT_TIME);
+
+    let state = emulator.state();
+    assert_eq!(state.reboots, 1);
+    assert_eq!(state.mask, u32::from(KMBoxNetMask::LEFT.bits()));
+    assert!(state.monitor_port.is_some());
+    assert_eq!(km.mouse_mask(), KMBoxNetMask::LEFT);
+    let after: Vec<_> = state
+        .commands
+        .iter()
+        .skip_while(|command| command.cmd != CMD::REBOOT)
+        .map(|command| command.cmd)
+        .collect();
+    assert_eq!(
+        after,
+        [CMD::REBOOT, CMD::CONNECT, CMD::MASK_MOUSE, CMD::MONITOR]
+    );
+
+    drop(km);
+    assert_eq!(emulator.state().mask, 0);
+}
+
+#[test]
+fn reboot_without_restore() {
+    let emulator = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(emulator.config()).unwrap();
+    km.mask_mouse(KMBoxNetMask::RIGHT).unwrap();
+    let km = km.reboot().unwrap().set_restore(false).wait().unwrap();
+
+    assert_eq!(emulator.state().mask, 0);
+    assert_eq!(km.mouse_mask(), KMBoxNetMask::NONE);
+}
+
+#[test]
+fn reboot_wait_times_out() {
+    let emulator = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let km = KMBoxNet::new(emulator.config()).unwrap();
+    let error = km
+        .reboot()
+        .unwrap()
+        .set_timeout(Duration::from_millis(100))
+        .wait()
+        .unwrap_err();
+    assert_eq!(error.0.kind(), std::io::ErrorKind::TimedOut);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use std::{
+    panic,
+    sync::{Arc, Mutex},
+};
+
+use common::{events, held_keys, key};
+use input_middleware::{
+    button_state::{ButtonState, MouseButton},
+    devices::{
+        kmbox_net::{cmd::CMD, KMBoxNet, KMBoxNetEmulator},
+        recorder::RecorderDevice,
+    },
+    keyboardkeys::KeyboardKey,
+    release::release_all_on_panic,
+    InputMiddlewareDeviceAction,
+};
+
+#[test]
+fn tracks_held_keys_and_buttons() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.keyboard_keydown(KeyboardKey::KEY_LEFTSHIFT).unwrap();
+    km.keyboard_keydown(KeyboardKey::KEY_A).unwrap();
+    let state = kmbox.state();
+    assert_eq!(state.modifiers, 0x02);
+    assert_eq!(state.keys[0], 0x04);
+    km.mouse_left_click(ButtonState::Pressed).unwrap();
+    km.mouse_right_click(ButtonState::Pressed).unwrap();
+    // the right click keeps the left button held
+    assert_eq!(kmbox.state().buttons, 0x03);
+    km.mouse_right_click(ButtonState::Released).unwrap();
+    assert_eq!(kmbox.state().buttons, 0x01);
+    assert_eq!(
+        km.pressed_keys(),
+        vec![KeyboardKey::KEY_LEFTSHIFT, KeyboardKey::KEY_A]
+    );
+    assert_eq!(km.pressed_buttons(), vec![MouseButton::Left]);
+    // connect, 2 keys, 3 clicks
+    assert_eq!(kmbox.state().commands.len(), 6);
+}
+
+#[test]
+fn move_does_not_repeat_and_keeps_buttons() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.mouse_move([10, -5]).unwrap();
+    assert_eq!(kmbox.state().position, [10, -5]);
+    km.mouse_left_click(ButtonState::Pressed).unwrap();
+    let state = kmbox.state();
+    assert_eq!(state.position, [10, -5]);
+    assert_eq!(state.buttons, 0x01);
+    km.keyboard_keydown(KeyboardKey::KEY_B).unwrap();
+    km.mouse_move([1, 1]).unwrap();
+    let state = kmbox.state();
+    assert_eq!(state.position, [11, -4]);
+    assert_eq!(state.buttons, 0x01);
+}
+
+#[test]
+fn drop_releases_held_input() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.keyboard_keydown(KeyboardKey::KEY_LEFTCONTROL).unwrap();
+    km.mouse_middle_click(ButtonState::Pressed).unwrap();
+    drop(km);
+
+    let state = kmbox.state();
+    let cmds: Vec<_> = state.commands[3..].iter().map(|c| c.cmd).collect();
+    assert_eq!(cmds, [CMD::KEYBOARD_ALL, CMD::MOUSE_MOVE]);
+    assert_eq!(state.modifiers, 0);
+    assert_eq!(state.keys, [0; 10]);
+    assert_eq!(state.buttons, 0);
+}
+
+#[test]
+fn drop_without_held_input_sends_nothing() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.keyboard_keydown(KeyboardKey::KEY_A).unwrap();
+    km.keyboard_keyup(KeyboardKey::KEY_A).unwrap();
+    drop(km);
+    assert_eq!(kmbox.state().commands.len(), 3);
+}
+
+#[test]
+fn panic_hook_releases_held_input() {
+    let device = Arc::new(Mutex::new(RecorderDevice::new()));
+    let guard = release_all_on_panic(&device);
+    let panicking = device.clone();
+    let result = std::thread::spawn(move || {
+        panicking
+            .lock()
+            .unwrap()
+            .keyboard_keydown(KeyboardKey::KEY_W)
+            .unwrap();
+        panic!("bot crashed");
+    })
+    .join();
+    assert!(result.is_err());
+    assert_eq!(
+        events(&device.lock().unwrap()),
+        vec![
+            key(KeyboardKey::KEY_W, true),
+            key(KeyboardKey::KEY_W, false)
+        ]
+    );
+    assert!(held_keys(&device.lock().unwrap()).is_empty());
+
+    // once the guard is gone a panic leaves the device alone
+    drop(guard);
+    let panicking = device.clone();
+    let result = panic::catch_unwind(move || {
+        panicking
+            .lock()
+            .unwrap()
+            .keyboard_keydown(KeyboardKey::KEY_S)
+            .unwrap();
+        panic!("bot crashed again");
+    });
+    assert!(result.is_err());
+    assert_eq!(
+        events(&device.lock().unwrap()).last(),
+        Some(&key(KeyboardKey::KEY_S, true))
+    );
+}
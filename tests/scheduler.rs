@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+
+use input_middleware::button_state::MouseButton;
+use input_middleware::devices::recorder::RecorderDevice;
+use input_middleware::input_macro::{InputAction, MacroEvent};
+use input_middleware::keyboardkeys::KeyboardKey;
+use input_middleware::scheduler::Scheduler;
+
+const PRESS: MacroEvent = MacroEvent::Button {
+    button: MouseButton::Left,
+    pressed: true,
+};
+const RELEASE: MacroEvent = MacroEvent::Button {
+    button: MouseButton::Left,
+    pressed: false,
+};
+
+fn sent(device: &RecorderDevice) -> Vec<MacroEvent> {
+    device.events().iter().map(|timed| timed.event).collect()
+}
+
+#[test]
+fn inputs_go_out_in_time_order() {
+    let mut scheduler = Scheduler::start(RecorderDevice::new());
+    let start = Instant::now() + Duration::from_millis(20);
+    let release = scheduler.schedule(start + Duration::from_millis(60), RELEASE);
+    let press = scheduler.schedule(start + Duration::from_millis(25), PRESS);
+    let moved = scheduler.schedule(start, MacroEvent::Move { x: 4, y: 2 });
+
+    let (device, reports) = scheduler.finish();
+    assert_eq!(
+        sent(&device),
+        [MacroEvent::Move { x: 4, y: 2 }, PRESS, RELEASE]
+    );
+    let ids: Vec<_> = reports.iter().map(|report| report.id).collect();
+    assert_eq!(ids, [moved, press, release]);
+    for report in &reports {
+        assert!(report.result.is_ok());
+        assert!(report.sent >= report.scheduled);
+        assert!(report.jitter() < Duration::from_millis(20), "{report:?}");
+    }
+}
+
+#[test]
+fn same_instant_keeps_the_schedule_order() {
+    let mut scheduler = Scheduler::start(RecorderDevice::new());
+    let at = Instant::now() + Duration::from_millis(5);
+    let keys = [KeyboardKey::KEY_A, KeyboardKey::KEY_B, KeyboardKey::KEY_C];
+    scheduler.schedule_all(keys.map(|key| {
+        let event = MacroEvent::Key { key, pressed: true };
+        (at, InputAction::from(event))
+    }));
+
+    let (device, reports) = scheduler.finish();
+    assert_eq!(reports.len(), 3);
+    let pressed: Vec<_> = sent(&device)
+        .into_iter()
+        .map(|event| match event {
+            MacroEvent::Key { key, .. } => key,
+            other => panic!("unexpected {other:?}"),
+        })
+        .collect();
+    assert_eq!(pressed, keys);
+}
+
+#[test]
+fn cancel_drops_pending_inputs_and_releases() {
+    let mut scheduler = Scheduler::start(RecorderDevice::new());
+    let now = Instant::now();
+    scheduler.schedule(now, PRESS);
+    scheduler.schedule(now + Duration::from_secs(60), RELEASE);
+    let report = scheduler.recv_report(Duration::from_secs(5)).unwrap();
+    assert_eq!(report.action, InputAction::Event(PRESS));
+
+    let device = scheduler.cancel();
+    // the release comes from release_all, not from the dropped input
+    assert_eq!(sent(&device), [PRESS, RELEASE]);
+}
+
+#[test]
+fn past_instants_are_sent_right_away() {
+    let mut scheduler = Scheduler::start(RecorderDevice::new());
+    let Some(past) = Instant::now().checked_sub(Duration::from_millis(50)) else {
+        return;
+    };
+    scheduler.schedule(past, InputAction::ReleaseAll);
+    let report = scheduler.recv_report(Duration::from_secs(5)).unwrap();
+    assert!(report.jitter() >= Duration::from_millis(50));
+}
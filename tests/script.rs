@@ -0,0 +1,170 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{events, Failing};
+use input_middleware::{
+    button_state::MouseButton,
+    devices::{
+        kmbox_net::{KMBoxNet, KMBoxNetEmulator},
+        recorder::RecorderDevice,
+    },
+    errors::InputMiddlewareScriptError,
+    input_macro::MacroEvent,
+    keyboardkeys::KeyboardKey,
+    script::Script,
+    typing::TypingOptions,
+    InputMiddlewareDeviceAction,
+};
+
+#[test]
+fn dry_run_records_with_virtual_time() {
+    let script: Script = r#"
+        # drag a box
+        let size = 20
+        click left down
+        repeat 2
+            move size, -size / 2   # half as high
+            wait 1s
+        end
+        click left up
+        wheel -(1 + 2)
+        key ctrl+c
+        type "a#"
+    "#
+    .parse()
+    .unwrap();
+    let events = script.dry_run().unwrap().events;
+    let at: Vec<_> = events.iter().map(|e| e.at.as_millis()).collect();
+    assert_eq!(at[..3], [0, 0, 1000]);
+    assert!(at[3..].iter().all(|at| *at == 2000));
+    let events: Vec<_> = events.into_iter().map(|e| e.event).collect();
+    let key = |key, pressed| MacroEvent::Key { key, pressed };
+    assert_eq!(
+        events,
+        [
+            MacroEvent::Button {
+                button: MouseButton::Left,
+                pressed: true
+            },
+            MacroEvent::Move { x: 20, y: -10 },
+            MacroEvent::Move { x: 20, y: -10 },
+            MacroEvent::Button {
+                button: MouseButton::Left,
+                pressed: false
+            },
+            MacroEvent::Wheel { delta: -3 },
+            key(KeyboardKey::KEY_LEFTCONTROL, true),
+            key(KeyboardKey::KEY_C, true),
+            key(KeyboardKey::KEY_C, false),
+            key(KeyboardKey::KEY_LEFTCONTROL, false),
+            key(KeyboardKey::KEY_A, true),
+            key(KeyboardKey::KEY_A, false),
+            key(KeyboardKey::KEY_LEFTSHIFT, true),
+            key(KeyboardKey::KEY_3_NUMBER_SIGN, true),
+            key(KeyboardKey::KEY_3_NUMBER_SIGN, false),
+            key(KeyboardKey::KEY_LEFTSHIFT, false),
+        ]
+    );
+}
+
+#[test]
+fn runs_on_boxed_device() {
+    let script = Script::parse("key shift down\nwait 5\nkey shift up").unwrap();
+    let mut device: Box<dyn InputMiddlewareDeviceAction> = Box::new(RecorderDevice::new());
+    script.run(&mut *device, &TypingOptions::default()).unwrap();
+}
+
+#[test]
+fn variables_and_loops() {
+    let script = Script::parse("let n = 3\nrepeat n\nmove n 0\nlet n = n - 1\nend").unwrap();
+    let mut device = RecorderDevice::new();
+    script.run(&mut device, &TypingOptions::default()).unwrap();
+    assert_eq!(
+        events(&device),
+        [
+            MacroEvent::Move { x: 3, y: 0 },
+            MacroEvent::Move { x: 2, y: 0 },
+            MacroEvent::Move { x: 1, y: 0 }
+        ]
+    );
+}
+
+#[test]
+fn syntax_errors_have_line_numbers() {
+    for (source, line, message) in [
+        ("move 1 2\njump 3", 2, "unknown command"),
+        ("\n\nmove 1", 3, "usage: move"),
+        ("click left sideways", 1, "expected down or up"),
+        ("key ctrl+nope", 1, "unknown key"),
+        ("type hello", 1, "double quotes"),
+        ("repeat 2\nmove 1 1", 1, "repeat without end"),
+        ("move 1 1\nend", 2, "end without repeat"),
+        ("let 1x = 2", 1, "invalid variable name"),
+        ("wait (5", 1, "missing )"),
+    ] {
+        let err = Script::parse(source).unwrap_err();
+        assert!(
+            matches!(err, InputMiddlewareScriptError::Syntax { .. }),
+            "{source:?}"
+        );
+        assert_eq!(err.line(), line, "{source:?}");
+        assert!(err.to_string().contains(message), "{source:?}: {err}");
+        assert!(err.to_string().starts_with(&format!("line {line}:")));
+    }
+}
+
+#[test]
+fn runtime_errors_have_line_numbers() {
+    let script = Script::parse("move 1 1\nmove x 1").unwrap();
+    let err = script.dry_run().unwrap_err();
+    assert!(matches!(
+        err,
+        InputMiddlewareScriptError::Runtime { line: 2, .. }
+    ));
+    let err = Script::parse("wait 1 / 0").unwrap().dry_run().unwrap_err();
+    assert_eq!(err.to_string(), "line 1: division by zero");
+    let err = Script::parse("wheel 1\nwheel -2147483648")
+        .unwrap()
+        .dry_run()
+        .unwrap_err();
+    assert_eq!(err.to_string(), "line 2: -2147483648 is out of range");
+}
+
+#[test]
+fn send_errors_have_line_numbers() {
+    let script = Script::parse("move 1 1\n\nmove 2 2").unwrap();
+    let mut device = Failing::new(RecorderDevice::new(), 1);
+    let err = script
+        .run(&mut device, &TypingOptions::default())
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        InputMiddlewareScriptError::Send { line: 3, .. }
+    ));
+}
+
+#[test]
+fn wait_units() {
+    let events = Script::parse("wait 2s\nmove 1 1\nwait 250ms\nwait 250\nmove 1 1")
+        .unwrap()
+        .dry_run()
+        .unwrap()
+        .events;
+    assert_eq!(events[0].at, Duration::from_secs(2));
+    assert_eq!(events[1].at, Duration::from_millis(2500));
+}
+
+#[test]
+fn clicks_side_buttons_on_the_emulator() {
+    let emulator = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(emulator.config()).unwrap();
+    let script = Script::parse("click side1 down").unwrap();
+    script.run(&mut km, &TypingOptions::default()).unwrap();
+    assert_eq!(emulator.state().buttons, 0x08);
+    Script::parse("click side1")
+        .unwrap()
+        .run(&mut km, &TypingOptions::default())
+        .unwrap();
+    assert_eq!(emulator.state().buttons, 0);
+}
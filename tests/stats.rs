@@ -0,0 +1,93 @@
+use std::{thread, time::Duration};
+
+use input_middleware::button_state::ButtonState;
+use input_middleware::devices::kmbox_net::{
+    cmd::CMD, KMBoxNet, KMBoxNetEmulator, RttHistogram, SendOutcome,
+};
+
+#[test]
+fn histogram_buckets_and_quantiles() {
+    let mut rtt = RttHistogram::default();
+    assert_eq!(rtt.quantile(0.5), None);
+    for micros in [80, 90, 300, 700, 4000] {
+        rtt.record(Duration::from_micros(micros));
+    }
+    assert_eq!(rtt.count(), 5);
+    assert_eq!(rtt.min(), Some(Duration::from_micros(80)));
+    assert_eq!(rtt.max(), Some(Duration::from_micros(4000)));
+    assert_eq!(rtt.mean(), Some(Duration::from_micros(1034)));
+    assert_eq!(rtt.quantile(0.4), Some(Duration::from_micros(100)));
+    assert_eq!(rtt.quantile(0.6), Some(Duration::from_micros(500)));
+    // capped by the slowest rtt instead of the 5ms bucket bound
+    assert_eq!(rtt.quantile(1.0), Some(Duration::from_micros(4000)));
+    assert_eq!(rtt.buckets().map(|(_, n)| n).sum::<u64>(), 5);
+}
+
+#[test]
+fn send_records_acknowledged_commands() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.mouse_move([1, 1]).unwrap();
+    km.mouse_move([2, 2]).unwrap();
+    km.mouse_left_click(ButtonState::Pressed).unwrap();
+
+    let stats = km.stats();
+    let moves = &stats.commands[&CMD::MOUSE_MOVE];
+    assert_eq!(moves.sent, 2);
+    assert_eq!(moves.ok, 2);
+    assert_eq!(moves.rtt.count(), 2);
+    assert_eq!(stats.total().sent, 3);
+    assert!(stats.packets_per_sec() > 0.0);
+
+    km.reset_stats();
+    assert!(km.stats().commands.is_empty());
+}
+
+#[test]
+fn timeouts_are_counted() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.set_timeout(Duration::from_millis(20)).unwrap();
+    kmbox.set_latency(Duration::from_millis(200));
+    assert!(km.ping().is_err());
+
+    let connect = &km.stats().commands[&CMD::CONNECT];
+    assert_eq!(connect.sent, 1);
+    assert_eq!(connect.timeouts, 1);
+    assert_eq!(connect.rtt.count(), 0);
+    assert_eq!(SendOutcome::Timeout.as_str(), "timeout");
+}
+
+#[test]
+fn late_replies_are_dropped() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.set_timeout(Duration::from_millis(50)).unwrap();
+    kmbox.set_latency(Duration::from_millis(100));
+    assert!(km.mouse_move([1, 1]).is_err());
+    kmbox.set_latency(Duration::ZERO);
+    // the late reply is waiting in the socket when the next command is sent
+    thread::sleep(Duration::from_millis(100));
+    km.mouse_move([2, 2]).unwrap();
+    km.mouse_move([3, 3]).unwrap();
+
+    let moves = &km.stats().commands[&CMD::MOUSE_MOVE];
+    assert_eq!(moves.sent, 3);
+    assert_eq!(moves.timeouts, 1);
+    assert_eq!(moves.ok, 2);
+    assert_eq!(moves.mismatches, 0);
+    // the dropped reply does not stand in for the answer
+    assert!(moves.rtt.max().unwrap() < Duration::from_millis(50));
+}
+
+#[test]
+fn prometheus_text() {
+    let kmbox = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut km = KMBoxNet::new(kmbox.config()).unwrap();
+    km.ping().unwrap();
+    let text = km.stats().to_prometheus();
+    assert!(text.contains("# TYPE kmbox_net_commands_total counter"));
+    assert!(text.contains("kmbox_net_commands_total{cmd=\"CONNECT\",result=\"ok\"} 1"));
+    assert!(text.contains("kmbox_net_rtt_seconds_bucket{cmd=\"CONNECT\",le=\"+Inf\"} 1"));
+    assert!(text.contains("kmbox_net_rtt_seconds_count{cmd=\"CONNECT\"} 1"));
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use common::{events, Failing};
+use input_middleware::{
+    devices::recorder::RecorderDevice,
+    input_macro::MacroEvent,
+    subpixel::{SubPixel, SubPixelAccumulator},
+    InputMiddlewareDeviceAction,
+};
+
+fn moved(device: &RecorderDevice) -> [i32; 2] {
+    events(device)
+        .into_iter()
+        .fold([0, 0], |total, event| match event {
+            MacroEvent::Move { x, y } => [total[0] + x, total[1] + y],
+            _ => total,
+        })
+}
+
+#[test]
+fn fractions_add_up_without_drift() {
+    let mut device = SubPixel::new(RecorderDevice::new());
+    for _ in 0..1000 {
+        device.mouse_move_f(0.3, -0.7).unwrap();
+    }
+    device.mouse_move_flush().unwrap();
+    let device = device.into_inner();
+    assert_eq!(moved(&device), [300, -700]);
+    // nothing is sent while the movement stays below a whole count
+    assert!(device.events().len() < 1000);
+}
+
+#[test]
+fn scale_is_applied_before_rounding() {
+    let mut device = SubPixel::new(RecorderDevice::new()).set_scale(2.5);
+    device.mouse_move_f(1.0, 0.1).unwrap();
+    assert_eq!(
+        events(device.inner()),
+        vec![MacroEvent::Move { x: 2, y: 0 }]
+    );
+    let remainder = device.accumulator().remainder();
+    assert!((remainder[0] - 0.5).abs() < 1e-9 && (remainder[1] - 0.25).abs() < 1e-9);
+}
+
+#[test]
+fn flush_sends_rounded_remainder() {
+    let mut device = SubPixel::new(RecorderDevice::new());
+    device.mouse_move_f(1.6, -0.4).unwrap();
+    device.mouse_move_flush().unwrap();
+    assert_eq!(
+        events(device.inner()),
+        vec![
+            MacroEvent::Move { x: 1, y: 0 },
+            MacroEvent::Move { x: 1, y: 0 }
+        ]
+    );
+    assert_eq!(device.accumulator().remainder(), [0.0, 0.0]);
+    // an empty remainder sends nothing
+    device.mouse_move_flush().unwrap();
+    assert_eq!(device.inner().events().len(), 2);
+}
+
+#[test]
+fn reset_drops_remainder() {
+    let mut device = SubPixel::new(RecorderDevice::new());
+    device.mouse_move_f(0.9, 0.9).unwrap();
+    device.mouse_move_reset();
+    device.mouse_move_f(0.9, 0.9).unwrap();
+    assert!(device.inner().events().is_empty());
+}
+
+#[test]
+fn whole_moves_pass_through() {
+    let mut device = SubPixel::new(RecorderDevice::new());
+    device.mouse_move_f(0.5, 0.0).unwrap();
+    device.mouse_move([3, -1]).unwrap();
+    assert_eq!(
+        events(device.inner()),
+        vec![MacroEvent::Move { x: 3, y: -1 }]
+    );
+    assert_eq!(device.accumulator().remainder(), [0.5, 0.0]);
+}
+
+#[test]
+fn failed_move_keeps_remainder() {
+    let mut device = SubPixel::new(Failing::new(RecorderDevice::new(), 0));
+    device.mouse_move_f(0.5, 0.0).unwrap();
+    device.mouse_move_f(0.75, 0.0).unwrap_err();
+    assert_eq!(device.accumulator().remainder(), [0.5, 0.0]);
+    device.mouse_move_f(0.75, 0.0).unwrap();
+    assert_eq!(
+        events(&device.inner().device),
+        vec![MacroEvent::Move { x: 1, y: 0 }]
+    );
+    assert_eq!(device.accumulator().remainder(), [0.25, 0.0]);
+}
+
+#[test]
+fn non_finite_delta_is_ignored() {
+    let mut accumulator = SubPixelAccumulator::default();
+    accumulator.accumulate(0.5, 0.5);
+    assert_eq!(accumulator.accumulate(f64::NAN, f64::INFINITY), [0, 0]);
+    assert_eq!(accumulator.remainder(), [0.5, 0.5]);
+}
@@ -0,0 +1,144 @@
+use std::time::{Duration, Instant};
+
+use input_middleware::button_state::{ButtonState, MouseButton, MwheelState};
+use input_middleware::devices::kmbox_net::{cmd::CMD, KMBoxNet, KMBoxNetEmulator};
+use input_middleware::devices::recorder::RecorderDevice;
+use input_middleware::input_macro::{InputAction, MacroEvent};
+use input_middleware::keyboardkeys::KeyboardKey;
+use input_middleware::throttle::Throttled;
+use input_middleware::InputMiddlewareDeviceAction;
+
+fn sent(device: &Throttled<RecorderDevice>) -> Vec<MacroEvent> {
+    device
+        .inner()
+        .events()
+        .iter()
+        .map(|timed| timed.event)
+        .collect()
+}
+
+#[test]
+fn burst_of_moves_is_coalesced() {
+    let mut device = Throttled::new(RecorderDevice::new(), 50.0);
+    for _ in 0..100 {
+        device.mouse_move([1, -1]).unwrap();
+    }
+    device.mouse_wheel(MwheelState::Up(1)).unwrap();
+    device.mouse_wheel(MwheelState::Down(3)).unwrap();
+    assert_eq!(device.queue_depth(), 2);
+    assert_eq!(device.coalesced(), 99);
+
+    device.flush().unwrap();
+    assert_eq!(device.queue_depth(), 0);
+    assert_eq!(
+        sent(&device),
+        [
+            MacroEvent::Move { x: 1, y: -1 },
+            MacroEvent::Move { x: 99, y: -99 },
+            MacroEvent::Wheel { delta: -2 },
+        ]
+    );
+}
+
+#[test]
+fn transitions_keep_their_place_between_moves() {
+    let mut device = Throttled::new(RecorderDevice::new(), 50.0);
+    device.mouse_move([1, 0]).unwrap();
+    device.mouse_move([1, 0]).unwrap();
+    device.mouse_move([1, 0]).unwrap();
+    device.mouse_left_click(ButtonState::Pressed).unwrap();
+    device.mouse_move([0, 2]).unwrap();
+    device.keyboard_keydown(KeyboardKey::KEY_A).unwrap();
+    device.mouse_left_click(ButtonState::Released).unwrap();
+    device.mouse_move([0, 3]).unwrap();
+    assert_eq!(device.peak_queue_depth(), 6);
+
+    device.flush().unwrap();
+    assert_eq!(
+        sent(&device),
+        [
+            MacroEvent::Move { x: 1, y: 0 },
+            MacroEvent::Move { x: 2, y: 0 },
+            MacroEvent::Button {
+                button: MouseButton::Left,
+                pressed: true
+            },
+            MacroEvent::Move { x: 0, y: 2 },
+            MacroEvent::Key {
+                key: KeyboardKey::KEY_A,
+                pressed: true
+            },
+            MacroEvent::Button {
+                button: MouseButton::Left,
+                pressed: false
+            },
+            MacroEvent::Move { x: 0, y: 3 },
+        ]
+    );
+}
+
+#[test]
+fn rate_is_enforced() {
+    let mut device = Throttled::new(RecorderDevice::new(), 100.0);
+    let begin = Instant::now();
+    for _ in 0..3 {
+        device.keyboard_keydown(KeyboardKey::KEY_B).unwrap();
+        device.keyboard_keyup(KeyboardKey::KEY_B).unwrap();
+    }
+    assert_eq!(device.inner().events().len(), 1);
+    device.flush().unwrap();
+    assert_eq!(device.inner().events().len(), 6);
+    assert!(begin.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn coalesce_window_holds_the_last_move() {
+    let mut device = Throttled::new(RecorderDevice::new(), f64::INFINITY)
+        .set_coalesce_window(Duration::from_millis(30));
+    device.mouse_move([2, 0]).unwrap();
+    device.mouse_move([3, 0]).unwrap();
+    assert_eq!(device.poll().unwrap(), 0);
+    assert_eq!(device.queue_depth(), 1);
+
+    std::thread::sleep(device.next_due().unwrap() - Instant::now());
+    assert_eq!(device.poll().unwrap(), 1);
+    assert_eq!(sent(&device), [MacroEvent::Move { x: 5, y: 0 }]);
+}
+
+#[test]
+fn into_inner_returns_the_queue() {
+    let mut device = Throttled::new(RecorderDevice::new(), 10.0);
+    device.mouse_move([1, 0]).unwrap();
+    device.mouse_left_click(ButtonState::Pressed).unwrap();
+    device.mouse_move([0, 2]).unwrap();
+    let (recorder, pending) = device.into_inner();
+    assert_eq!(recorder.events().len(), 1);
+    assert_eq!(
+        pending,
+        [
+            InputAction::Event(MacroEvent::Button {
+                button: MouseButton::Left,
+                pressed: true,
+            }),
+            InputAction::Event(MacroEvent::Move { x: 0, y: 2 }),
+        ]
+    );
+}
+
+#[test]
+fn drop_flushes_the_queue() {
+    let emulator = KMBoxNetEmulator::start("0000ABCD").unwrap();
+    let mut device = Throttled::new(KMBoxNet::new(emulator.config()).unwrap(), 20.0);
+    device.mouse_move([1, 0]).unwrap();
+    device.mouse_left_click(ButtonState::Pressed).unwrap();
+    device.mouse_move([0, 2]).unwrap();
+    assert_eq!(device.queue_depth(), 2);
+    drop(device);
+    let state = emulator.state();
+    assert_eq!(state.position, [1, 2]);
+    // the kmbox releases the button again when it is dropped after the flush
+    assert!(state
+        .commands
+        .iter()
+        .any(|command| command.cmd == CMD::MOUSE_LEFT));
+}
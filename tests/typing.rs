@@ -0,0 +1,109 @@
+mod common;
+
+use std::time::Duration;
+
+use common::{events, held_keys, key, Failing};
+use input_middleware::devices::recorder::RecorderDevice;
+use input_middleware::errors::InputMiddlewareTypingError;
+use input_middleware::keyboard_layout::{
+    DeLayout, FrLayout, KeyStroke, KeyboardLayout, UkLayout, UsLayout,
+};
+use input_middleware::keyboardkeys::KeyboardKey::*;
+use input_middleware::typing::TypingOptions;
+use input_middleware::InputMiddlewareDeviceAction;
+
+fn fast() -> TypingOptions {
+    TypingOptions::default()
+        .set_key_hold(Duration::ZERO)
+        .set_key_delay(Duration::ZERO)
+}
+
+#[test]
+fn type_shifted_text() {
+    let mut device = RecorderDevice::new();
+    let report = device.type_text("aB!", &fast()).unwrap();
+    assert_eq!(report.typed, 3);
+    assert!(report.unmappable.is_empty());
+    assert_eq!(
+        events(&device),
+        vec![
+            key(KEY_A, true),
+            key(KEY_A, false),
+            key(KEY_LEFTSHIFT, true),
+            key(KEY_B, true),
+            key(KEY_B, false),
+            key(KEY_LEFTSHIFT, false),
+            key(KEY_LEFTSHIFT, true),
+            key(KEY_1_EXCLAMATION_MARK, true),
+            key(KEY_1_EXCLAMATION_MARK, false),
+            key(KEY_LEFTSHIFT, false),
+        ]
+    );
+}
+
+#[test]
+fn unmappable_characters_are_reported() {
+    let mut device = RecorderDevice::new();
+    let report = device.type_text("a€b\r\n", &fast()).unwrap();
+    assert_eq!(report.typed, 3);
+    assert_eq!(report.unmappable, vec![(1, '€')]);
+
+    let mut device = RecorderDevice::new();
+    let err = device
+        .type_text("a€b", &fast().set_strict(true))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        InputMiddlewareTypingError::Unmappable { ref chars, .. } if chars == &vec![(1, '€')]
+    ));
+    assert!(device.events().is_empty());
+}
+
+#[test]
+fn keys_are_released_when_sending_fails() {
+    let mut device = Failing::new(RecorderDevice::new(), 1);
+    assert!(device.type_text("A", &fast()).is_err());
+    assert!(held_keys(&device.device).is_empty());
+}
+
+#[test]
+fn layouts() {
+    assert_eq!(
+        UkLayout.strokes('@'),
+        Some(vec![KeyStroke::shift(KEY_SINGLE_AND_DOUBLE_QUOTE)])
+    );
+    assert_eq!(DeLayout.strokes('z'), Some(vec![KeyStroke::plain(KEY_Y)]));
+    assert_eq!(DeLayout.strokes('@'), Some(vec![KeyStroke::altgr(KEY_Q)]));
+    assert_eq!(
+        DeLayout.strokes('é'),
+        Some(vec![
+            KeyStroke::plain(KEY_EQUAL_PLUS),
+            KeyStroke::plain(KEY_E)
+        ])
+    );
+    assert_eq!(
+        DeLayout.strokes('^'),
+        Some(vec![
+            KeyStroke::plain(KEY_GRAVE_ACCENT_AND_TILDE),
+            KeyStroke::plain(KEY_SPACEBAR)
+        ])
+    );
+    assert_eq!(FrLayout.strokes('a'), Some(vec![KeyStroke::plain(KEY_Q)]));
+    assert_eq!(
+        FrLayout.strokes('1'),
+        Some(vec![KeyStroke::shift(KEY_1_EXCLAMATION_MARK)])
+    );
+    assert_eq!(FrLayout.strokes(','), Some(vec![KeyStroke::plain(KEY_M)]));
+    assert_eq!(FrLayout.strokes('É'), None);
+    assert_eq!(UsLayout.strokes('ä'), None);
+}
+
+#[test]
+fn every_printable_ascii_character_on_us_and_uk() {
+    for c in (' '..='~').chain(['\n', '\t']) {
+        assert!(UsLayout.strokes(c).is_some(), "{c:?} on us");
+        assert!(UkLayout.strokes(c).is_some(), "{c:?} on uk");
+        assert!(DeLayout.strokes(c).is_some(), "{c:?} on de");
+        assert!(FrLayout.strokes(c).is_some(), "{c:?} on fr");
+    }
+}